@@ -1,8 +1,9 @@
 use futures::executor::block_on;
 use grafo::{Color, MathRect, Renderer, Shape, Stroke};
 use protextinator::style::{
-    FontColor, FontFamily, FontSize, HorizontalTextAlignment, LineHeight, TextStyle, TextWrap,
-    VerticalTextAlignment,
+    FontColor, FontFamily, FontSize, HorizontalTextAlignment, Hyphens, LineHeight, ParagraphStyle,
+    TextDecoration, TextDirection, TextEffects, TextStyle, TextWrap, VerticalTextAlignment,
+    WhiteSpace, WritingMode,
 };
 use protextinator::{AlphaMode, Id, Point, Rect, TextManager};
 use std::sync::Arc;
@@ -133,9 +134,20 @@ impl<'a> App<'a> {
                 horizontal_alignment: HorizontalTextAlignment::Start,
                 vertical_alignment: VerticalTextAlignment::Start,
                 wrap: Some(TextWrap::Wrap),
+                white_space: WhiteSpace::PreWrap,
+                direction: TextDirection::Auto,
+                writing_mode: WritingMode::HorizontalTb,
+                language: None,
+                hyphens: Hyphens::Manual,
+                decoration: TextDecoration::NONE,
+                effects: TextEffects::NONE,
                 font_family: FontFamily::Name("Arial".into()),
                 weight: protextinator::style::Weight::NORMAL,
                 letter_spacing: None,
+                tab_width: 4,
+                tab_stops: None,
+                paragraph: ParagraphStyle::NONE,
+                paragraph_overrides: Default::default(),
             };
 
             // Create or update the text state
@@ -192,9 +204,20 @@ impl<'a> App<'a> {
                     horizontal_alignment: HorizontalTextAlignment::Start,
                     vertical_alignment: VerticalTextAlignment::Start,
                     wrap: Some(TextWrap::Wrap),
+                    white_space: WhiteSpace::PreWrap,
+                    direction: TextDirection::Auto,
+                    writing_mode: WritingMode::HorizontalTb,
+                    language: None,
+                    hyphens: Hyphens::Manual,
+                    decoration: TextDecoration::NONE,
+                    effects: TextEffects::NONE,
                     font_family: FontFamily::Serif,
                     weight: protextinator::style::Weight::NORMAL,
                     letter_spacing: None,
+                    tab_width: 4,
+                    tab_stops: None,
+                    paragraph: ParagraphStyle::NONE,
+                    paragraph_overrides: Default::default(),
                 };
 
                 // Create or update the stats text state
@@ -31,6 +31,15 @@ pub enum Action {
     MoveCursorUp,
     /// Insert a character or string at the cursor position.
     InsertChar(SmolStr),
+    /// Insert a tab at the cursor position. If the selection spans multiple lines, every
+    /// selected line is indented by one tab instead.
+    InsertTab,
+    /// Remove one level of indentation (a tab, or up to `tab_width` spaces) from the line with
+    /// the cursor, or from every selected line.
+    Outdent,
+    /// Switch between inserting typed characters and overwriting the text after the cursor,
+    /// see [`crate::TextState::set_overwrite_mode`].
+    ToggleOverwrite,
 }
 
 /// The result of applying an action to a text state.
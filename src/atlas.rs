@@ -0,0 +1,380 @@
+//! Glyph atlas support for GPU renderers.
+//!
+//! Instead of rasterizing every text state into its own texture, a renderer can keep all glyph
+//! bitmaps in one shared atlas texture that it owns, implementing [`GlyphAtlas`] to allocate
+//! space and upload bitmaps. [`crate::TextState::glyph_quads`] then describes the text as
+//! positioned quads that sample the atlas, so any number of text states can be drawn from one
+//! texture in one draw call.
+//!
+//! [`CpuGlyphAtlas`] is a reference implementation that keeps the atlas in memory and can draw
+//! quads on the CPU.
+
+use crate::distance_field::{DistanceField, DistanceFieldKind};
+use crate::math::Rect;
+use crate::utils::{blend_unmultiplied, median};
+use ahash::HashMap;
+use cosmic_text::{CacheKey, Color};
+
+/// Pixel format of a glyph bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlyphContent {
+    /// One byte of coverage per pixel. The glyph is drawn in the color of its quad.
+    Mask,
+    /// Four bytes of unmultiplied RGBA per pixel, as in color emoji. The quad color is ignored.
+    Color,
+    /// A distance field with the given settings, see [`DistanceField`]. Single-channel fields
+    /// have one byte per pixel, multi-channel fields four. The glyph is drawn in the color of its
+    /// quad.
+    DistanceField(DistanceField),
+}
+
+impl GlyphContent {
+    /// Number of bytes per pixel.
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Mask => 1,
+            Self::Color => 4,
+            Self::DistanceField(field) => field.bytes_per_pixel(),
+        }
+    }
+}
+
+/// A rasterized glyph bitmap to upload into the atlas.
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphImage<'a> {
+    /// Width of the bitmap in pixels.
+    pub width: u32,
+    /// Height of the bitmap in pixels.
+    pub height: u32,
+    /// Horizontal offset of the bitmap's left edge from the glyph origin, in pixels.
+    pub left: i32,
+    /// Vertical offset of the bitmap's top edge above the baseline, in pixels.
+    pub top: i32,
+    /// Pixel format of `data`.
+    pub content: GlyphContent,
+    /// Rows of pixels from top to bottom, without padding.
+    pub data: &'a [u8],
+}
+
+impl GlyphImage<'_> {
+    /// Describes this image stored at the given position in the atlas.
+    pub const fn placed_at(&self, x: u32, y: u32) -> AtlasGlyph {
+        AtlasGlyph {
+            x,
+            y,
+            width: self.width,
+            height: self.height,
+            left: self.left,
+            top: self.top,
+            content: self.content,
+        }
+    }
+}
+
+/// A glyph bitmap stored in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AtlasGlyph {
+    /// Left edge of the bitmap in the atlas texture, in pixels.
+    pub x: u32,
+    /// Top edge of the bitmap in the atlas texture, in pixels.
+    pub y: u32,
+    /// Width of the bitmap in pixels.
+    pub width: u32,
+    /// Height of the bitmap in pixels.
+    pub height: u32,
+    /// Horizontal offset of the bitmap's left edge from the glyph origin, in pixels.
+    pub left: i32,
+    /// Vertical offset of the bitmap's top edge above the baseline, in pixels.
+    pub top: i32,
+    /// Pixel format the bitmap was uploaded with.
+    pub content: GlyphContent,
+}
+
+/// Storage for glyph bitmaps that is shared by many text states, usually a GPU texture.
+///
+/// # Examples
+/// ```
+/// use protextinator::{AtlasGlyph, GlyphAtlas, GlyphImage};
+/// use protextinator::cosmic_text::CacheKey;
+/// use std::collections::HashMap;
+///
+/// /// Puts every glyph in its own row of a texture that never runs out of space.
+/// #[derive(Default)]
+/// struct RowAtlas {
+///     glyphs: HashMap<CacheKey, AtlasGlyph>,
+///     next_y: u32,
+/// }
+///
+/// impl GlyphAtlas for RowAtlas {
+///     fn get(&self, key: &CacheKey) -> Option<AtlasGlyph> {
+///         self.glyphs.get(key).copied()
+///     }
+///
+///     fn insert(&mut self, key: CacheKey, image: &GlyphImage) -> Option<AtlasGlyph> {
+///         let glyph = image.placed_at(0, self.next_y);
+///         // Upload `image.data` to the texture here
+///         self.next_y += image.height;
+///         self.glyphs.insert(key, glyph);
+///         Some(glyph)
+///     }
+/// }
+/// ```
+pub trait GlyphAtlas {
+    /// Returns a glyph that has been inserted before.
+    fn get(&self, key: &CacheKey) -> Option<AtlasGlyph>;
+
+    /// Allocates space for the glyph bitmap and uploads it. Returns `None` if the glyph doesn't
+    /// fit, in which case it's left out of the quads.
+    fn insert(&mut self, key: CacheKey, image: &GlyphImage) -> Option<AtlasGlyph>;
+}
+
+/// A glyph positioned in a text area, drawn by sampling its bitmap from the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    /// Key of the glyph in the atlas.
+    pub cache_key: CacheKey,
+    /// The glyph bitmap in the atlas.
+    pub glyph: AtlasGlyph,
+    /// Left edge of the quad in DEVICE pixels, relative to the text area.
+    pub x: i32,
+    /// Top edge of the quad in DEVICE pixels, relative to the text area.
+    pub y: i32,
+    /// Width of the quad in DEVICE pixels.
+    pub width: u32,
+    /// Height of the quad in DEVICE pixels.
+    pub height: u32,
+    /// The bitmap is drawn rotated by 90 degrees clockwise, as sideways glyphs in vertical
+    /// text are. The quad's width is then the bitmap's height, and the other way around.
+    pub rotated: bool,
+    /// Color of the glyph, used for [`GlyphContent::Mask`] and [`GlyphContent::DistanceField`]
+    /// bitmaps.
+    pub color: Color,
+    /// Area outside of which nothing must be drawn, in DEVICE pixels relative to the text area.
+    pub clip: Rect,
+}
+
+/// Padding between glyphs in [`CpuGlyphAtlas`], so that filtered sampling doesn't pick up the
+/// neighboring glyphs.
+const ATLAS_PADDING: u32 = 1;
+
+/// A row of glyphs in [`CpuGlyphAtlas`].
+#[derive(Debug, Clone, Copy)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+/// Reference [`GlyphAtlas`] that keeps the atlas texture in memory as RGBA8.
+///
+/// Glyphs are packed into shelves. Coverage masks are stored as white with the coverage in the
+/// alpha channel, so a renderer multiplies the sampled color with the quad color.
+///
+/// # Examples
+/// ```
+/// use protextinator::{CpuGlyphAtlas, TextManager, Id, math::Size};
+///
+/// let mut manager: TextManager<()> = TextManager::new();
+/// let id = Id::new("label");
+/// manager.create_state(id, "Hello", ());
+/// manager.text_states.get_mut(&id).unwrap().set_outer_size(&Size::new(200.0, 50.0));
+///
+/// let mut atlas = CpuGlyphAtlas::new(512, 512);
+/// for (_id, quads) in manager.glyph_quads_all(&mut atlas) {
+///     let mut pixels = vec![0; 200 * 50 * 4];
+///     atlas.draw_quads(&quads, &mut pixels, 200, 50);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CpuGlyphAtlas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    glyphs: HashMap<CacheKey, AtlasGlyph>,
+    shelves: Vec<Shelf>,
+}
+
+impl CpuGlyphAtlas {
+    /// Creates an empty atlas with the given size in pixels.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            glyphs: HashMap::default(),
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Width of the atlas texture in pixels.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the atlas texture in pixels.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The atlas texture as RGBA8 rows.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Number of glyphs in the atlas.
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Returns `true` if the atlas has no glyphs.
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Removes all glyphs. Quads created before are no longer valid.
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.glyphs.clear();
+        self.shelves.clear();
+    }
+
+    /// Draws the quads into an RGBA8 image with unmultiplied alpha, blending them over its
+    /// contents the way a GPU renderer would. Distance field glyphs are thresholded like a
+    /// fragment shader would, at the scale they were generated for.
+    ///
+    /// # Arguments
+    /// * `quads` - Quads of a text state, created with this atlas
+    /// * `target` - RGBA8 pixels of the image
+    /// * `target_width` - Width of the image in pixels
+    /// * `target_height` - Height of the image in pixels
+    pub fn draw_quads(
+        &self,
+        quads: &[GlyphQuad],
+        target: &mut [u8],
+        target_width: u32,
+        target_height: u32,
+    ) {
+        for quad in quads {
+            let min_x = (quad.x.max(0) as f32).max(quad.clip.min.x);
+            let min_y = (quad.y.max(0) as f32).max(quad.clip.min.y);
+            let max_x = ((quad.x + quad.width as i32) as f32)
+                .min(quad.clip.max.x)
+                .min(target_width as f32);
+            let max_y = ((quad.y + quad.height as i32) as f32)
+                .min(quad.clip.max.y)
+                .min(target_height as f32);
+            for y in min_y.ceil() as i32..max_y.ceil() as i32 {
+                for x in min_x.ceil() as i32..max_x.ceil() as i32 {
+                    let (quad_x, quad_y) = ((x - quad.x) as u32, (y - quad.y) as u32);
+                    // A sideways glyph is rotated clockwise, so the quad's columns are the
+                    // bitmap's rows from the bottom up
+                    let (glyph_x, glyph_y) = if quad.rotated {
+                        (quad_y, quad.glyph.height - 1 - quad_x)
+                    } else {
+                        (quad_x, quad_y)
+                    };
+                    let source = self.pixel(quad.glyph.x + glyph_x, quad.glyph.y + glyph_y);
+                    let source = match quad.glyph.content {
+                        GlyphContent::Mask => {
+                            let alpha = u32::from(source[3]) * u32::from(quad.color.a()) / 255;
+                            [quad.color.r(), quad.color.g(), quad.color.b(), alpha as u8]
+                        }
+                        GlyphContent::Color => source,
+                        GlyphContent::DistanceField(field) => {
+                            let value = match field.kind {
+                                DistanceFieldKind::Sdf => source[3],
+                                DistanceFieldKind::Msdf => median(source[0], source[1], source[2]),
+                            };
+                            let alpha = field.coverage(value) * f32::from(quad.color.a());
+                            let alpha = alpha.round() as u8;
+                            [quad.color.r(), quad.color.g(), quad.color.b(), alpha]
+                        }
+                    };
+                    let index = (y as usize * target_width as usize + x as usize) * 4;
+                    blend_unmultiplied(&mut target[index..index + 4], source);
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+            self.pixels[index + 3],
+        ]
+    }
+
+    /// Finds space for a bitmap of the given size, starting a new shelf if needed.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let padded_width = width + ATLAS_PADDING;
+        let padded_height = height + ATLAS_PADDING;
+        if padded_width > self.width {
+            return None;
+        }
+        let shelf = self.shelves.iter_mut().find(|shelf| {
+            shelf.height >= padded_height && shelf.next_x + padded_width <= self.width
+        });
+        let shelf = match shelf {
+            Some(shelf) => shelf,
+            None => {
+                let y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                if y + padded_height > self.height {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height: padded_height,
+                    next_x: 0,
+                });
+                self.shelves.last_mut()?
+            }
+        };
+        let position = (shelf.next_x, shelf.y);
+        shelf.next_x += padded_width;
+        Some(position)
+    }
+}
+
+impl GlyphAtlas for CpuGlyphAtlas {
+    fn get(&self, key: &CacheKey) -> Option<AtlasGlyph> {
+        self.glyphs.get(key).copied()
+    }
+
+    fn insert(&mut self, key: CacheKey, image: &GlyphImage) -> Option<AtlasGlyph> {
+        let (x, y) = self.allocate(image.width, image.height)?;
+        let bytes_per_pixel = image.content.bytes_per_pixel();
+        for row in 0..image.height as usize {
+            for column in 0..image.width as usize {
+                let source = (row * image.width as usize + column) * bytes_per_pixel;
+                let pixel = match image.content {
+                    GlyphContent::Mask
+                    | GlyphContent::DistanceField(DistanceField {
+                        kind: DistanceFieldKind::Sdf,
+                        ..
+                    }) => [255, 255, 255, image.data[source]],
+                    GlyphContent::Color
+                    | GlyphContent::DistanceField(DistanceField {
+                        kind: DistanceFieldKind::Msdf,
+                        ..
+                    }) => [
+                        image.data[source],
+                        image.data[source + 1],
+                        image.data[source + 2],
+                        image.data[source + 3],
+                    ],
+                };
+                let target = ((y as usize + row) * self.width as usize + x as usize + column) * 4;
+                self.pixels[target..target + 4].copy_from_slice(&pixel);
+            }
+        }
+        let glyph = image.placed_at(x, y);
+        self.glyphs.insert(key, glyph);
+        Some(glyph)
+    }
+}
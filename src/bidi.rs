@@ -0,0 +1,129 @@
+//! Caret geometry and visual caret movement in bidirectional text.
+//!
+//! A logical caret position between left-to-right and right-to-left text touches two different
+//! places on screen: the trailing edge of the character before it and the leading edge of the
+//! character after it. The cursor affinity picks which one the caret is drawn at.
+
+use cosmic_text::{Affinity, Buffer, Cursor, LayoutRun, Motion};
+
+/// Minimal distance between two caret edges, in DEVICE pixels, for them to be considered apart.
+const EDGE_EPSILON: f32 = 0.5;
+
+/// Horizontal positions of a caret position in a layout run, in layout coordinates.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CaretEdges {
+    /// Trailing edge of the character before the caret.
+    pub trailing: Option<f32>,
+    /// Leading edge of the character after the caret.
+    pub leading: Option<f32>,
+}
+
+impl CaretEdges {
+    pub fn new(run: &LayoutRun, index: usize) -> Self {
+        let trailing = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.end == index && glyph.start < index)
+            .map(|glyph| {
+                if glyph.level.is_rtl() {
+                    glyph.x
+                } else {
+                    glyph.x + glyph.w
+                }
+            });
+        let leading = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.start == index)
+            .map(|glyph| {
+                if glyph.level.is_rtl() {
+                    glyph.x + glyph.w
+                } else {
+                    glyph.x
+                }
+            });
+        Self { trailing, leading }
+    }
+
+    /// The edge the caret is drawn at for the given affinity.
+    pub fn primary(&self, affinity: Affinity) -> Option<f32> {
+        match affinity {
+            Affinity::Before => self.trailing.or(self.leading),
+            Affinity::After => self.leading.or(self.trailing),
+        }
+    }
+
+    /// Returns `(primary, secondary)` edges if the caret is split between two places.
+    pub fn split(&self, affinity: Affinity) -> Option<(f32, f32)> {
+        let (trailing, leading) = (self.trailing?, self.leading?);
+        if (trailing - leading).abs() <= EDGE_EPSILON {
+            return None;
+        }
+        match affinity {
+            Affinity::Before => Some((trailing, leading)),
+            Affinity::After => Some((leading, trailing)),
+        }
+    }
+}
+
+/// Result of moving the caret visually within its layout line.
+pub(crate) enum VisualMotion {
+    /// The caret has moved to the visually adjacent position.
+    Moved(Cursor),
+    /// The caret is at the edge of its layout line. Moving on to the adjacent line means applying
+    /// the motion to the cursor at the logical end of the line in that direction.
+    LineEdge(Cursor, Motion),
+}
+
+/// Moves the cursor to the visually adjacent position to the left or to the right. Returns
+/// `None` if the cursor isn't on a laid out line with glyphs.
+pub(crate) fn visual_motion(
+    buffer: &Buffer,
+    cursor: Cursor,
+    to_right: bool,
+) -> Option<VisualMotion> {
+    let run = buffer
+        .layout_runs()
+        .filter(|run| run.line_i == cursor.line)
+        .find(|run| {
+            CaretEdges::new(run, cursor.index)
+                .primary(cursor.affinity)
+                .is_some()
+        })?;
+    let caret_x = CaretEdges::new(&run, cursor.index).primary(cursor.affinity)?;
+    let crossed_glyph = run
+        .glyphs
+        .iter()
+        .filter(|glyph| glyph.w > 0.0)
+        .find(|glyph| {
+            let edge = if to_right { glyph.x } else { glyph.x + glyph.w };
+            (edge - caret_x).abs() <= EDGE_EPSILON
+        });
+
+    let Some(crossed_glyph) = crossed_glyph else {
+        let is_moving_forward = to_right != run.rtl;
+        let motion = if is_moving_forward {
+            let end = run.glyphs.iter().map(|glyph| glyph.end).max()?;
+            VisualMotion::LineEdge(
+                Cursor::new_with_affinity(cursor.line, end, Affinity::Before),
+                Motion::Next,
+            )
+        } else {
+            let start = run.glyphs.iter().map(|glyph| glyph.start).min()?;
+            VisualMotion::LineEdge(
+                Cursor::new_with_affinity(cursor.line, start, Affinity::After),
+                Motion::Previous,
+            )
+        };
+        return Some(motion);
+    };
+
+    // The caret ends up next to the glyph it has crossed
+    let is_moving_forward = to_right != crossed_glyph.level.is_rtl();
+    let cursor = if is_moving_forward {
+        Cursor::new_with_affinity(cursor.line, crossed_glyph.end, Affinity::Before)
+    } else {
+        Cursor::new_with_affinity(cursor.line, crossed_glyph.start, Affinity::After)
+    };
+    Some(VisualMotion::Moved(cursor))
+}
@@ -1,8 +1,18 @@
+use crate::bidi::CaretEdges;
 use crate::byte_cursor::ByteCursor;
+use crate::hyphenation::{suppress_overflowing_soft_hyphens, SuppressedSoftHyphens, SOFT_HYPHEN};
 use crate::math::{Point, Rect, Size};
-use crate::style::{FontFamily, TextStyle, TextWrap, VerticalTextAlignment};
+use crate::paragraph::{self, LineOffset, ParagraphOffsets, TabShift};
+use crate::style::{FontFamily, TabStops, TextStyle, TextWrap, VerticalTextAlignment};
 use crate::text_params::TextParams;
-use cosmic_text::{Attrs, Buffer, Cursor, Edit, Editor, FontSystem, Shaping};
+use crate::utils::with_font_ref;
+use crate::vertical::VERTICAL_ALTERNATES_FEATURE;
+use cosmic_text::skrifa::instance::{LocationRef, Size as FontUnitsSize};
+use cosmic_text::skrifa::{GlyphId, MetadataProvider};
+use cosmic_text::{
+    Attrs, Buffer, BufferLine, Cursor, Edit, Editor, FeatureTag, FontFeatures, FontSystem,
+    LayoutGlyph, LayoutLine, Shaping,
+};
 
 impl From<TextWrap> for cosmic_text::Wrap {
     fn from(value: TextWrap) -> Self {
@@ -31,21 +41,23 @@ pub(crate) fn vertical_offset(
 /// Returns caret top-left in LOGICAL pixels relative to the viewport.
 pub(crate) fn adjust_vertical_scroll_to_make_caret_visible(
     buffer: &mut Buffer,
+    paragraph_offsets: &ParagraphOffsets,
     current_char_byte_cursor: ByteCursor,
     font_system: &mut FontSystem,
     text_area_size: Size,
     style: &TextStyle,
     scale_factor: f32,
 ) -> Option<Point> {
-    let mut editor = Editor::new(&mut *buffer);
-    editor.set_cursor(current_char_byte_cursor.cursor);
-
-    let caret_position = editor.cursor_position();
+    let cursor = current_char_byte_cursor.cursor;
+    let caret_position = layout_cursor_position(buffer, cursor);
 
     match caret_position {
         Some(position) => {
             // caret position from cosmic_text is in DEVICE pixels
-            let mut caret_top_left_corner = Point::from(position);
+            let paragraph_offset = paragraph_offsets.caret_offset(buffer, cursor, position.y);
+            let mut caret_top_left_corner = position;
+            caret_top_left_corner.x += paragraph_offset.x;
+            caret_top_left_corner.y += paragraph_offset.y;
             let mut scroll = buffer.scroll();
             let scale = scale_factor.max(0.01);
             let line_height_device = style.line_height_pt() * scale;
@@ -70,7 +82,7 @@ pub(crate) fn adjust_vertical_scroll_to_make_caret_visible(
         }
         None => {
             // Caret is not visible, we need to shape the text and move the scroll
-            editor.shape_as_needed(font_system, false);
+            buffer.shape_until_cursor(font_system, cursor, false);
 
             // TODO: Let's keep it the code below for a little while, it might be useful in the
             //  future.
@@ -93,8 +105,9 @@ pub(crate) fn adjust_vertical_scroll_to_make_caret_visible(
             //     });
             // }
             // Return caret position in LOGICAL pixels
-            editor.cursor_position().map(|p| {
-                let p = Point::from(p);
+            layout_cursor_position(buffer, cursor).map(|p| {
+                let paragraph_offset = paragraph_offsets.caret_offset(buffer, cursor, p.y);
+                let p = Point::new(p.x + paragraph_offset.x, p.y + paragraph_offset.y);
                 let scale = scale_factor.max(0.01);
                 Point::new(p.x / scale, p.y / scale)
             })
@@ -102,9 +115,37 @@ pub(crate) fn adjust_vertical_scroll_to_make_caret_visible(
     }
 }
 
+/// Caret top-left in DEVICE pixels as reported by the layout, without paragraph offsets.
+pub(crate) fn layout_cursor_position(buffer: &mut Buffer, cursor: Cursor) -> Option<Point> {
+    // cosmic_text puts a caret that follows a soft hyphen at the end of the layout line that
+    // breaks there, but it belongs to the start of the next one
+    let follows_soft_hyphen = buffer.lines.get(cursor.line).is_some_and(|line| {
+        line.text()
+            .get(..cursor.index)
+            .is_some_and(|text| text.ends_with(SOFT_HYPHEN))
+    });
+    if follows_soft_hyphen {
+        let position = buffer
+            .layout_runs()
+            .filter(|run| run.line_i == cursor.line)
+            .find_map(|run| {
+                let x = CaretEdges::new(&run, cursor.index).leading?;
+                Some(Point::new(x.floor(), run.line_top.floor()))
+            });
+        if position.is_some() {
+            return position;
+        }
+    }
+
+    let mut editor = Editor::new(buffer);
+    editor.set_cursor(cursor);
+    editor.cursor_position().map(Point::from)
+}
+
 /// Hit-test a character under a LOGICAL pixel coordinate, accounting for scroll and scale.
 pub fn char_under_position(
     buffer: &Buffer,
+    paragraph_offsets: &ParagraphOffsets,
     interaction_position_relative_to_element: Point,
     scale_factor: f32,
 ) -> Option<Cursor> {
@@ -112,29 +153,37 @@ pub fn char_under_position(
     let scale = scale_factor.max(0.01);
     let x_device = interaction_position_relative_to_element.x * scale + horizontal_scroll_device;
     let y_device = interaction_position_relative_to_element.y * scale;
-    buffer.hit(x_device, y_device)
+    paragraph::hit(buffer, paragraph_offsets, x_device, y_device)
 }
 
-/// Returns inner buffer dimensions
+/// Returns inner buffer dimensions, the paragraph offsets of the new layout and the soft hyphens
+/// taken out of the buffer lines
 pub(crate) fn update_buffer(
     params: &TextParams,
     buffer: &mut Buffer,
     font_system: &mut FontSystem,
     font_family: &FontFamily,
-) -> Size {
+) -> (Size, ParagraphOffsets, SuppressedSoftHyphens) {
     let text_style = &params.style();
     let font_color = text_style.font_color;
     let horizontal_alignment = text_style.horizontal_alignment;
-    let wrap = text_style.wrap.unwrap_or_default();
-    let text_area_size = params.size();
+    let wrap = text_style.effective_wrap();
+    let text_area_size = params.layout_size();
     let weight = text_style.weight;
     let letter_spacing = text_style.letter_spacing;
     let metadata = params.metadata();
     let old_scroll = buffer.scroll();
 
     let scale_factor = params.scale_factor();
+    // With explicit tab stops every tab gets its own advance, see `TabLayout`
+    let tab_width = if text_style.tab_stops.is_some() {
+        1
+    } else {
+        text_style.tab_width.max(1)
+    };
     buffer.set_metrics(font_system, params.metrics());
     buffer.set_wrap(font_system, wrap.into());
+    buffer.set_tab_width(font_system, tab_width);
 
     // Setting vertical size to None means that the buffer will use the height of the text.
     // This is needed to ensue that glyphs can be scrolled vertically by smaller amounts than
@@ -151,69 +200,272 @@ pub(crate) fn update_buffer(
     if let Some(letter_spacing) = letter_spacing {
         attrs = attrs.letter_spacing(letter_spacing.0 * scale_factor);
     }
+    // Brackets and punctuation get their vertical forms, which are drawn upright
+    if text_style.writing_mode.is_vertical() {
+        let mut features = FontFeatures::new();
+        features.enable(FeatureTag::new(&VERTICAL_ALTERNATES_FEATURE));
+        attrs = attrs.font_features(features);
+    }
 
     buffer.set_text(
         font_system,
-        params.text_for_internal_use(),
+        params.display_text(),
         &attrs,
         Shaping::Advanced,
         None,
     );
 
+    let rtl: Vec<bool> = buffer
+        .lines
+        .iter_mut()
+        .map(|line| line.shape(font_system, tab_width).rtl)
+        .collect();
+    let mut offsets = ParagraphOffsets::new(text_style, &rtl, scale_factor);
+    let font_size_device = text_style.font_size.value() * scale_factor;
+    let area_width = text_area_size.x * scale_factor;
+    let tab_stops = text_style.tab_stops.as_ref().map(|tab_stops| TabLayout {
+        tab_stops,
+        tab_width: text_style.tab_width.max(1),
+        scale_factor,
+    });
+
+    let layout = |line: &mut BufferLine, font_system: &mut FontSystem, width: f32| {
+        line.layout(
+            font_system,
+            font_size_device,
+            Some(width),
+            wrap.into(),
+            None,
+            tab_width,
+            cosmic_text::Hinting::Enabled,
+        );
+    };
+
     let mut buffer_measurement = Size::default();
-    for line in buffer.lines.iter_mut() {
+    let mut suppressed = SuppressedSoftHyphens::default();
+    for (line_index, line) in buffer.lines.iter_mut().enumerate() {
+        let offset = offsets.line(line_index);
         line.set_align(horizontal_alignment.into());
-        for layout_line in line
-            .layout(
-                font_system,
-                text_style.font_size.value() * scale_factor,
-                Some(text_area_size.x * scale_factor),
-                text_style.wrap.unwrap_or_default().into(),
-                None,
-                // TODO: what is the default tab width? Make it configurable?
-                2,
-                cosmic_text::Hinting::Enabled,
-            )
-            .iter()
-        {
+        let suppressed_offsets = layout_paragraph(
+            line,
+            font_system,
+            &layout,
+            offset,
+            area_width,
+            Some(area_width),
+        );
+        suppressed.extend(line_index, suppressed_offsets);
+        let start_share = paragraph::start_edge_share(horizontal_alignment, rtl[line_index]);
+        for (layout_index, layout_line) in line.layout_opt().into_iter().flatten().enumerate() {
             let line_height = layout_line
                 .line_height_opt
                 .unwrap_or(text_style.line_height_pt() * scale_factor);
+            let indent = if layout_index == 0 {
+                offset.first_line_indent
+            } else {
+                0.0
+            };
+            let tabs_width = tab_stops.as_ref().map_or(0.0, |tab_stops| {
+                let (tabs, tabs_width) =
+                    tab_stops.shifts(font_system, line.text(), layout_line, start_share);
+                offsets.set_tabs(line_index, layout_index, tabs);
+                tabs_width
+            });
             buffer_measurement.y += line_height;
-            buffer_measurement.x = buffer_measurement.x.max(layout_line.w);
+            buffer_measurement.x = buffer_measurement
+                .x
+                .max(layout_line.w + tabs_width + offset.width + indent);
         }
     }
+    buffer_measurement.y += offsets.total_height();
 
-    if buffer_measurement.x > text_area_size.x * scale_factor {
+    if buffer_measurement.x > area_width {
         #[cfg(test)]
         eprintln!(
             "RELAYOUT: buffer_measurement.x={}, text_area_size.x * scale_factor={}",
-            buffer_measurement.x,
-            text_area_size.x * scale_factor
+            buffer_measurement.x, area_width
         );
         // If the buffer is smaller than the text area, we need to set the width to the text area
         // size to ensure that the text is centered.
         // After we've measured the buffer, we need to run layout() again to realign the lines
-        for line in buffer.lines.iter_mut() {
-            line.reset_layout();
+        for (line_index, line) in buffer.lines.iter_mut().enumerate() {
+            let offset = offsets.line(line_index);
             line.set_align(horizontal_alignment.into());
-            line.layout(
+            let suppressed_offsets = layout_paragraph(
+                line,
                 font_system,
-                text_style.font_size.value() * scale_factor,
-                Some(buffer_measurement.x),
-                wrap.into(),
+                &layout,
+                offset,
+                buffer_measurement.x,
                 None,
-                // TODO: what is the default tab width? Make it configurable?
-                2,
-                cosmic_text::Hinting::Enabled,
             );
+            suppressed.extend(line_index, suppressed_offsets);
+            let Some(tab_stops) = &tab_stops else {
+                continue;
+            };
+            let start_share = paragraph::start_edge_share(horizontal_alignment, rtl[line_index]);
+            for (layout_index, layout_line) in line.layout_opt().into_iter().flatten().enumerate() {
+                let (tabs, _) =
+                    tab_stops.shifts(font_system, line.text(), layout_line, start_share);
+                offsets.set_tabs(line_index, layout_index, tabs);
+            }
         }
     }
 
     buffer.set_scroll(old_scroll);
     // We shaped at device pixels; convert inner_dimensions back to logical for API
-    Size::from((
+    let inner_dimensions = Size::from((
         buffer_measurement.x / scale_factor,
         buffer_measurement.y / scale_factor,
-    ))
+    ));
+    (inner_dimensions, offsets, suppressed)
+}
+
+/// Lays out a paragraph for a text area that is `area_width` wide, with the first layout line
+/// narrower or wider than the others by the first line indent. Widths are in DEVICE pixels.
+/// Returns the byte offsets of the soft hyphens that were taken out of the line.
+///
+/// `laid_out_width` is the width the paragraph's current layout was made at, if it has one
+/// that can be kept.
+fn layout_paragraph(
+    line: &mut BufferLine,
+    font_system: &mut FontSystem,
+    layout: &impl Fn(&mut BufferLine, &mut FontSystem, f32),
+    offset: LineOffset,
+    area_width: f32,
+    laid_out_width: Option<f32>,
+) -> Vec<usize> {
+    let width = (area_width - offset.width).max(0.0);
+    let first_line_width = (width - offset.first_line_indent).max(0.0);
+    // The first layout line is as wide as the others and moved by the first line indent, so
+    // the paragraph is laid out at the wider of the two widths if its first line still fits
+    // into its own width, and at the narrower one otherwise
+    let mut layout_width = width.max(first_line_width);
+    if laid_out_width != Some(layout_width) {
+        line.reset_layout();
+    }
+    layout(line, font_system, layout_width);
+    if first_line_width != width && !fits(line, first_line_width, width) {
+        layout_width = width.min(first_line_width);
+        line.reset_layout();
+        layout(line, font_system, layout_width);
+    }
+    // Lines that only fit without the hyphen drawn at their end break earlier
+    let mut suppressed = Vec::new();
+    loop {
+        let offsets = suppress_overflowing_soft_hyphens(font_system, line, layout_width);
+        if offsets.is_empty() {
+            return suppressed;
+        }
+        suppressed.extend(offsets);
+        layout(line, font_system, layout_width);
+    }
+}
+
+/// Returns `true` if the first layout line of the paragraph is at most `first_line_width` wide,
+/// and the other layout lines at most `width`.
+fn fits(line: &BufferLine, first_line_width: f32, width: f32) -> bool {
+    line.layout_opt()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .all(|(index, layout_line)| {
+            let width = if index == 0 { first_line_width } else { width };
+            layout_line.w <= width + 0.5
+        })
+}
+
+/// Explicit tab stops, applied to laid out lines.
+///
+/// cosmic_text only knows about regular tab stops, so with explicit stops the buffer is laid out
+/// with a tab width of 1, which makes tabs at most as wide as a space. The glyphs after each tab
+/// are then moved to the tab's stop by [`ParagraphOffsets`]. Lines are wrapped before they are
+/// moved, so a line with tabs can be wider than the wrap width.
+struct TabLayout<'a> {
+    tab_stops: &'a TabStops,
+    tab_width: u16,
+    scale_factor: f32,
+}
+
+impl TabLayout<'_> {
+    /// Shifts that move the glyphs after every tab of the layout line to the tab's stop, in
+    /// DEVICE pixels. Returns the shifts and how much wider they make the line.
+    ///
+    /// Stops are measured from the left edge of the line. The line is moved back by the share of
+    /// its extra width that its alignment puts on the left, see
+    /// [`paragraph::start_edge_share`].
+    fn shifts(
+        &self,
+        font_system: &mut FontSystem,
+        text: &str,
+        layout_line: &LayoutLine,
+        start_share: f32,
+    ) -> (Vec<TabShift>, f32) {
+        let mut glyphs: Vec<&LayoutGlyph> = layout_line
+            .glyphs
+            .iter()
+            .filter(|glyph| text.get(glyph.start..glyph.end) == Some("\t"))
+            .collect();
+        if glyphs.is_empty() {
+            return (Vec::new(), 0.0);
+        }
+        glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let origin = layout_line
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.x)
+            .fold(f32::INFINITY, f32::min);
+
+        let mut shift = 0.0;
+        let mut tabs = Vec::with_capacity(glyphs.len());
+        for glyph in glyphs {
+            let Some(space_em) = space_advance_em(font_system, glyph) else {
+                continue;
+            };
+            let x = glyph.x + shift - origin;
+            let stop = match self.tab_stops.next_stop_after(x / self.scale_factor) {
+                Some(stop) => stop * self.scale_factor,
+                None => {
+                    let interval = f32::from(self.tab_width) * space_em * glyph.font_size;
+                    ((x / interval).floor() + 1.0) * interval
+                }
+            };
+            shift += (origin + stop).round() - (glyph.x + shift + glyph.w);
+            tabs.push(TabShift {
+                start: glyph.x,
+                end: glyph.x + glyph.w,
+                shift,
+            });
+        }
+
+        let width = shift;
+        let line_shift = (-width * (1.0 - start_share)).round();
+        if line_shift != 0.0 {
+            for tab in &mut tabs {
+                tab.shift += line_shift;
+            }
+            tabs.insert(
+                0,
+                TabShift {
+                    start: f32::NEG_INFINITY,
+                    end: f32::NEG_INFINITY,
+                    shift: line_shift,
+                },
+            );
+        }
+        (tabs, width)
+    }
+}
+
+/// Returns the advance of the space glyph that cosmic_text shapes a tab with, in ems.
+fn space_advance_em(font_system: &mut FontSystem, glyph: &LayoutGlyph) -> Option<f32> {
+    with_font_ref(font_system, glyph.font_id, glyph.font_weight, |font_ref| {
+        let units_per_em = font_ref
+            .metrics(FontUnitsSize::unscaled(), LocationRef::default())
+            .units_per_em;
+        let advance = font_ref
+            .glyph_metrics(FontUnitsSize::unscaled(), LocationRef::default())
+            .advance_width(GlyphId::new(u32::from(glyph.glyph_id)))?;
+        Some(advance / f32::from(units_per_em.max(1)))
+    })
 }
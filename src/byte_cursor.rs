@@ -1,4 +1,6 @@
+use crate::text_params::TextParams;
 use cosmic_text::{Affinity, Cursor};
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct ByteCursor {
@@ -18,33 +20,44 @@ impl ByteCursor {
         }
     }
 
-    pub fn before_last_character(string: &str) -> Self {
+    pub fn before_last_character(params: &TextParams) -> Self {
+        let string = params.original_text();
         if string.is_empty() {
             Self::string_start()
         } else {
             let last_byte_offset = string
-                .char_indices()
-                .last()
-                .map(|(byte_idx, _ch)| byte_idx)
-                .expect("string is not empty, so there must be at least one character");
+                .grapheme_indices(true)
+                .next_back()
+                .map(|(byte_idx, _grapheme)| byte_idx)
+                .expect("string is not empty, so there must be at least one grapheme");
             Self {
-                cursor: char_byte_offset_to_cursor(string, last_byte_offset)
+                cursor: params
+                    .cursor_for_byte_offset(last_byte_offset)
                     .expect("the byte offset must be a valid cursor at this point"),
                 byte_character_start: last_byte_offset,
             }
         }
     }
 
-    pub fn after_last_character(string: &str) -> Self {
-        let mut res = Self::before_last_character(string);
-        res.cursor.affinity = Affinity::After;
-        res.byte_character_start = string.len();
-        res
+    pub fn after_last_character(params: &TextParams) -> Self {
+        let end = params.original_text().len();
+        match params.cursor_for_byte_offset(end) {
+            Some(cursor) => Self {
+                cursor,
+                byte_character_start: end,
+            },
+            None => {
+                let mut res = Self::before_last_character(params);
+                res.cursor.affinity = Affinity::After;
+                res.byte_character_start = end;
+                res
+            }
+        }
     }
 
-    pub fn from_cursor(cursor: Cursor, string: &str) -> Option<ByteCursor> {
+    pub fn from_cursor(cursor: Cursor, params: &TextParams) -> Option<ByteCursor> {
         let mut res = Self::string_start();
-        let is_valid_cursor = res.update_cursor(cursor, string);
+        let is_valid_cursor = res.update_cursor(cursor, params);
         if is_valid_cursor {
             Some(res)
         } else {
@@ -52,16 +65,17 @@ impl ByteCursor {
         }
     }
 
-    /// Returns char index of the cursor in a given string
-    pub fn char_index(&self, string: &str) -> Option<usize> {
-        char_byte_offset_to_char_index(string, self.byte_character_start)
+    /// Returns the index of the grapheme cluster the cursor is before in a given string
+    pub fn grapheme_index(&self, string: &str) -> Option<usize> {
+        byte_offset_to_grapheme_index(string, self.byte_character_start)
     }
 
-    pub fn update_cursor(&mut self, cursor: Cursor, string: &str) -> bool {
+    pub fn update_cursor(&mut self, cursor: Cursor, params: &TextParams) -> bool {
+        let cursor = params.skip_direction_mark(cursor);
         if cursor == self.cursor {
             return true;
         }
-        if let Some(byte_offset) = byte_offset_cursor_to_byte_offset(string, cursor) {
+        if let Some(byte_offset) = params.byte_offset_for_cursor(cursor) {
             self.cursor = cursor;
             self.byte_character_start = byte_offset;
             true
@@ -70,11 +84,11 @@ impl ByteCursor {
         }
     }
 
-    pub fn update_byte_offset(&mut self, byte_offset: usize, string: &str) -> bool {
+    pub fn update_byte_offset(&mut self, byte_offset: usize, params: &TextParams) -> bool {
         if self.byte_character_start == byte_offset {
             return true;
         }
-        if let Some(cursor) = char_byte_offset_to_cursor(string, byte_offset) {
+        if let Some(cursor) = params.cursor_for_byte_offset(byte_offset) {
             self.cursor = cursor;
             self.byte_character_start = byte_offset;
             true
@@ -86,6 +100,10 @@ impl ByteCursor {
     pub fn prev_char_byte_offset(&self, string: &str) -> Option<usize> {
         previous_char_byte_offset(string, self.byte_character_start)
     }
+
+    pub fn prev_grapheme_byte_offset(&self, string: &str) -> Option<usize> {
+        previous_grapheme_byte_offset(string, self.byte_character_start)
+    }
 }
 
 pub fn char_byte_offset_to_cursor(full_text: &str, char_byte_offset: usize) -> Option<Cursor> {
@@ -135,28 +153,34 @@ pub fn char_byte_offset_to_cursor(full_text: &str, char_byte_offset: usize) -> O
     }
 }
 
-pub fn char_byte_offset_to_char_index(text: &str, char_byte_offset: usize) -> Option<usize> {
-    if char_byte_offset > text.len() {
+/// Returns the index of the grapheme cluster that starts at the byte offset, or the number of
+/// grapheme clusters if the offset is at the end of the text. Returns `None` if the offset is
+/// inside of a grapheme cluster.
+pub fn byte_offset_to_grapheme_index(text: &str, byte_offset: usize) -> Option<usize> {
+    if byte_offset > text.len() {
         return None;
     }
-
-    // If the byte offset is at the end of the string, return the character count
-    if char_byte_offset == text.len() {
-        return Some(text.chars().count());
-    }
-
-    // Iterate over characters until we find a required byte offset
-    for (char_index, (byte_offset, _)) in text.char_indices().enumerate() {
-        if byte_offset == char_byte_offset {
-            return Some(char_index);
-        }
-        if byte_offset > char_byte_offset {
-            // The byte offset is not at a character boundary
-            return None;
+    let mut count = 0;
+    for (offset, _) in text.grapheme_indices(true) {
+        if offset >= byte_offset {
+            return (offset == byte_offset).then_some(count);
         }
+        count += 1;
     }
+    Some(count)
+}
 
-    None
+/// Moves the byte offset back to the start of the grapheme cluster it's inside of. Offsets at
+/// or past the end of the text are left as they are.
+pub fn grapheme_start(text: &str, byte_offset: usize) -> usize {
+    if byte_offset >= text.len() {
+        return byte_offset;
+    }
+    text.grapheme_indices(true)
+        .map(|(offset, _)| offset)
+        .take_while(|offset| *offset <= byte_offset)
+        .last()
+        .unwrap_or(0)
 }
 
 fn previous_char_byte_offset(text: &str, current: usize) -> Option<usize> {
@@ -175,6 +199,16 @@ fn previous_char_byte_offset(text: &str, current: usize) -> Option<usize> {
         .map(|(byte_idx, _ch)| byte_idx)
 }
 
+fn previous_grapheme_byte_offset(text: &str, current: usize) -> Option<usize> {
+    if current == 0 || current > text.len() {
+        return None;
+    }
+    text[..current]
+        .grapheme_indices(true)
+        .next_back()
+        .map(|(byte_idx, _grapheme)| byte_idx)
+}
+
 pub fn byte_offset_cursor_to_byte_offset(string: &str, cursor: Cursor) -> Option<usize> {
     let mut char_byte_offset = 0;
 
@@ -0,0 +1,118 @@
+//! Caret shapes, colors and blinking.
+//!
+//! [`CaretStyle`] describes how the caret of a [`crate::TextState`] looks. The geometry of the
+//! caret in its current shape is returned by [`crate::TextState::caret_rects`], and
+//! [`crate::TextState::caret_visible`] tells whether a blinking caret is in its visible phase.
+
+use cosmic_text::Color;
+use std::time::{Duration, Instant};
+
+/// Shape of the caret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaretShape {
+    /// A thin bar between two graphemes.
+    #[default]
+    Bar,
+    /// A block covering the grapheme after the caret, as wide as half of the font size at the end
+    /// of a line.
+    Block,
+    /// A line under the grapheme after the caret.
+    Underline,
+}
+
+/// How the caret of a [`crate::TextState`] is drawn, set with [`crate::TextState::caret`].
+///
+/// # Examples
+/// ```
+/// use protextinator::{CaretShape, CaretStyle};
+/// use cosmic_text::Color;
+/// use std::time::Duration;
+///
+/// let style = CaretStyle::default()
+///     .with_shape(CaretShape::Underline)
+///     .with_color(Some(Color::rgb(255, 0, 0)))
+///     .with_blink_interval(Some(Duration::from_millis(500)));
+/// assert_eq!(style.width, 3.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaretStyle {
+    /// Shape of the caret when inserting text.
+    pub shape: CaretShape,
+    /// Shape of the caret in overwrite mode, see [`crate::TextState::set_overwrite_mode`].
+    pub overwrite_shape: CaretShape,
+    /// Width of the bar, or thickness of the underline, in LOGICAL pixels.
+    pub width: f32,
+    /// Color to paint the caret with in the rasterized texture. `None` leaves drawing the caret
+    /// to the renderer. The caret is only painted if the text is editable.
+    pub color: Option<Color>,
+    /// How long the caret stays visible, and then hidden, when blinking. `None` doesn't blink.
+    pub blink_interval: Option<Duration>,
+}
+
+impl Default for CaretStyle {
+    fn default() -> Self {
+        Self {
+            shape: CaretShape::Bar,
+            overwrite_shape: CaretShape::Block,
+            width: 3.0,
+            color: None,
+            blink_interval: None,
+        }
+    }
+}
+
+impl CaretStyle {
+    /// Sets the shape of the caret when inserting text and returns the modified style.
+    pub const fn with_shape(mut self, shape: CaretShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the shape of the caret in overwrite mode and returns the modified style.
+    pub const fn with_overwrite_shape(mut self, shape: CaretShape) -> Self {
+        self.overwrite_shape = shape;
+        self
+    }
+
+    /// Sets the width of the bar, or thickness of the underline, and returns the modified style.
+    pub const fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the color of the caret in the rasterized texture and returns the modified style.
+    pub const fn with_color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the blink interval and returns the modified style.
+    pub const fn with_blink_interval(mut self, interval: Option<Duration>) -> Self {
+        self.blink_interval = interval;
+        self
+    }
+
+    /// Whether a caret that was last moved at `activity` is in the visible phase of its blink
+    /// at `now`. The caret is visible for the first interval after it moves.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::CaretStyle;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let style = CaretStyle::default().with_blink_interval(Some(Duration::from_millis(500)));
+    /// let moved = Instant::now();
+    /// assert!(style.is_visible(moved, moved + Duration::from_millis(400)));
+    /// assert!(!style.is_visible(moved, moved + Duration::from_millis(600)));
+    /// assert!(style.is_visible(moved, moved + Duration::from_millis(1100)));
+    /// ```
+    pub fn is_visible(&self, activity: Instant, now: Instant) -> bool {
+        match self.blink_interval {
+            Some(interval) if !interval.is_zero() => {
+                let elapsed = now.saturating_duration_since(activity);
+                (elapsed.as_nanos() / interval.as_nanos()).is_multiple_of(2)
+            }
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,874 @@
+//! Color glyphs, such as emoji.
+//!
+//! Bitmap glyphs (`CBDT` and `sbix`) are taken from swash, which already decodes and scales
+//! them. Layered glyphs (`COLR` versions 0 and 1) are painted by a small software renderer from
+//! the paint graph skrifa walks, because swash only supports version 0 and paints the
+//! foreground color of the text in gray. Every image is stored with straight alpha, so it can
+//! be blended in both [`crate::AlphaMode`]s.
+
+use crate::utils::with_font_ref;
+use ahash::HashMap;
+use cosmic_text::skrifa::color::{
+    Brush, ColorPainter, ColorStop, CompositeMode, Extend, Transform,
+};
+use cosmic_text::skrifa::instance::{LocationRef, Size};
+use cosmic_text::skrifa::outline::{DrawSettings, OutlinePen};
+use cosmic_text::skrifa::raw::types::BoundingBox;
+use cosmic_text::skrifa::{FontRef, GlyphId, MetadataProvider, OutlineGlyphCollection};
+use cosmic_text::{CacheKey, Color, FontSystem, SwashCache, SwashContent};
+use zeno::{Command, Mask, Vector};
+
+/// Palette index of the foreground color, the color of the text.
+const FOREGROUND_INDEX: u16 = 0xFFFF;
+
+/// How color glyphs, such as emoji, are drawn into the rasterized texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ColorGlyphs {
+    /// In the colors of the font. The alpha of the text color fades them.
+    #[default]
+    Color,
+    /// In the text color, with the alpha of the color image as coverage, like any other glyph.
+    Monochrome,
+}
+
+/// A color glyph image with straight alpha.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ColorGlyphImage {
+    /// Offset of the left edge from the glyph origin.
+    pub left: i32,
+    /// Offset of the top edge above the baseline.
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+    /// RGBA of each pixel, rows from top to bottom.
+    pub data: Vec<[u8; 4]>,
+}
+
+/// Most text colors a glyph painted in the foreground color is kept in at once. The images of
+/// the glyph are dropped when another color is needed, so animating the text color doesn't grow
+/// the cache.
+const MAX_FOREGROUND_IMAGES: usize = 8;
+
+/// Cached images of a glyph.
+#[derive(Debug)]
+enum CachedGlyph {
+    /// Not a color glyph.
+    Outline,
+    /// A color glyph that looks the same in every text color.
+    Fixed(ColorGlyphImage),
+    /// A `COLR` glyph with layers in the foreground color, with an image for each text color.
+    Foreground(HashMap<u32, ColorGlyphImage>),
+}
+
+/// Cache for color glyph images.
+#[derive(Debug, Default)]
+pub struct ColorGlyphCache {
+    glyphs: HashMap<CacheKey, CachedGlyph>,
+}
+
+impl ColorGlyphCache {
+    /// Creates an empty cache. Images are painted the first time a glyph is drawn, see
+    /// [`crate::TextContext::color_glyph_cache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of glyphs in the cache, counting glyphs that aren't color glyphs.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::ColorGlyphCache;
+    ///
+    /// assert_eq!(ColorGlyphCache::new().len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Whether the cache holds no glyphs.
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Drops every cached image, for example after fonts were removed from the font system.
+    /// Glyphs are painted again the next time they are drawn.
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+    }
+
+    /// Returns the color image of the glyph, or `None` if the glyph isn't a color glyph.
+    ///
+    /// Only `COLR` glyphs with layers in the foreground color are cached for every text color,
+    /// up to `MAX_FOREGROUND_IMAGES` of them. The alpha of the text color is left to the
+    /// caller.
+    pub(crate) fn get(
+        &mut self,
+        font_system: &mut FontSystem,
+        swash_cache: &mut SwashCache,
+        cache_key: CacheKey,
+        foreground: Color,
+    ) -> Option<&ColorGlyphImage> {
+        let foreground = Color::rgb(foreground.r(), foreground.g(), foreground.b());
+        let glyph = self
+            .glyphs
+            .entry(cache_key)
+            .or_insert_with(|| render_glyph(font_system, swash_cache, cache_key, foreground));
+        match glyph {
+            CachedGlyph::Outline => None,
+            CachedGlyph::Fixed(image) => Some(image),
+            CachedGlyph::Foreground(images) => {
+                if images.len() >= MAX_FOREGROUND_IMAGES && !images.contains_key(&foreground.0) {
+                    images.clear();
+                }
+                Some(images.entry(foreground.0).or_insert_with(|| {
+                    paint_colr(font_system, cache_key, foreground)
+                        .map(|(image, _)| image)
+                        .unwrap_or_default()
+                }))
+            }
+        }
+    }
+}
+
+fn render_glyph(
+    font_system: &mut FontSystem,
+    swash_cache: &mut SwashCache,
+    cache_key: CacheKey,
+    foreground: Color,
+) -> CachedGlyph {
+    if let Some((image, uses_foreground)) = paint_colr(font_system, cache_key, foreground) {
+        return if uses_foreground {
+            CachedGlyph::Foreground(HashMap::from_iter([(foreground.0, image)]))
+        } else {
+            CachedGlyph::Fixed(image)
+        };
+    }
+    let Some(image) = swash_cache.get_image(font_system, cache_key).as_ref() else {
+        return CachedGlyph::Outline;
+    };
+    if image.content != SwashContent::Color {
+        return CachedGlyph::Outline;
+    }
+    CachedGlyph::Fixed(ColorGlyphImage {
+        left: image.placement.left,
+        top: image.placement.top,
+        width: image.placement.width,
+        height: image.placement.height,
+        data: image
+            .data
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect(),
+    })
+}
+
+/// Paints a `COLR` glyph, or returns `None` if the glyph has no color layers. Also returns
+/// whether any layer is painted in the foreground color.
+fn paint_colr(
+    font_system: &mut FontSystem,
+    cache_key: CacheKey,
+    foreground: Color,
+) -> Option<(ColorGlyphImage, bool)> {
+    with_font_ref(
+        font_system,
+        cache_key.font_id,
+        cache_key.font_weight,
+        |font_ref| paint_colr_glyph(font_ref, cache_key, foreground),
+    )
+}
+
+/// Paints a `COLR` glyph of the font, see [`paint_colr`].
+fn paint_colr_glyph(
+    font_ref: FontRef,
+    cache_key: CacheKey,
+    foreground: Color,
+) -> Option<(ColorGlyphImage, bool)> {
+    let glyph_id = GlyphId::new(u32::from(cache_key.glyph_id));
+    let color_glyph = font_ref.color_glyphs().get(glyph_id)?;
+    let palettes = font_ref.color_palettes();
+    let palette: Vec<[f32; 4]> = palettes
+        .get(0)
+        .map(|palette| {
+            palette
+                .colors()
+                .iter()
+                .map(|color| {
+                    premultiply([
+                        f32::from(color.red) / 255.0,
+                        f32::from(color.green) / 255.0,
+                        f32::from(color.blue) / 255.0,
+                        f32::from(color.alpha) / 255.0,
+                    ])
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let outlines = font_ref.outline_glyphs();
+
+    // Font units with y going up are scaled to pixels relative to the glyph origin with y going
+    // down
+    let units_per_em = font_ref
+        .metrics(Size::unscaled(), LocationRef::default())
+        .units_per_em;
+    let scale = f32::from_bits(cache_key.font_size_bits) / f32::from(units_per_em.max(1));
+    let origin_transform = Transform {
+        xx: scale,
+        yx: 0.0,
+        xy: 0.0,
+        yy: -scale,
+        dx: cache_key.x_bin.as_float(),
+        dy: -cache_key.y_bin.as_float(),
+    };
+
+    // The first pass finds the pixels the glyph paints, the second one paints them
+    let mut bounds = BoundsPainter {
+        outlines: outlines.clone(),
+        transforms: vec![origin_transform],
+        clips: vec![None],
+        bounds: None,
+        uses_foreground: false,
+    };
+    color_glyph
+        .paint(LocationRef::default(), &mut bounds)
+        .ok()?;
+    let uses_foreground = bounds.uses_foreground;
+    let Some([min_x, min_y, max_x, max_y]) = bounds.bounds else {
+        return Some((ColorGlyphImage::default(), uses_foreground));
+    };
+    let left = min_x.floor() as i32;
+    let top = -(min_y.floor() as i32);
+    let width = (max_x.ceil() as i32 - left).max(0) as u32;
+    let height = (max_y.ceil() as i32 + top).max(0) as u32;
+    if width == 0 || height == 0 {
+        return Some((ColorGlyphImage::default(), uses_foreground));
+    }
+
+    let foreground = premultiply([
+        f32::from(foreground.r()) / 255.0,
+        f32::from(foreground.g()) / 255.0,
+        f32::from(foreground.b()) / 255.0,
+        f32::from(foreground.a()) / 255.0,
+    ]);
+    let image_transform = Transform {
+        dx: origin_transform.dx - left as f32,
+        dy: origin_transform.dy + top as f32,
+        ..origin_transform
+    };
+    let mut painter = Painter {
+        outlines,
+        palette,
+        foreground,
+        width,
+        height,
+        transforms: vec![image_transform],
+        clips: Vec::new(),
+        layers: vec![(
+            vec![[0.0; 4]; width as usize * height as usize],
+            CompositeMode::SrcOver,
+        )],
+    };
+    color_glyph
+        .paint(LocationRef::default(), &mut painter)
+        .ok()?;
+    let (pixels, _) = painter.layers.swap_remove(0);
+    let image = ColorGlyphImage {
+        left,
+        top,
+        width,
+        height,
+        data: pixels.into_iter().map(unpremultiply_u8).collect(),
+    };
+    Some((image, uses_foreground))
+}
+
+/// Collects the outline of a glyph as path commands, transformed to pixels.
+struct PathPen {
+    transform: Transform,
+    commands: Vec<Command>,
+}
+
+impl PathPen {
+    fn point(&self, x: f32, y: f32) -> Vector {
+        let (x, y) = apply(&self.transform, x, y);
+        Vector::new(x, y)
+    }
+
+    /// The smallest rectangle containing all points of the path, as min x, min y, max x and
+    /// max y.
+    fn bounds(&self) -> Option<[f32; 4]> {
+        self.commands
+            .iter()
+            .flat_map(|command| match *command {
+                Command::MoveTo(point) | Command::LineTo(point) => vec![point],
+                Command::QuadTo(control, point) => vec![control, point],
+                Command::CurveTo(control1, control2, point) => vec![control1, control2, point],
+                Command::Close => Vec::new(),
+            })
+            .map(|point| [point.x, point.y, point.x, point.y])
+            .reduce(union)
+    }
+}
+
+impl OutlinePen for PathPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.commands.push(Command::MoveTo(point));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.point(x, y);
+        self.commands.push(Command::LineTo(point));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        let control = self.point(cx0, cy0);
+        let point = self.point(x, y);
+        self.commands.push(Command::QuadTo(control, point));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let control1 = self.point(cx0, cy0);
+        let control2 = self.point(cx1, cy1);
+        let point = self.point(x, y);
+        self.commands
+            .push(Command::CurveTo(control1, control2, point));
+    }
+
+    fn close(&mut self) {
+        self.commands.push(Command::Close);
+    }
+}
+
+/// The outline of a glyph in font units, transformed to pixels.
+fn glyph_path(
+    outlines: &OutlineGlyphCollection,
+    glyph_id: GlyphId,
+    transform: Transform,
+) -> PathPen {
+    let mut pen = PathPen {
+        transform,
+        commands: Vec::new(),
+    };
+    if let Some(outline) = outlines.get(glyph_id) {
+        let settings = DrawSettings::unhinted(Size::unscaled(), LocationRef::default());
+        if outline.draw(settings, &mut pen).is_err() {
+            pen.commands.clear();
+        }
+    }
+    pen
+}
+
+/// A clip box in font units, transformed to pixels.
+fn box_path(clip_box: BoundingBox<f32>, transform: Transform) -> PathPen {
+    let mut pen = PathPen {
+        transform,
+        commands: Vec::new(),
+    };
+    pen.move_to(clip_box.x_min, clip_box.y_min);
+    pen.line_to(clip_box.x_max, clip_box.y_min);
+    pen.line_to(clip_box.x_max, clip_box.y_max);
+    pen.line_to(clip_box.x_min, clip_box.y_max);
+    pen.close();
+    pen
+}
+
+/// Finds the pixels relative to the glyph origin that a color glyph paints.
+struct BoundsPainter<'a> {
+    outlines: OutlineGlyphCollection<'a>,
+    transforms: Vec<Transform>,
+    /// Bounds of the clips, `None` if nothing is clipped yet.
+    clips: Vec<Option<[f32; 4]>>,
+    bounds: Option<[f32; 4]>,
+    /// Whether any fill uses the foreground color.
+    uses_foreground: bool,
+}
+
+impl BoundsPainter<'_> {
+    fn push_clip_bounds(&mut self, bounds: Option<[f32; 4]>) {
+        let bounds = bounds.unwrap_or([0.0; 4]);
+        let clip = match self.clips.last().copied().flatten() {
+            Some(clip) => intersection(clip, bounds),
+            None => bounds,
+        };
+        self.clips.push(Some(clip));
+    }
+}
+
+impl ColorPainter for BoundsPainter<'_> {
+    fn push_transform(&mut self, transform: Transform) {
+        let current = *self.transforms.last().unwrap_or(&Transform::default());
+        self.transforms.push(current * transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        let transform = *self.transforms.last().unwrap_or(&Transform::default());
+        let bounds = glyph_path(&self.outlines, glyph_id, transform).bounds();
+        self.push_clip_bounds(bounds);
+    }
+
+    fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+        let transform = *self.transforms.last().unwrap_or(&Transform::default());
+        let bounds = box_path(clip_box, transform).bounds();
+        self.push_clip_bounds(bounds);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn fill(&mut self, brush: Brush<'_>) {
+        self.uses_foreground |= match brush {
+            Brush::Solid { palette_index, .. } => palette_index == FOREGROUND_INDEX,
+            Brush::LinearGradient { color_stops, .. }
+            | Brush::RadialGradient { color_stops, .. }
+            | Brush::SweepGradient { color_stops, .. } => color_stops
+                .iter()
+                .any(|stop| stop.palette_index == FOREGROUND_INDEX),
+        };
+        // Unclipped fills cover everything, glyphs are always clipped to their layers though
+        let Some(clip) = self.clips.last().copied().flatten() else {
+            return;
+        };
+        if clip[0] >= clip[2] || clip[1] >= clip[3] {
+            return;
+        }
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => union(bounds, clip),
+            None => clip,
+        });
+    }
+
+    fn push_layer(&mut self, _composite_mode: CompositeMode) {}
+}
+
+/// Paints a color glyph into premultiplied layers.
+struct Painter<'a> {
+    outlines: OutlineGlyphCollection<'a>,
+    /// Premultiplied colors of the first palette of the font.
+    palette: Vec<[f32; 4]>,
+    /// Premultiplied color of the text.
+    foreground: [f32; 4],
+    width: u32,
+    height: u32,
+    transforms: Vec<Transform>,
+    /// Coverage of every pixel by all clips pushed so far.
+    clips: Vec<Vec<f32>>,
+    /// Premultiplied pixels of the open layers, and how they are composited onto the layer
+    /// below.
+    layers: Vec<(Vec<[f32; 4]>, CompositeMode)>,
+}
+
+impl Painter<'_> {
+    fn transform(&self) -> Transform {
+        *self.transforms.last().unwrap_or(&Transform::default())
+    }
+
+    fn push_clip_path(&mut self, pen: PathPen) {
+        let mut coverage = vec![0u8; self.width as usize * self.height as usize];
+        if !pen.commands.is_empty() {
+            Mask::new(&pen.commands[..])
+                .size(self.width, self.height)
+                .render_into(&mut coverage, None);
+        }
+        let clip = match self.clips.last() {
+            Some(parent) => coverage
+                .iter()
+                .zip(parent)
+                .map(|(coverage, parent)| f32::from(*coverage) / 255.0 * parent)
+                .collect(),
+            None => coverage
+                .iter()
+                .map(|coverage| f32::from(*coverage) / 255.0)
+                .collect(),
+        };
+        self.clips.push(clip);
+    }
+
+    /// Premultiplied color of a palette entry with the alpha applied.
+    fn color(&self, palette_index: u16, alpha: f32) -> [f32; 4] {
+        let color = if palette_index == FOREGROUND_INDEX {
+            self.foreground
+        } else {
+            self.palette
+                .get(usize::from(palette_index))
+                .copied()
+                .unwrap_or([0.0; 4])
+        };
+        color.map(|channel| channel * alpha.clamp(0.0, 1.0))
+    }
+
+    /// Premultiplied colors of the stops of a gradient.
+    fn stops(&self, color_stops: &[ColorStop]) -> Vec<(f32, [f32; 4])> {
+        color_stops
+            .iter()
+            .map(|stop| (stop.offset, self.color(stop.palette_index, stop.alpha)))
+            .collect()
+    }
+}
+
+impl ColorPainter for Painter<'_> {
+    fn push_transform(&mut self, transform: Transform) {
+        self.transforms.push(self.transform() * transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        let pen = glyph_path(&self.outlines, glyph_id, self.transform());
+        self.push_clip_path(pen);
+    }
+
+    fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+        let pen = box_path(clip_box, self.transform());
+        self.push_clip_path(pen);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn fill(&mut self, brush: Brush<'_>) {
+        // Gradients are defined in font units, pixel centers are mapped back to them
+        let Some(inverse) = invert(&self.transform()) else {
+            return;
+        };
+        let paint: Box<dyn Fn(f32, f32) -> [f32; 4]> = match brush {
+            Brush::Solid {
+                palette_index,
+                alpha,
+            } => {
+                let color = self.color(palette_index, alpha);
+                Box::new(move |_, _| color)
+            }
+            Brush::LinearGradient {
+                p0,
+                p1,
+                color_stops,
+                extend,
+            } => {
+                let stops = self.stops(color_stops);
+                let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+                let length_squared = dx * dx + dy * dy;
+                Box::new(move |x, y| {
+                    let t = if length_squared > 0.0 {
+                        ((x - p0.x) * dx + (y - p0.y) * dy) / length_squared
+                    } else {
+                        0.0
+                    };
+                    gradient_color(&stops, extend, t)
+                })
+            }
+            Brush::RadialGradient {
+                c0,
+                r0,
+                c1,
+                r1,
+                color_stops,
+                extend,
+            } => {
+                let stops = self.stops(color_stops);
+                Box::new(move |x, y| {
+                    radial_parameter((c0.x, c0.y), r0, (c1.x, c1.y), r1, (x, y))
+                        .map_or([0.0; 4], |t| gradient_color(&stops, extend, t))
+                })
+            }
+            Brush::SweepGradient {
+                c0,
+                start_angle,
+                end_angle,
+                color_stops,
+                extend,
+            } => {
+                let stops = self.stops(color_stops);
+                Box::new(move |x, y| {
+                    let angle = (y - c0.y).atan2(x - c0.x).to_degrees().rem_euclid(360.0);
+                    let t = if end_angle != start_angle {
+                        (angle - start_angle) / (end_angle - start_angle)
+                    } else {
+                        0.0
+                    };
+                    gradient_color(&stops, extend, t)
+                })
+            }
+        };
+
+        let width = self.width as usize;
+        let clip = self.clips.last();
+        let Some((pixels, _)) = self.layers.last_mut() else {
+            return;
+        };
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            let coverage = clip.map_or(1.0, |clip| clip[index]);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let (x, y) = ((index % width) as f32 + 0.5, (index / width) as f32 + 0.5);
+            let (x, y) = apply(&inverse, x, y);
+            let source = paint(x, y).map(|channel| channel * coverage);
+            *pixel = composite(CompositeMode::SrcOver, source, *pixel);
+        }
+    }
+
+    fn push_layer(&mut self, composite_mode: CompositeMode) {
+        let len = self.width as usize * self.height as usize;
+        self.layers.push((vec![[0.0; 4]; len], composite_mode));
+    }
+
+    fn pop_layer(&mut self) {
+        if self.layers.len() < 2 {
+            return;
+        }
+        let Some((source, mode)) = self.layers.pop() else {
+            return;
+        };
+        let Some((target, _)) = self.layers.last_mut() else {
+            return;
+        };
+        for (target, source) in target.iter_mut().zip(source) {
+            *target = composite(mode, source, *target);
+        }
+    }
+}
+
+/// Maps a point with the transform.
+fn apply(transform: &Transform, x: f32, y: f32) -> (f32, f32) {
+    (
+        transform.xx * x + transform.xy * y + transform.dx,
+        transform.yx * x + transform.yy * y + transform.dy,
+    )
+}
+
+/// The inverse of the transform, or `None` if it collapses the plane.
+fn invert(transform: &Transform) -> Option<Transform> {
+    let determinant = transform.xx * transform.yy - transform.xy * transform.yx;
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let xx = transform.yy / determinant;
+    let xy = -transform.xy / determinant;
+    let yx = -transform.yx / determinant;
+    let yy = transform.xx / determinant;
+    Some(Transform {
+        xx,
+        yx,
+        xy,
+        yy,
+        dx: -(xx * transform.dx + xy * transform.dy),
+        dy: -(yx * transform.dx + yy * transform.dy),
+    })
+}
+
+fn union(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+fn intersection(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[0].max(b[0]),
+        a[1].max(b[1]),
+        a[2].min(b[2]),
+        a[3].min(b[3]),
+    ]
+}
+
+/// Parameter of the largest circle of a two-point conical gradient that passes through the
+/// point and doesn't have a negative radius, or `None` if there is no such circle.
+fn radial_parameter(
+    c0: (f32, f32),
+    r0: f32,
+    c1: (f32, f32),
+    r1: f32,
+    point: (f32, f32),
+) -> Option<f32> {
+    let (cdx, cdy) = (c1.0 - c0.0, c1.1 - c0.1);
+    let (pdx, pdy) = (point.0 - c0.0, point.1 - c0.1);
+    let dr = r1 - r0;
+    let a = cdx * cdx + cdy * cdy - dr * dr;
+    let b = pdx * cdx + pdy * cdy + r0 * dr;
+    let c = pdx * pdx + pdy * pdy - r0 * r0;
+    let radius = |t: f32| r0 + t * dr;
+    if a.abs() < 1e-6 {
+        if b.abs() < 1e-6 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return (radius(t) >= 0.0).then_some(t);
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let (t1, t2) = ((b + root) / a, (b - root) / a);
+    let (larger, smaller) = if t1 > t2 { (t1, t2) } else { (t2, t1) };
+    if radius(larger) >= 0.0 {
+        Some(larger)
+    } else if radius(smaller) >= 0.0 {
+        Some(smaller)
+    } else {
+        None
+    }
+}
+
+/// Premultiplied color of the gradient at the parameter, with the stops sorted by offset.
+fn gradient_color(stops: &[(f32, [f32; 4])], extend: Extend, t: f32) -> [f32; 4] {
+    let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+        return [0.0; 4];
+    };
+    let t = match extend {
+        Extend::Repeat => t - t.floor(),
+        Extend::Reflect => {
+            let t = t.rem_euclid(2.0);
+            if t > 1.0 {
+                2.0 - t
+            } else {
+                t
+            }
+        }
+        _ => t,
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+    for pair in stops.windows(2) {
+        let ((start, from), (end, to)) = (pair[0], pair[1]);
+        if t <= end {
+            let fraction = if end > start {
+                (t - start) / (end - start)
+            } else {
+                1.0
+            };
+            return [0, 1, 2, 3]
+                .map(|channel| from[channel] + (to[channel] - from[channel]) * fraction);
+        }
+    }
+    last.1
+}
+
+/// Composites a premultiplied source pixel onto a premultiplied target pixel. Blend modes
+/// that mix hue, saturation and luminosity are drawn as source-over.
+fn composite(mode: CompositeMode, source: [f32; 4], target: [f32; 4]) -> [f32; 4] {
+    let (source_alpha, target_alpha) = (source[3], target[3]);
+    let porter_duff = |source_factor: f32, target_factor: f32| {
+        [0, 1, 2, 3].map(|channel| {
+            (source[channel] * source_factor + target[channel] * target_factor).min(1.0)
+        })
+    };
+    let blend: fn(f32, f32) -> f32 = match mode {
+        CompositeMode::Clear => return [0.0; 4],
+        CompositeMode::Src => return source,
+        CompositeMode::Dest => return target,
+        CompositeMode::DestOver => return porter_duff(1.0 - target_alpha, 1.0),
+        CompositeMode::SrcIn => return porter_duff(target_alpha, 0.0),
+        CompositeMode::DestIn => return porter_duff(0.0, source_alpha),
+        CompositeMode::SrcOut => return porter_duff(1.0 - target_alpha, 0.0),
+        CompositeMode::DestOut => return porter_duff(0.0, 1.0 - source_alpha),
+        CompositeMode::SrcAtop => return porter_duff(target_alpha, 1.0 - source_alpha),
+        CompositeMode::DestAtop => return porter_duff(1.0 - target_alpha, source_alpha),
+        CompositeMode::Xor => return porter_duff(1.0 - target_alpha, 1.0 - source_alpha),
+        CompositeMode::Plus => return porter_duff(1.0, 1.0),
+        CompositeMode::Screen => |s, d| s + d - s * d,
+        CompositeMode::Multiply => |s, d| s * d,
+        CompositeMode::Darken => f32::min,
+        CompositeMode::Lighten => f32::max,
+        CompositeMode::Difference => |s, d| (s - d).abs(),
+        CompositeMode::Exclusion => |s, d| s + d - 2.0 * s * d,
+        CompositeMode::Overlay => |s, d| hard_light(d, s),
+        CompositeMode::HardLight => hard_light,
+        CompositeMode::ColorDodge => |s, d| {
+            if d <= 0.0 {
+                0.0
+            } else if s >= 1.0 {
+                1.0
+            } else {
+                (d / (1.0 - s)).min(1.0)
+            }
+        },
+        CompositeMode::ColorBurn => |s, d| {
+            if d >= 1.0 {
+                1.0
+            } else if s <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - d) / s).min(1.0)
+            }
+        },
+        CompositeMode::SoftLight => |s, d| {
+            if s <= 0.5 {
+                d - (1.0 - 2.0 * s) * d * (1.0 - d)
+            } else {
+                let g = if d <= 0.25 {
+                    ((16.0 * d - 12.0) * d + 4.0) * d
+                } else {
+                    d.sqrt()
+                };
+                d + (2.0 * s - 1.0) * (g - d)
+            }
+        },
+        _ => return porter_duff(1.0, 1.0 - source_alpha),
+    };
+    // Separable blend modes mix the straight colors where both pixels are painted
+    let straight = |pixel: [f32; 4], channel: usize| {
+        if pixel[3] > 0.0 {
+            pixel[channel] / pixel[3]
+        } else {
+            0.0
+        }
+    };
+    let mut result = [0.0; 4];
+    for (channel, value) in result.iter_mut().enumerate().take(3) {
+        let mixed = blend(straight(source, channel), straight(target, channel));
+        *value = (source[channel] * (1.0 - target_alpha)
+            + target[channel] * (1.0 - source_alpha)
+            + source_alpha * target_alpha * mixed)
+            .clamp(0.0, 1.0);
+    }
+    result[3] = source_alpha + target_alpha - source_alpha * target_alpha;
+    result
+}
+
+fn hard_light(source: f32, target: f32) -> f32 {
+    if source <= 0.5 {
+        target * 2.0 * source
+    } else {
+        let source = 2.0 * source - 1.0;
+        source + target - source * target
+    }
+}
+
+fn premultiply(color: [f32; 4]) -> [f32; 4] {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+fn unpremultiply_u8(color: [f32; 4]) -> [u8; 4] {
+    let alpha = color[3].clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return [0; 4];
+    }
+    let channel = |value: f32| ((value / alpha).clamp(0.0, 1.0) * 255.0).round() as u8;
+    [
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2]),
+        (alpha * 255.0).round() as u8,
+    ]
+}
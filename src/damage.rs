@@ -0,0 +1,165 @@
+//! Tracking of the parts of a rasterized texture that change between rasterizations.
+//!
+//! Every rasterization records a [`RasterRun`] per layout line: a hash of everything that
+//! affects how the line is drawn and the pixels it covers. Lines whose records differ from the
+//! previous rasterization are damaged, and only their bounds are cleared and drawn again.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// A rectangle of pixels in a rasterized texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DirtyRect {
+    /// Left edge in pixels.
+    pub x: u32,
+    /// Top edge in pixels.
+    pub y: u32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl DirtyRect {
+    /// A rectangle covering a whole texture of the given size.
+    pub const fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if the rectangle contains no pixels.
+    pub const fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns `true` if the pixel is inside the rectangle.
+    pub const fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Pixel bounds with inclusive minimum and exclusive maximum, which can lie outside of the
+/// texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct PixelBounds {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl PixelBounds {
+    pub const EMPTY: Self = Self {
+        min_x: i32::MAX,
+        min_y: i32::MAX,
+        max_x: i32::MIN,
+        max_y: i32::MIN,
+    };
+
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            min_x: x,
+            min_y: y,
+            max_x: x + width as i32,
+            max_y: y + height as i32,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x >= self.max_x || self.min_y >= self.max_y
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        Self {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    pub fn translate(self, x: i32, y: i32) -> Self {
+        Self {
+            min_x: self.min_x + x,
+            min_y: self.min_y + y,
+            max_x: self.max_x + x,
+            max_y: self.max_y + y,
+        }
+    }
+
+    /// Pixels inside both bounds, which can be empty.
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min_x < other.max_x
+            && other.min_x < self.max_x
+            && self.min_y < other.max_y
+            && other.min_y < self.max_y
+    }
+
+    /// Clips the bounds to a texture of the given size.
+    pub fn clip(&self, width: u32, height: u32) -> DirtyRect {
+        let min_x = self.min_x.clamp(0, width as i32);
+        let min_y = self.min_y.clamp(0, height as i32);
+        let max_x = self.max_x.clamp(min_x, width as i32);
+        let max_y = self.max_y.clamp(min_y, height as i32);
+        DirtyRect {
+            x: min_x as u32,
+            y: min_y as u32,
+            width: (max_x - min_x) as u32,
+            height: (max_y - min_y) as u32,
+        }
+    }
+}
+
+/// What was drawn for one layout line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RasterRun {
+    /// Hash of the glyphs of the line with their positions and colors.
+    pub hash: u64,
+    /// Pixels covered by the glyphs of the line.
+    pub bounds: PixelBounds,
+}
+
+impl RasterRun {
+    pub fn new(content: impl Hash, bounds: PixelBounds) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            bounds,
+        }
+    }
+}
+
+/// Returns the pixels that differ between two rasterizations of the lines.
+pub(crate) fn damaged_bounds(old: &[RasterRun], new: &[RasterRun]) -> PixelBounds {
+    let mut damage = PixelBounds::EMPTY;
+    for index in 0..old.len().max(new.len()) {
+        match (old.get(index), new.get(index)) {
+            (Some(old), Some(new)) if old == new => {}
+            (old, new) => {
+                for run in old.into_iter().chain(new) {
+                    damage = damage.union(run.bounds);
+                }
+            }
+        }
+    }
+    damage
+}
@@ -0,0 +1,116 @@
+//! Renderer-agnostic drawing commands.
+//!
+//! [`crate::TextState::display_list`] describes everything there is to draw for a text state:
+//! the background, highlight and selection backgrounds, glyphs, decoration lines, and the caret.
+//! Positions are in LOGICAL pixels relative to the text area, with scroll and vertical alignment
+//! applied, so a backend only has to draw rectangles and glyphs without walking the layout
+//! itself.
+
+use crate::math::{Point, Rect};
+use crate::utils::with_font_ref;
+use cosmic_text::skrifa::instance::{LocationRef, Size as FontUnitsSize};
+use cosmic_text::skrifa::MetadataProvider;
+use cosmic_text::{CacheKey, Color, FontSystem, LayoutGlyph};
+use std::ops::Range;
+
+/// A range of the text drawn with a background color, e.g. search results or spelling errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Byte offsets of the highlighted text in [`crate::TextState::text`].
+    pub range: Range<usize>,
+    /// Background color of the range.
+    pub color: Color,
+}
+
+impl Highlight {
+    /// Creates a highlight of the given byte range.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::Highlight;
+    /// use cosmic_text::Color;
+    ///
+    /// let highlight = Highlight::new(0..5, Color::rgba(255, 255, 0, 128));
+    /// ```
+    pub const fn new(range: Range<usize>, color: Color) -> Self {
+        Self { range, color }
+    }
+}
+
+/// A glyph to draw, in LOGICAL pixels relative to the text area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayGlyph {
+    /// Key to rasterize the glyph with, for example with [`cosmic_text::SwashCache`]. The
+    /// glyph is rasterized at the font size in DEVICE pixels, with its subpixel offset.
+    pub cache_key: CacheKey,
+    /// Position of the glyph origin on the baseline. Multiplied by the scale factor, this is
+    /// a whole number of DEVICE pixels, so the bitmap can be drawn without resampling.
+    pub position: Point,
+    /// Color of the glyph.
+    pub color: Color,
+    /// The glyph is rotated by 90 degrees clockwise around its origin, as sideways glyphs in
+    /// vertical text are.
+    pub rotated: bool,
+}
+
+/// A single drawing operation. Commands are listed in the order they're painted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    /// The background of the whole text area, see [`crate::TextState::background`].
+    Background { rect: Rect, color: Color },
+    /// Background of a part of a [`Highlight`] on one line.
+    Highlight { rect: Rect, color: Color },
+    /// Background of the selection on one line, see [`crate::Selection::lines`].
+    Selection { rect: Rect },
+    /// The glyphs of one line.
+    GlyphRun { glyphs: Vec<DisplayGlyph> },
+    /// An underline, overline or line through the text of one line.
+    Decoration { rect: Rect, color: Color },
+    /// The caret. There are two carets at a boundary between left-to-right and right-to-left
+    /// text, see [`crate::SplitCaret`]. A block caret comes before the glyphs, other shapes
+    /// after them.
+    Caret { rect: Rect },
+}
+
+/// Vertical positions of decoration lines relative to the baseline, in DEVICE pixels with y
+/// going down.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DecorationMetrics {
+    pub underline_offset: f32,
+    pub overline_offset: f32,
+    pub line_through_offset: f32,
+    pub thickness: f32,
+}
+
+impl DecorationMetrics {
+    /// Reads the decoration metrics from the font of the glyph, falling back to typical values
+    /// if the font doesn't have them.
+    pub fn new(font_system: &mut FontSystem, glyph: &LayoutGlyph) -> Self {
+        let font_size = glyph.font_size;
+        let fallback = Self {
+            underline_offset: font_size * 0.1,
+            overline_offset: -font_size * 0.8,
+            line_through_offset: -font_size * 0.3,
+            thickness: (font_size / 14.0).max(1.0),
+        };
+        let Some(metrics) = with_font_ref(font_system, glyph.font_id, glyph.font_weight, |font| {
+            Some(font.metrics(FontUnitsSize::new(font_size), LocationRef::default()))
+        }) else {
+            return fallback;
+        };
+        // Font metrics have y going up
+        let thickness = metrics
+            .underline
+            .map_or(fallback.thickness, |underline| underline.thickness.max(1.0));
+        Self {
+            underline_offset: metrics
+                .underline
+                .map_or(fallback.underline_offset, |underline| -underline.offset),
+            overline_offset: -metrics.ascent,
+            line_through_offset: metrics
+                .strikeout
+                .map_or(fallback.line_through_offset, |strikeout| -strikeout.offset),
+            thickness,
+        }
+    }
+}
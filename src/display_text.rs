@@ -0,0 +1,250 @@
+//! White space collapsing, paragraph direction marks, and hyphenation points.
+//!
+//! The text is stored as it was set, and a modified copy is shaped instead when the
+//! [`WhiteSpace`] mode collapses white space, the [`TextDirection`] is forced, or words are
+//! hyphenated automatically.
+//! [`DisplayText`] keeps the mapping between byte offsets in both, so that cursors in the shaped
+//! buffer can be turned into offsets in the original text. Edits only rebuild the part of the
+//! display text around them, see [`DisplayText::replace`].
+
+use crate::hyphenation::{hyphenation_points, SOFT_HYPHEN};
+use crate::style::{Language, TextDirection, WhiteSpace};
+use std::ops::Range;
+
+/// Text as it's shaped, with white space collapsed, and direction marks and soft hyphens added,
+/// and the mapping back to the original text.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct DisplayText {
+    text: String,
+    /// `(display byte offset, original byte offset)` of every char of the display text and of
+    /// every removed sequence of white space, followed by the end offsets of both texts. Sorted
+    /// by both offsets.
+    offsets: Vec<(usize, usize)>,
+    /// Display byte offsets of the direction marks, sorted. Every line starts with a mark,
+    /// except for the empty line after a trailing line break.
+    marks: Vec<usize>,
+    settings: Settings,
+}
+
+/// What the display text changes in the original text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Settings {
+    white_space: WhiteSpace,
+    mark: Option<char>,
+    hyphenation_language: Option<Language>,
+}
+
+impl DisplayText {
+    /// Collapses white space in `text` according to `white_space`, starts every paragraph
+    /// with a mark that forces `direction`, and inserts soft hyphens at the hyphenation points
+    /// for `hyphenation_language`. Returns `None` if none of these can change the text.
+    ///
+    /// Collapsing white space also removes it from the start and the end of the text, and
+    /// around line breaks that are preserved.
+    pub fn new(
+        text: &str,
+        white_space: WhiteSpace,
+        direction: TextDirection,
+        hyphenation_language: Option<Language>,
+    ) -> Option<Self> {
+        let settings = Settings {
+            white_space,
+            mark: direction.mark(),
+            hyphenation_language,
+        };
+        if !white_space.collapses_spaces() && settings.mark.is_none() {
+            hyphenation_language?;
+        }
+
+        let mut display = String::with_capacity(text.len());
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let mut marks = Vec::new();
+        settings.build(text, 0..text.len(), &mut display, &mut offsets, &mut marks);
+        offsets.push((display.len(), text.len()));
+        Some(Self {
+            text: display,
+            offsets,
+            marks,
+            settings,
+        })
+    }
+
+    /// The text to shape.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Updates the display text after `removed` bytes of the original text were replaced with
+    /// the `inserted` range of `text`, the original text after the edit. Only the paragraph,
+    /// or the words when line breaks collapse, around the edit is collapsed again.
+    pub fn replace(&mut self, text: &str, inserted: Range<usize>, removed: usize) {
+        let window = self.settings.window(text, inserted.clone());
+        let old_end = window.end + removed - inserted.len();
+        let first = self
+            .offsets
+            .partition_point(|&(_, original)| original < window.start);
+        let last = self
+            .offsets
+            .partition_point(|&(_, original)| original < old_end);
+        let display_start = self.offsets[first].0;
+        let display_end = self.offsets[last].0;
+
+        let mut display = String::with_capacity(window.len());
+        let mut offsets = Vec::with_capacity(window.len());
+        let mut marks = Vec::new();
+        self.settings
+            .build(text, window, &mut display, &mut offsets, &mut marks);
+        let shift =
+            |display_offset: usize| display_offset + display.len() - (display_end - display_start);
+        for (display_offset, original) in &mut self.offsets[last..] {
+            *display_offset = shift(*display_offset);
+            *original = *original + inserted.len() - removed;
+        }
+        let first_mark = self.marks.partition_point(|&mark| mark < display_start);
+        let last_mark = self.marks.partition_point(|&mark| mark < display_end);
+        for mark in &mut self.marks[last_mark..] {
+            *mark = shift(*mark);
+        }
+        self.marks.splice(
+            first_mark..last_mark,
+            marks.into_iter().map(|mark| mark + display_start),
+        );
+        self.offsets.splice(
+            first..last,
+            offsets
+                .into_iter()
+                .map(|(display, original)| (display + display_start, original)),
+        );
+        self.text
+            .replace_range(display_start..display_end, &display);
+    }
+
+    /// Returns `true` if the line at the given index of the display text starts with a
+    /// direction mark.
+    pub fn line_starts_with_mark(&self, line_index: usize) -> bool {
+        line_index < self.marks.len()
+    }
+
+    /// Maps a byte offset in the display text to the original text.
+    pub fn to_original(&self, display_offset: usize) -> usize {
+        let index = self
+            .offsets
+            .partition_point(|&(display, _)| display <= display_offset);
+        self.offsets[index.saturating_sub(1)].1
+    }
+
+    /// Maps a byte offset in the original text to the display text. Offsets inside a collapsed
+    /// sequence map to the space it collapsed into.
+    pub fn to_display(&self, original_offset: usize) -> usize {
+        let index = self
+            .offsets
+            .partition_point(|&(_, original)| original <= original_offset);
+        self.offsets[index.saturating_sub(1)].0
+    }
+}
+
+impl Settings {
+    /// Returns `true` if the char is part of a sequence of white space that collapses.
+    fn is_collapsible(&self, character: char) -> bool {
+        self.white_space.collapses_spaces()
+            && match character {
+                ' ' | '\t' => true,
+                '\n' | '\r' => self.white_space.collapses_line_breaks(),
+                _ => false,
+            }
+    }
+
+    /// The smallest range of `text` around `edit` whose display text doesn't depend on the text
+    /// outside of it: the paragraphs the edit touches, or when line breaks collapse, the words
+    /// it touches with the white space after them.
+    fn window(&self, text: &str, edit: Range<usize>) -> Range<usize> {
+        if !self.white_space.collapses_line_breaks() {
+            let start = text[..edit.start].rfind('\n').map_or(0, |index| index + 1);
+            let end = text[edit.end..]
+                .find('\n')
+                .map_or(text.len(), |index| edit.end + index + 1);
+            return start..end;
+        }
+
+        // The window starts with the white space before the first word, so that it collapses
+        // the same way as when the whole text is collapsed
+        let before = text[..edit.start].trim_end_matches(|c| !self.is_collapsible(c));
+        let start = before.trim_end_matches(|c| self.is_collapsible(c)).len();
+        let after = text[edit.end..].trim_start_matches(|c| !self.is_collapsible(c));
+        let end = text.len() - after.trim_start_matches(|c| self.is_collapsible(c)).len();
+        start..end
+    }
+
+    /// Appends the display text of the `window` of `text` to `display`, the offsets of its
+    /// chars to `offsets`, and the offsets of its direction marks to `marks`. The window has to
+    /// start at the start of the text, of a paragraph, or of a sequence of white space.
+    fn build(
+        &self,
+        text: &str,
+        window: Range<usize>,
+        display: &mut String,
+        offsets: &mut Vec<(usize, usize)>,
+        marks: &mut Vec<usize>,
+    ) {
+        // Marks map to the start of their paragraph in the original text. The empty line after
+        // a trailing line break doesn't need a direction.
+        let push_mark = |display: &mut String, offsets: &mut Vec<_>, marks: &mut Vec<_>, offset| {
+            if let Some(mark) = self.mark {
+                marks.push(display.len());
+                offsets.push((display.len(), offset));
+                display.push(mark);
+            }
+        };
+        let is_line_break = |character: char| matches!(character, '\n' | '\r');
+        // White space collapses into a single space, or nothing at the start and the end of the
+        // text and around preserved line breaks. Removed white space maps to the display offset
+        // it would have been at.
+        let push_white_space = |display: &mut String, offsets: &mut Vec<_>, range: Range<usize>| {
+            offsets.push((display.len(), range.start));
+            let is_trimmed = range.start == 0
+                || range.end == text.len()
+                || text[..range.start].ends_with(is_line_break)
+                || text[range.end..].starts_with(is_line_break);
+            if !is_trimmed {
+                display.push(' ');
+            }
+        };
+
+        if window.start == 0 || (text[..window.start].ends_with('\n') && window.start < text.len())
+        {
+            push_mark(display, offsets, marks, window.start);
+        }
+        let mut hyphenation_points = self
+            .hyphenation_language
+            .map(|language| hyphenation_points(&text[window.clone()], language))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|point| point + window.start)
+            .peekable();
+        let mut white_space_start = None;
+        for (offset, character) in text[window.clone()].char_indices() {
+            let offset = offset + window.start;
+            if self.is_collapsible(character) {
+                white_space_start.get_or_insert(offset);
+                continue;
+            }
+            if let Some(start) = white_space_start.take() {
+                push_white_space(display, offsets, start..offset);
+            }
+            // Soft hyphens map to the char after them, like the marks
+            if hyphenation_points.next_if_eq(&offset).is_some() {
+                offsets.push((display.len(), offset));
+                display.push(SOFT_HYPHEN);
+            }
+            offsets.push((display.len(), offset));
+            display.push(character);
+            let next_offset = offset + character.len_utf8();
+            if character == '\n' && next_offset < window.end {
+                push_mark(display, offsets, marks, next_offset);
+            }
+        }
+        if let Some(start) = white_space_start {
+            push_white_space(display, offsets, start..window.end);
+        }
+    }
+}
@@ -0,0 +1,555 @@
+//! Signed distance field glyphs for resolution-independent text.
+//!
+//! Instead of coverage, every pixel of a distance field glyph stores its distance to the glyph
+//! outline, so a renderer can draw the glyph at any scale with sharp edges by thresholding the
+//! interpolated distance. Multi-channel fields (MSDF) store distances to differently colored
+//! edges in the red, green and blue channels, which keeps corners sharp when the median of the
+//! channels is thresholded.
+//!
+//! Fields are generated from the flattened glyph outlines. MSDF edges are colored like in
+//! Viktor Chlumsky's msdfgen, with pixels whose channels disagree with the true distance
+//! replaced by it.
+
+use ahash::HashMap;
+use cosmic_text::{CacheKey, FontSystem, SwashCache};
+use zeno::{Command, Vector};
+
+/// Maximum distance between a curve and the line segments it's flattened to, in pixels.
+const FLATTEN_TOLERANCE: f32 = 0.05;
+/// Sine of the smallest angle between two outline segments that makes a corner.
+const CORNER_THRESHOLD: f32 = 0.1411;
+
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const CYAN: u8 = GREEN | BLUE;
+const MAGENTA: u8 = RED | BLUE;
+const YELLOW: u8 = RED | GREEN;
+
+/// The kind of distance field to generate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum DistanceFieldKind {
+    /// One channel with the distance to the outline. Corners are rounded when the glyph is
+    /// magnified.
+    #[default]
+    Sdf,
+    /// Distances to differently colored edges in the red, green and blue channels, and the
+    /// distance to the outline in the alpha channel. The median of the color channels keeps
+    /// corners sharp.
+    Msdf,
+}
+
+/// Settings of distance field glyphs.
+///
+/// Distances are stored as `0.5 + distance / (2 * spread)`, clamped between 0 and 1 and scaled
+/// to a byte. Values above 0.5 are inside of the glyph. A renderer draws the edge where the
+/// value crosses 0.5, antialiased over `spread` bitmap pixels in each direction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DistanceField {
+    /// The kind of field.
+    pub kind: DistanceFieldKind,
+    /// Distance from the outline at which the field saturates, in pixels of the glyph bitmap.
+    /// Bitmaps are padded by this much on every side.
+    pub spread: u32,
+}
+
+impl DistanceField {
+    /// Single-channel fields with the given spread in pixels.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{DistanceField, DistanceFieldKind};
+    ///
+    /// let field = DistanceField::sdf(4);
+    /// assert_eq!(field.kind, DistanceFieldKind::Sdf);
+    /// ```
+    pub const fn sdf(spread: u32) -> Self {
+        Self {
+            kind: DistanceFieldKind::Sdf,
+            spread,
+        }
+    }
+
+    /// Multi-channel fields with the given spread in pixels.
+    pub const fn msdf(spread: u32) -> Self {
+        Self {
+            kind: DistanceFieldKind::Msdf,
+            spread,
+        }
+    }
+
+    /// Number of bytes per pixel of the glyph bitmaps.
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self.kind {
+            DistanceFieldKind::Sdf => 1,
+            DistanceFieldKind::Msdf => 4,
+        }
+    }
+
+    /// Coverage of a pixel with the given field value, drawn at the scale the field was
+    /// generated for. For [`DistanceFieldKind::Msdf`], pass the median of the color channels.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::DistanceField;
+    ///
+    /// let field = DistanceField::sdf(4);
+    /// assert!((field.coverage(128) - 0.5).abs() < 0.05);
+    /// assert_eq!(field.coverage(255), 1.0);
+    /// assert_eq!(field.coverage(0), 0.0);
+    /// ```
+    pub fn coverage(self, value: u8) -> f32 {
+        let distance = (f32::from(value) / 255.0 - 0.5) * 2.0 * self.spread as f32;
+        (distance + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+/// A distance field glyph bitmap.
+#[derive(Debug, Clone)]
+pub(crate) struct DistanceFieldGlyph {
+    /// Offset of the left edge from the glyph origin.
+    pub left: i32,
+    /// Offset of the top edge above the baseline.
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Rows from top to bottom, see [`DistanceField::bytes_per_pixel`].
+    pub data: Vec<u8>,
+}
+
+/// Cache for distance field glyphs.
+#[derive(Debug, Default)]
+pub struct DistanceFieldCache {
+    glyphs: HashMap<(CacheKey, DistanceField), Option<DistanceFieldGlyph>>,
+}
+
+impl DistanceFieldCache {
+    /// Creates an empty cache. Fields are generated the first time a glyph is rasterized with
+    /// [`crate::TextState::distance_field`] or returned by
+    /// [`crate::TextState::distance_field_quads`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the distance field of the glyph, or `None` if the glyph has no outline.
+    pub(crate) fn get(
+        &mut self,
+        font_system: &mut FontSystem,
+        swash_cache: &mut SwashCache,
+        cache_key: CacheKey,
+        field: DistanceField,
+    ) -> Option<&DistanceFieldGlyph> {
+        self.glyphs
+            .entry((cache_key, field))
+            .or_insert_with(|| {
+                let commands = swash_cache.get_outline_commands(font_system, cache_key)?;
+                let offset = Vector::new(cache_key.x_bin.as_float(), cache_key.y_bin.as_float());
+                generate(commands, offset, field)
+            })
+            .as_ref()
+    }
+}
+
+/// A straight piece of a flattened outline.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: Vector,
+    end: Vector,
+    /// Colors of the edge the segment belongs to.
+    color: u8,
+    /// The segment starts an edge, so its start is a corner.
+    edge_start: bool,
+    /// The segment ends an edge.
+    edge_end: bool,
+}
+
+/// A curve or line of the outline, flattened to points.
+struct Piece {
+    start_direction: Vector,
+    end_direction: Vector,
+    points: Vec<Vector>,
+}
+
+fn sub(a: Vector, b: Vector) -> Vector {
+    Vector::new(a.x - b.x, a.y - b.y)
+}
+
+fn dot(a: Vector, b: Vector) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Vector, b: Vector) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn length(a: Vector) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: Vector) -> Vector {
+    let length = length(a);
+    if length > 0.0 {
+        Vector::new(a.x / length, a.y / length)
+    } else {
+        a
+    }
+}
+
+/// Returns the first direction that isn't zero.
+fn direction(candidates: &[Vector]) -> Vector {
+    candidates
+        .iter()
+        .copied()
+        .find(|direction| length(*direction) > 1e-6)
+        .map_or(Vector::ZERO, normalize)
+}
+
+fn is_corner(a: Vector, b: Vector) -> bool {
+    dot(a, b) <= 0.0 || cross(a, b).abs() > CORNER_THRESHOLD
+}
+
+/// Splits the outline into closed contours of pieces, translated by the offset.
+fn contours(commands: &[Command], offset: Vector) -> Vec<Vec<Piece>> {
+    let mut contours = Vec::new();
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut start = Vector::ZERO;
+    let mut current = Vector::ZERO;
+    let translate = |point: &Vector| Vector::new(point.x + offset.x, point.y + offset.y);
+    let mut close = |pieces: &mut Vec<Piece>, current: Vector, start: Vector| {
+        if length(sub(start, current)) > 1e-6 {
+            let direction = direction(&[sub(start, current)]);
+            pieces.push(Piece {
+                start_direction: direction,
+                end_direction: direction,
+                points: vec![start],
+            });
+        }
+        if !pieces.is_empty() {
+            contours.push(std::mem::take(pieces));
+        }
+    };
+    for command in commands {
+        match command {
+            Command::MoveTo(point) => {
+                close(&mut pieces, current, start);
+                start = translate(point);
+                current = start;
+            }
+            Command::LineTo(point) => {
+                let point = translate(point);
+                if length(sub(point, current)) > 1e-6 {
+                    let direction = direction(&[sub(point, current)]);
+                    pieces.push(Piece {
+                        start_direction: direction,
+                        end_direction: direction,
+                        points: vec![point],
+                    });
+                }
+                current = point;
+            }
+            Command::QuadTo(control, point) => {
+                let (control, point) = (translate(control), translate(point));
+                let deviation = length(Vector::new(
+                    current.x - 2.0 * control.x + point.x,
+                    current.y - 2.0 * control.y + point.y,
+                ));
+                let steps = (deviation / (8.0 * FLATTEN_TOLERANCE)).sqrt().ceil();
+                let steps = steps.clamp(1.0, 64.0) as usize;
+                let points = (1..=steps)
+                    .map(|step| {
+                        let t = step as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        Vector::new(
+                            mt * mt * current.x + 2.0 * mt * t * control.x + t * t * point.x,
+                            mt * mt * current.y + 2.0 * mt * t * control.y + t * t * point.y,
+                        )
+                    })
+                    .collect();
+                pieces.push(Piece {
+                    start_direction: direction(&[sub(control, current), sub(point, current)]),
+                    end_direction: direction(&[sub(point, control), sub(point, current)]),
+                    points,
+                });
+                current = point;
+            }
+            Command::CurveTo(control1, control2, point) => {
+                let (control1, control2, point) =
+                    (translate(control1), translate(control2), translate(point));
+                let deviation = length(Vector::new(
+                    current.x - 2.0 * control1.x + control2.x,
+                    current.y - 2.0 * control1.y + control2.y,
+                ))
+                .max(length(Vector::new(
+                    control1.x - 2.0 * control2.x + point.x,
+                    control1.y - 2.0 * control2.y + point.y,
+                )));
+                let steps = (deviation * 0.75 / FLATTEN_TOLERANCE).sqrt().ceil();
+                let steps = steps.clamp(1.0, 64.0) as usize;
+                let points = (1..=steps)
+                    .map(|step| {
+                        let t = step as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        let (a, b, c, d) =
+                            (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                        Vector::new(
+                            a * current.x + b * control1.x + c * control2.x + d * point.x,
+                            a * current.y + b * control1.y + c * control2.y + d * point.y,
+                        )
+                    })
+                    .collect();
+                pieces.push(Piece {
+                    start_direction: direction(&[
+                        sub(control1, current),
+                        sub(control2, current),
+                        sub(point, current),
+                    ]),
+                    end_direction: direction(&[
+                        sub(point, control2),
+                        sub(point, control1),
+                        sub(point, current),
+                    ]),
+                    points,
+                });
+                current = point;
+            }
+            Command::Close => {
+                close(&mut pieces, current, start);
+                current = start;
+            }
+        }
+    }
+    close(&mut pieces, current, start);
+    contours
+}
+
+/// Flattens a contour into segments with edge colors.
+fn colored_segments(contour: &[Piece], segments: &mut Vec<Segment>) {
+    let corners: Vec<bool> = (0..contour.len())
+        .map(|index| {
+            let previous = &contour[(index + contour.len() - 1) % contour.len()];
+            is_corner(previous.end_direction, contour[index].start_direction)
+        })
+        .collect();
+    let corner_count = corners.iter().filter(|corner| **corner).count();
+
+    // Start at a corner, so that every edge is a run of pieces
+    let first = corners.iter().position(|corner| *corner).unwrap_or(0);
+    let mut contour_segments = Vec::new();
+    let mut start = contour[(first + contour.len() - 1) % contour.len()]
+        .points
+        .last()
+        .copied()
+        .unwrap_or(Vector::ZERO);
+    let mut edge = 0;
+    for offset in 0..contour.len() {
+        let index = (first + offset) % contour.len();
+        if offset > 0 && corners[index] {
+            edge += 1;
+        }
+        for (point_index, point) in contour[index].points.iter().enumerate() {
+            contour_segments.push((
+                Segment {
+                    start,
+                    end: *point,
+                    color: WHITE,
+                    edge_start: corners[index] && point_index == 0,
+                    edge_end: false,
+                },
+                edge,
+            ));
+            start = *point;
+        }
+    }
+
+    let segment_count = contour_segments.len();
+    for (index, (segment, edge)) in contour_segments.iter_mut().enumerate() {
+        segment.color = match corner_count {
+            // Smooth contours only need one channel
+            0 => WHITE,
+            // A teardrop is split into three edges
+            1 => [MAGENTA, WHITE, YELLOW][(index * 3 / segment_count).min(2)],
+            // Neighboring edges share exactly one channel. The last edge also has to differ
+            // from the first.
+            _ if corner_count % 3 == 1 && *edge == corner_count - 1 => MAGENTA,
+            _ => [CYAN, MAGENTA, YELLOW][*edge % 3],
+        };
+    }
+    for index in 0..segment_count {
+        let mut segment = contour_segments[index].0;
+        let previous = &contour_segments[(index + segment_count - 1) % segment_count].0;
+        let next = &contour_segments[(index + 1) % segment_count].0;
+        // Edges of a teardrop also meet where the color changes
+        segment.edge_start |= previous.color != segment.color;
+        segment.edge_end = next.edge_start || next.color != segment.color;
+        segments.push(segment);
+    }
+}
+
+/// Distance from the point to the segment, how far along the segment the nearest point is
+/// without clamping, and the side of the segment the point is on.
+fn segment_distance(segment: &Segment, point: Vector) -> (f32, f32, f32) {
+    let direction = sub(segment.end, segment.start);
+    let to_point = sub(point, segment.start);
+    let length_squared = dot(direction, direction).max(1e-12);
+    let t = dot(to_point, direction) / length_squared;
+    let clamped = t.clamp(0.0, 1.0);
+    let nearest = Vector::new(
+        segment.start.x + direction.x * clamped,
+        segment.start.y + direction.y * clamped,
+    );
+    (length(sub(point, nearest)), t, cross(direction, to_point))
+}
+
+/// The nearest segment so far for one channel.
+#[derive(Clone, Copy)]
+struct Nearest {
+    distance: f32,
+    orthogonality: f32,
+    segment: usize,
+}
+
+fn generate(
+    commands: &[Command],
+    offset: Vector,
+    field: DistanceField,
+) -> Option<DistanceFieldGlyph> {
+    let contours = contours(commands, offset);
+    let mut segments = Vec::new();
+    for contour in &contours {
+        colored_segments(contour, &mut segments);
+    }
+    if segments.is_empty() {
+        return None;
+    }
+
+    // Inside is on the left of segments if the outer contours run counterclockwise
+    let area: f32 = segments
+        .iter()
+        .map(|segment| cross(segment.start, segment.end))
+        .sum();
+    let orientation = if area >= 0.0 { 1.0 } else { -1.0 };
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for segment in &segments {
+        min_x = min_x.min(segment.start.x);
+        min_y = min_y.min(segment.start.y);
+        max_x = max_x.max(segment.start.x);
+        max_y = max_y.max(segment.start.y);
+    }
+    let spread = field.spread.max(1) as f32;
+    let left = (min_x - spread).floor() as i32;
+    let right = (max_x + spread).ceil() as i32;
+    let top = (max_y + spread).ceil() as i32;
+    let bottom = (min_y - spread).floor() as i32;
+    let width = (right - left) as u32;
+    let height = (top - bottom) as u32;
+    let encode =
+        |distance: f32| ((0.5 + distance / (2.0 * spread)).clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    let mut data = Vec::with_capacity(width as usize * height as usize * field.bytes_per_pixel());
+    for row in 0..height {
+        for column in 0..width {
+            let point = Vector::new(
+                left as f32 + column as f32 + 0.5,
+                top as f32 - row as f32 - 0.5,
+            );
+            let mut winding = 0;
+            let mut true_distance = f32::MAX;
+            let empty = Nearest {
+                distance: f32::MAX,
+                orthogonality: 0.0,
+                segment: 0,
+            };
+            let mut channels = [empty; 3];
+            for (index, segment) in segments.iter().enumerate() {
+                // Nonzero winding of a ray to the right
+                if (segment.start.y <= point.y) != (segment.end.y <= point.y) {
+                    let t = (point.y - segment.start.y) / (segment.end.y - segment.start.y);
+                    if segment.start.x + t * (segment.end.x - segment.start.x) > point.x {
+                        winding += if segment.end.y > segment.start.y {
+                            1
+                        } else {
+                            -1
+                        };
+                    }
+                }
+                let (distance, _, side) = segment_distance(segment, point);
+                true_distance = true_distance.min(distance);
+                if field.kind == DistanceFieldKind::Sdf {
+                    continue;
+                }
+                let orthogonality =
+                    (side / length(sub(segment.end, segment.start)).max(1e-6) / distance.max(1e-6))
+                        .abs();
+                for (channel, nearest) in channels.iter_mut().enumerate() {
+                    if segment.color & (1 << channel) == 0 {
+                        continue;
+                    }
+                    let closer = distance < nearest.distance - 1e-4
+                        || (distance < nearest.distance + 1e-4
+                            && orthogonality > nearest.orthogonality);
+                    if closer {
+                        *nearest = Nearest {
+                            distance,
+                            orthogonality,
+                            segment: index,
+                        };
+                    }
+                }
+            }
+            let inside = winding != 0;
+            let true_distance = if inside {
+                true_distance
+            } else {
+                -true_distance
+            };
+            if field.kind == DistanceFieldKind::Sdf {
+                data.push(encode(true_distance));
+                continue;
+            }
+
+            let mut pixel = [0.0; 3];
+            for (value, nearest) in pixel.iter_mut().zip(channels) {
+                if nearest.distance == f32::MAX {
+                    *value = true_distance;
+                    continue;
+                }
+                let segment = &segments[nearest.segment];
+                let (distance, t, side) = segment_distance(segment, point);
+                let signed_side = side * orientation;
+                let extended = (segment.edge_start && t < 0.0) || (segment.edge_end && t > 1.0);
+                *value = if extended {
+                    // Distance to the line the edge ends on, which keeps corners sharp
+                    let line_length = length(sub(segment.end, segment.start)).max(1e-6);
+                    let pseudo_distance = signed_side / line_length;
+                    if pseudo_distance.abs() <= distance {
+                        pseudo_distance
+                    } else {
+                        distance.copysign(signed_side)
+                    }
+                } else if (0.0..=1.0).contains(&t) {
+                    distance.copysign(signed_side)
+                } else {
+                    // Nearest to a bend inside an edge, where the side is ambiguous
+                    distance.copysign(true_distance)
+                };
+            }
+            // Replace channels that would put the pixel on the wrong side of the outline
+            let mut sorted = pixel;
+            sorted.sort_by(f32::total_cmp);
+            if (sorted[1] > 0.0) != inside {
+                pixel = [true_distance; 3];
+            }
+            data.extend(pixel.map(encode));
+            data.push(encode(true_distance));
+        }
+    }
+    Some(DistanceFieldGlyph {
+        left,
+        top,
+        width,
+        height,
+        data,
+    })
+}
@@ -0,0 +1,192 @@
+//! Outlines and shadows painted around glyphs by the CPU rasterizer.
+//!
+//! The coverage of the glyphs is dilated into the outline, and the glyphs together with their
+//! outline are blurred into the shadow. Both reach out of the glyph bitmaps by the margins of
+//! [`EffectGeometry`], which the rasterized texture is grown by.
+
+use crate::damage::PixelBounds;
+use crate::style::TextEffects;
+use cosmic_text::Color;
+
+/// Text effects in DEVICE pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct EffectGeometry {
+    /// Width of the outline and its color.
+    pub outline: Option<(f32, Color)>,
+    /// Offset of the shadow, radius of each of the three box blurs, and color.
+    pub shadow: Option<(i32, i32, usize, Color)>,
+    /// How far the effects reach out of a glyph pixel to the left, top, right and bottom.
+    pub margins: [i32; 4],
+}
+
+impl EffectGeometry {
+    pub fn new(effects: &TextEffects, scale: f32) -> Self {
+        let outline = effects
+            .outline
+            .filter(|outline| outline.width > 0.0)
+            .map(|outline| (outline.width * scale, outline.color.0));
+        let outline_reach = outline.map_or(0, |(width, _)| (width + 0.5).ceil() as i32);
+        let shadow = effects.shadow.map(|shadow| {
+            // Three box blurs approximate a gaussian with a standard deviation of half the blur
+            // radius, like CSS does
+            let sigma = (shadow.blur * scale).max(0.0) / 2.0;
+            let box_width = (4.0 * sigma * sigma + 1.0).sqrt();
+            (
+                (shadow.offset_x * scale).round() as i32,
+                (shadow.offset_y * scale).round() as i32,
+                ((box_width - 1.0) / 2.0).round() as usize,
+                shadow.color.0,
+            )
+        });
+        let mut margins = [outline_reach; 4];
+        if let Some((offset_x, offset_y, box_radius, _)) = shadow {
+            let reach = outline_reach + box_radius as i32 * 3;
+            let offsets = [-offset_x, -offset_y, offset_x, offset_y];
+            for (margin, offset) in margins.iter_mut().zip(offsets) {
+                *margin = (*margin).max(reach + offset).max(0);
+            }
+        }
+        Self {
+            outline,
+            shadow,
+            margins,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.outline.is_none() && self.shadow.is_none()
+    }
+
+    /// Grows the bounds of glyph pixels to the pixels their effects can reach.
+    pub fn grow(&self, bounds: PixelBounds) -> PixelBounds {
+        if bounds.is_empty() {
+            return bounds;
+        }
+        let [left, top, right, bottom] = self.margins;
+        PixelBounds {
+            min_x: bounds.min_x - left,
+            min_y: bounds.min_y - top,
+            max_x: bounds.max_x + right,
+            max_y: bounds.max_y + bottom,
+        }
+    }
+
+    /// How far the effects reach in any direction.
+    pub fn reach(&self) -> i32 {
+        self.margins.into_iter().max().unwrap_or(0)
+    }
+}
+
+/// Coverage of an area of pixels, between 0 and 1.
+#[derive(Debug, Clone)]
+pub(crate) struct CoverageMap {
+    bounds: PixelBounds,
+    width: usize,
+    data: Vec<f32>,
+}
+
+impl CoverageMap {
+    pub fn new(bounds: PixelBounds) -> Self {
+        let width = (bounds.max_x - bounds.min_x).max(0) as usize;
+        let height = (bounds.max_y - bounds.min_y).max(0) as usize;
+        Self {
+            bounds,
+            width,
+            data: vec![0.0; width * height],
+        }
+    }
+
+    pub fn bounds(&self) -> PixelBounds {
+        self.bounds
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < self.bounds.min_x
+            || x >= self.bounds.max_x
+            || y < self.bounds.min_y
+            || y >= self.bounds.max_y
+        {
+            return None;
+        }
+        Some((y - self.bounds.min_y) as usize * self.width + (x - self.bounds.min_x) as usize)
+    }
+
+    /// Coverage of the pixel, zero outside of the map.
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        self.index(x, y).map_or(0.0, |index| self.data[index])
+    }
+
+    /// Adds coverage to the pixel, as if two shapes were drawn over each other.
+    pub fn add(&mut self, x: i32, y: i32, coverage: f32) {
+        if let Some(index) = self.index(x, y) {
+            let value = &mut self.data[index];
+            *value += coverage * (1.0 - *value);
+        }
+    }
+
+    /// Returns the coverage grown by the radius in every direction, with antialiased edges.
+    pub fn dilate(&self, radius: f32) -> Self {
+        let reach = (radius + 0.5).ceil() as i32;
+        let mut kernel = Vec::new();
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                let weight = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                if weight > 0.0 {
+                    kernel.push((dx, dy, weight));
+                }
+            }
+        }
+        let mut dilated = Self::new(self.bounds);
+        for y in self.bounds.min_y..self.bounds.max_y {
+            for x in self.bounds.min_x..self.bounds.max_x {
+                let coverage = kernel
+                    .iter()
+                    .map(|(dx, dy, weight)| self.get(x + dx, y + dy) * weight)
+                    .fold(0.0, f32::max);
+                let index = self.index(x, y).unwrap();
+                dilated.data[index] = coverage;
+            }
+        }
+        dilated
+    }
+
+    /// Returns the coverage blurred with three box blurs of the radius in each direction.
+    pub fn blur(&self, radius: usize) -> Self {
+        let mut blurred = self.clone();
+        if radius == 0 || self.data.is_empty() {
+            return blurred;
+        }
+        let height = self.data.len() / self.width;
+        let mut line = Vec::new();
+        for _ in 0..3 {
+            for row in blurred.data.chunks_exact_mut(self.width) {
+                line.clear();
+                line.extend_from_slice(row);
+                box_blur(&line, row.iter_mut(), radius);
+            }
+            for column in 0..self.width {
+                line.clear();
+                line.extend((0..height).map(|row| blurred.data[row * self.width + column]));
+                let cells = blurred.data[column..].iter_mut().step_by(self.width);
+                box_blur(&line, cells, radius);
+            }
+        }
+        blurred
+    }
+}
+
+/// Writes the average of the values within the radius of each value, with zeros outside.
+fn box_blur<'a>(values: &[f32], target: impl Iterator<Item = &'a mut f32>, radius: usize) {
+    let size = (radius * 2 + 1) as f32;
+    let mut sum: f32 = values.iter().take(radius).sum();
+    for (index, cell) in target.enumerate() {
+        if let Some(value) = values.get(index + radius) {
+            sum += value;
+        }
+        if index > radius {
+            sum -= values[index - radius - 1];
+        }
+        *cell = (sum / size).clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,107 @@
+//! Gamma and contrast correction of glyph coverage.
+//!
+//! Blending coverage in linear space makes light text on dark backgrounds look thinner than
+//! dark text on light backgrounds. Browsers compensate with contrast and gamma curves that
+//! depend on the brightness of the text color, see [`GammaCorrection`].
+
+use crate::utils::srgb_to_linear_u8;
+use cosmic_text::Color;
+
+/// How the coverage of glyph pixels is adjusted before the CPU rasterizer composites them, set
+/// with [`crate::TextContext::gamma_correction`].
+///
+/// Contrast is applied first and thickens partially covered pixels of all text, similar to
+/// stem darkening. Gamma is applied after it, depending on the luminance of the text color:
+/// coverage of white text is raised to `1 / gamma`, coverage of black text to `gamma`, and
+/// coverage of mid-gray text is left as it is. Fully covered and empty pixels never change.
+///
+/// Color glyphs, such as emoji, distance fields and text effects aren't adjusted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GammaCorrection {
+    /// Gamma of the coverage of light and dark text. `1.0` leaves coverage as it is, larger
+    /// values make light text bolder and dark text lighter.
+    pub gamma: f32,
+    /// How much partially covered pixels are darkened, from `0.0` for no change. A contrast of
+    /// `1.0` turns a coverage of 50% into 67%.
+    pub contrast: f32,
+}
+
+impl GammaCorrection {
+    /// Leaves coverage as it is.
+    pub const NONE: Self = Self {
+        gamma: 1.0,
+        contrast: 0.0,
+    };
+
+    /// Creates a correction with the given gamma and contrast.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::GammaCorrection;
+    /// use cosmic_text::Color;
+    ///
+    /// let correction = GammaCorrection::new(1.8, 0.5);
+    /// let white = Color::rgb(255, 255, 255);
+    /// assert!(correction.adjust(0.5, white) > 0.5);
+    /// assert_eq!(correction.adjust(1.0, white), 1.0);
+    /// ```
+    pub const fn new(gamma: f32, contrast: f32) -> Self {
+        Self { gamma, contrast }
+    }
+
+    /// Sets the gamma and returns the modified correction.
+    pub const fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Sets the contrast and returns the modified correction.
+    pub const fn with_contrast(mut self, contrast: f32) -> Self {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Returns `true` if the correction leaves coverage as it is.
+    pub fn is_none(&self) -> bool {
+        self.gamma == 1.0 && self.contrast == 0.0
+    }
+
+    /// Adjusts the coverage of a pixel of a glyph drawn in the given color.
+    ///
+    /// # Arguments
+    /// * `coverage` - How much of the pixel the glyph covers, from `0.0` to `1.0`
+    /// * `color` - Color of the glyph, only its luminance matters
+    ///
+    /// # Returns
+    /// The adjusted coverage, from `0.0` to `1.0`
+    pub fn adjust(&self, coverage: f32, color: Color) -> f32 {
+        let coverage = coverage.clamp(0.0, 1.0);
+        let contrast = self.contrast.max(0.0);
+        let coverage = coverage * (contrast + 1.0) / (coverage * contrast + 1.0);
+        let exponent = self.gamma.max(0.01).powf(1.0 - 2.0 * luminance(color));
+        coverage.powf(exponent)
+    }
+
+    /// Adjusted 8-bit coverage of every 8-bit coverage of a glyph drawn in the given color.
+    pub(crate) fn table(&self, color: Color) -> [u8; 256] {
+        let mut table = [0; 256];
+        for (coverage, adjusted) in table.iter_mut().enumerate() {
+            let coverage = coverage as f32 / 255.0;
+            *adjusted = (self.adjust(coverage, color) * 255.0).round() as u8;
+        }
+        table
+    }
+}
+
+impl Default for GammaCorrection {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Relative luminance of an sRGB color, from `0.0` for black to `1.0` for white.
+fn luminance(color: Color) -> f32 {
+    0.2126 * srgb_to_linear_u8(color.r())
+        + 0.7152 * srgb_to_linear_u8(color.g())
+        + 0.0722 * srgb_to_linear_u8(color.b())
+}
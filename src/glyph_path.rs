@@ -0,0 +1,83 @@
+//! Vector outlines of laid-out text.
+//!
+//! [`crate::TextState::glyph_paths`] returns the outline of every glyph as path commands in
+//! LOGICAL pixels relative to the text area, for exporting text to vector formats such as PDF
+//! or SVG, or tessellating it into meshes.
+
+use crate::math::{Point, Rect};
+use cosmic_text::{CacheKey, Color};
+
+/// A command of a glyph outline. Coordinates are in LOGICAL pixels relative to the text area,
+/// with y going down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new contour at the point.
+    MoveTo(Point),
+    /// A straight line to the point.
+    LineTo(Point),
+    /// A quadratic Bézier curve with a control point, to the point.
+    QuadTo(Point, Point),
+    /// A cubic Bézier curve with two control points, to the point.
+    CurveTo(Point, Point, Point),
+    /// Closes the contour with a straight line to its start.
+    Close,
+}
+
+/// The outline of one glyph, positioned in the text area.
+///
+/// Contours are filled with the nonzero winding rule, as in the font.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphPath {
+    /// Key the glyph is rasterized with, which identifies its font, glyph and size.
+    pub cache_key: CacheKey,
+    /// Color of the glyph.
+    pub color: Color,
+    /// The outline, contour after contour.
+    pub commands: Vec<PathCommand>,
+}
+
+impl GlyphPath {
+    /// The smallest rectangle containing all points of the path, including control points, or
+    /// `None` if the path is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{GlyphPath, PathCommand, Point};
+    /// use protextinator::cosmic_text::{CacheKey, CacheKeyFlags, Color};
+    ///
+    /// let (cache_key, _, _) = CacheKey::new(
+    ///     Default::default(),
+    ///     0,
+    ///     16.0,
+    ///     (0.0, 0.0),
+    ///     Default::default(),
+    ///     CacheKeyFlags::empty(),
+    /// );
+    /// let path = GlyphPath {
+    ///     cache_key,
+    ///     color: Color::rgb(0, 0, 0),
+    ///     commands: vec![
+    ///         PathCommand::MoveTo(Point::new(1.0, 2.0)),
+    ///         PathCommand::QuadTo(Point::new(5.0, 0.0), Point::new(3.0, 4.0)),
+    ///         PathCommand::Close,
+    ///     ],
+    /// };
+    /// let bounds = path.bounds().unwrap();
+    /// assert_eq!((bounds.min, bounds.max), (Point::new(1.0, 0.0), Point::new(5.0, 4.0)));
+    /// ```
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.commands.iter().flat_map(|command| match *command {
+            PathCommand::MoveTo(point) | PathCommand::LineTo(point) => vec![point],
+            PathCommand::QuadTo(control, point) => vec![control, point],
+            PathCommand::CurveTo(control1, control2, point) => vec![control1, control2, point],
+            PathCommand::Close => Vec::new(),
+        });
+        let first = points.next()?;
+        Some(points.fold(Rect::new(first, first), |rect, point| {
+            Rect::new(
+                Point::new(rect.min.x.min(point.x), rect.min.y.min(point.y)),
+                Point::new(rect.max.x.max(point.x), rect.max.y.max(point.y)),
+            )
+        }))
+    }
+}
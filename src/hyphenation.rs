@@ -0,0 +1,175 @@
+//! Hyphenation of wrapped text.
+//!
+//! Hyphenation points are inserted into the display text as soft hyphens (U+00AD), see
+//! [`crate::display_text::DisplayText`]. A soft hyphen is invisible when shaped, but it gives
+//! cosmic_text a line break opportunity inside the word. cosmic_text doesn't draw anything at
+//! such a break, so the rasterizer draws the glyph returned by [`hyphen_glyph`] at the end of a
+//! layout line that ends at a soft hyphen.
+//!
+//! cosmic_text breaks lines without the hyphen, so a line could fit only without it. After
+//! layout, [`suppress_overflowing_soft_hyphens`] takes such breaks out of the buffer line, and
+//! the word breaks at an earlier opportunity instead. The buffer line text then differs from the
+//! display text, so grapheme boundaries and exported text come from
+//! [`SuppressedSoftHyphens::line_text`].
+
+use crate::style::Language;
+use crate::utils::with_font_ref;
+use ahash::HashMap;
+use cosmic_text::skrifa::instance::{LocationRef, Size as FontUnitsSize};
+use cosmic_text::skrifa::GlyphId;
+use cosmic_text::skrifa::MetadataProvider;
+use cosmic_text::{Buffer, BufferLine, FontSystem, LayoutGlyph, LayoutRun};
+use std::borrow::Cow;
+
+pub(crate) const SOFT_HYPHEN: char = '\u{AD}';
+/// Replaces soft hyphens that must not break the line. It's invisible and as long as a soft
+/// hyphen in UTF-8, so byte offsets into the line don't change, but it isn't a line break
+/// opportunity. Being a combining mark, it joins the grapheme before it.
+const COMBINING_GRAPHEME_JOINER: char = '\u{34F}';
+
+/// Byte offsets in `text` at which words can be hyphenated, in ascending order.
+#[cfg(feature = "hyphenation")]
+pub(crate) fn hyphenation_points(text: &str, language: Language) -> Vec<usize> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let Some(lang) = hypher::Lang::from_iso(language.code()) else {
+        return Vec::new();
+    };
+    let mut points = Vec::new();
+    for (word_start, word) in text.split_word_bound_indices() {
+        if !word.chars().all(char::is_alphabetic) {
+            continue;
+        }
+        let mut offset = word_start;
+        for syllable in hypher::hyphenate(word, lang) {
+            if offset != word_start {
+                points.push(offset);
+            }
+            offset += syllable.len();
+        }
+    }
+    points
+}
+
+/// Byte offsets in `text` at which words can be hyphenated. There are no dictionaries without
+/// the `hyphenation` feature.
+#[cfg(not(feature = "hyphenation"))]
+pub(crate) fn hyphenation_points(_text: &str, _language: Language) -> Vec<usize> {
+    Vec::new()
+}
+
+/// Returns the hyphen to draw at the end of the layout run if the line breaks at a soft hyphen.
+pub(crate) fn hyphen_glyph(font_system: &mut FontSystem, run: &LayoutRun) -> Option<LayoutGlyph> {
+    let last_glyph = soft_hyphen_break(run.text, run.glyphs)?;
+    let (glyph_id, advance) = hyphen_advance(font_system, last_glyph)?;
+
+    // The hyphen follows the soft hyphen in the direction of the text
+    let x = if last_glyph.level.is_rtl() {
+        last_glyph.x - advance
+    } else {
+        last_glyph.x + last_glyph.w
+    };
+    Some(LayoutGlyph {
+        glyph_id: u16::try_from(glyph_id.to_u32()).ok()?,
+        x,
+        w: advance,
+        ..last_glyph.clone()
+    })
+}
+
+/// Takes the soft hyphens out of the laid out line at which it breaks, but where the hyphen
+/// doesn't fit into `width` in DEVICE pixels. Returns the byte offsets of the soft hyphens that
+/// were taken out. If there are any, the line has to be laid out again.
+pub(crate) fn suppress_overflowing_soft_hyphens(
+    font_system: &mut FontSystem,
+    line: &mut BufferLine,
+    width: f32,
+) -> Vec<usize> {
+    let Some(layout_lines) = line.layout_opt() else {
+        return Vec::new();
+    };
+    let overflowing: Vec<usize> = layout_lines
+        .iter()
+        .filter_map(|layout_line| {
+            let last_glyph = soft_hyphen_break(line.text(), &layout_line.glyphs)?;
+            let (_, advance) = hyphen_advance(font_system, last_glyph)?;
+            (layout_line.w + advance > width).then_some(last_glyph.start)
+        })
+        .collect();
+    if overflowing.is_empty() {
+        return overflowing;
+    }
+
+    let mut text = line.text().to_string();
+    for &offset in &overflowing {
+        text.replace_range(
+            offset..offset + SOFT_HYPHEN.len_utf8(),
+            COMBINING_GRAPHEME_JOINER.encode_utf8(&mut [0; 2]),
+        );
+    }
+    let attrs_list = line.attrs_list().clone();
+    let ending = line.ending();
+    line.set_text(text, ending, attrs_list);
+    overflowing
+}
+
+/// Byte offsets of the soft hyphens that [`suppress_overflowing_soft_hyphens`] took out of the
+/// buffer lines, by line index.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SuppressedSoftHyphens {
+    lines: HashMap<usize, Vec<usize>>,
+}
+
+impl SuppressedSoftHyphens {
+    /// Records the soft hyphens taken out of the line at the given index.
+    pub fn extend(&mut self, line_index: usize, offsets: Vec<usize>) {
+        if !offsets.is_empty() {
+            self.lines.entry(line_index).or_default().extend(offsets);
+        }
+    }
+
+    /// The text of the buffer line at the given index with its soft hyphens put back. The
+    /// joiner that replaces them is a combining mark, which would join the grapheme before it.
+    pub fn line_text<'a>(&self, buffer: &'a Buffer, line_index: usize) -> Option<Cow<'a, str>> {
+        let text = buffer.lines.get(line_index)?.text();
+        let Some(offsets) = self.lines.get(&line_index) else {
+            return Some(Cow::Borrowed(text));
+        };
+        let mut text = text.to_string();
+        for &offset in offsets {
+            // Edits since the layout can have moved the joiner
+            let range = offset..offset + COMBINING_GRAPHEME_JOINER.len_utf8();
+            if text
+                .get(range.clone())
+                .is_some_and(|joiner| joiner.starts_with(COMBINING_GRAPHEME_JOINER))
+            {
+                text.replace_range(range, SOFT_HYPHEN.encode_utf8(&mut [0; 2]));
+            }
+        }
+        Some(Cow::Owned(text))
+    }
+}
+
+/// Returns the last glyph of the layout line if the line breaks at the soft hyphen it shows.
+fn soft_hyphen_break<'a>(text: &str, glyphs: &'a [LayoutGlyph]) -> Option<&'a LayoutGlyph> {
+    let last_glyph = glyphs.iter().max_by_key(|glyph| glyph.end)?;
+    let ends_at_soft_hyphen = text[last_glyph.start..].starts_with(SOFT_HYPHEN);
+    // A soft hyphen at the end of the paragraph doesn't break anything
+    (ends_at_soft_hyphen && last_glyph.end < text.len()).then_some(last_glyph)
+}
+
+/// Glyph and advance in DEVICE pixels of the hyphen in the font of the glyph.
+fn hyphen_advance(font_system: &mut FontSystem, glyph: &LayoutGlyph) -> Option<(GlyphId, f32)> {
+    with_font_ref(font_system, glyph.font_id, glyph.font_weight, |font_ref| {
+        let glyph_id = font_ref.charmap().map('-')?;
+        let units_per_em = font_ref
+            .metrics(FontUnitsSize::unscaled(), LocationRef::default())
+            .units_per_em;
+        let advance = font_ref
+            .glyph_metrics(FontUnitsSize::unscaled(), LocationRef::default())
+            .advance_width(glyph_id)?
+            / f32::from(units_per_em.max(1))
+            * glyph.font_size;
+        Some((glyph_id, advance))
+    })
+}
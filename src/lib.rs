@@ -6,9 +6,23 @@
 //! - Vertical text alignment
 //! - Measuring text buffer size
 //! - Managing scroll position with absolute coordinates
+//! - Smooth scrolling by fractions of a pixel without glyph shimmer
 //! - A simple interface for loading and managing fonts
 //! - A collection of text states that has optional track of usage for garbage collection
 //! - Custom metadata for text states
+//! - Padding and background fill for text fields
+//! - Editing, cursor motion and selection by user-perceived characters (grapheme clusters)
+//! - Bar, block and underline carets with blinking and an overwrite mode
+//! - Renderer-agnostic display lists with glyphs, selection, decorations and the caret
+//! - Glyph quads for GPU renderers that draw all text from a shared glyph atlas
+//! - CPU rasterization with grayscale or subpixel (LCD) antialiasing
+//! - Color emoji from bitmap (CBDT, sbix) and layered (COLR) fonts, or monochrome emoji
+//! - Gamma and contrast correction for light text on dark backgrounds
+//! - Outline, shadow and glow effects for text over busy backgrounds
+//! - Signed distance field (SDF and MSDF) glyphs for resolution-independent rendering
+//! - Glyph outlines as vector paths for exporting and tessellating text
+//! - SVG export of the layout with glyph outlines or positioned text
+//! - Optional PNG encoding and image comparison of rasterized textures for golden-image tests
 //!   
 //! and more.
 //!
@@ -63,22 +77,57 @@
 //! ```
 
 mod action;
+mod atlas;
+mod bidi;
 mod buffer_utils;
 mod byte_cursor;
+mod caret;
+mod color_glyph;
+mod damage;
+mod display_list;
+mod display_text;
+mod distance_field;
+mod effects;
 mod font_family_query;
+mod gamma;
+mod glyph_path;
+mod hyphenation;
 mod id;
 pub mod math;
+mod paragraph;
+#[cfg(feature = "png")]
+mod snapshot;
 mod state;
 pub mod style;
+mod subpixel;
+mod svg;
 #[cfg(test)]
 mod tests;
 mod text_manager;
 mod text_params;
 pub mod utils;
+mod vertical;
 
 pub use action::{Action, ActionResult};
+pub use atlas::{AtlasGlyph, CpuGlyphAtlas, GlyphAtlas, GlyphContent, GlyphImage, GlyphQuad};
+pub use caret::{CaretShape, CaretStyle};
+pub use color_glyph::{ColorGlyphCache, ColorGlyphs};
 pub use cosmic_text;
+pub use damage::DirtyRect;
+pub use display_list::{DisplayGlyph, DrawCommand, Highlight};
+pub use distance_field::{DistanceField, DistanceFieldCache, DistanceFieldKind};
+pub use gamma::GammaCorrection;
+pub use glyph_path::{GlyphPath, PathCommand};
 pub use id::Id;
 pub use math::{Point, Rect};
-pub use state::{AlphaMode, RasterizedTexture, Selection, SelectionLine, TextState};
+#[cfg(feature = "png")]
+pub use png;
+#[cfg(feature = "png")]
+pub use snapshot::{ImageDiff, PixelMismatch};
+pub use state::{
+    AlphaMode, Backspace, CursorMovement, PixelFormat, RasterizedTexture, ScrollRendering,
+    Selection, SelectionColors, SelectionLine, SplitCaret, TextState,
+};
+pub use subpixel::{Antialiasing, SubpixelCache, SubpixelOrder};
+pub use svg::{SvgGlyphs, SvgOptions};
 pub use text_manager::{TextContext, TextManager};
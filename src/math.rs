@@ -213,3 +213,74 @@ impl From<(i32, i32)> for Point {
         }
     }
 }
+
+/// Insets from the edges of a rectangle, e.g. the space between the edges of a text area and
+/// its text.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Padding {
+    /// Inset from the top edge.
+    pub top: f32,
+    /// Inset from the right edge.
+    pub right: f32,
+    /// Inset from the bottom edge.
+    pub bottom: f32,
+    /// Inset from the left edge.
+    pub left: f32,
+}
+
+impl Padding {
+    /// No insets.
+    pub const ZERO: Self = Self::uniform(0.0);
+
+    /// Creates padding with a different inset for every edge, in the same order as CSS.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::math::Padding;
+    ///
+    /// let padding = Padding::new(1.0, 2.0, 3.0, 4.0);
+    /// assert_eq!((padding.top, padding.left), (1.0, 4.0));
+    /// ```
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Creates padding with the same inset for every edge.
+    pub const fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    /// Creates padding with one inset for the left and right edges and one for the top and
+    /// bottom edges.
+    pub const fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Offset of the inner rectangle from the top-left corner of the outer one.
+    #[inline(always)]
+    pub const fn offset(&self) -> Point {
+        Point::new(self.left, self.top)
+    }
+
+    /// Size of the rectangle inside of the padding, which is never negative.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::math::{Padding, Size};
+    ///
+    /// let padding = Padding::symmetric(10.0, 4.0);
+    /// assert_eq!(padding.inner_size(Size::new(100.0, 30.0)), Size::new(80.0, 22.0));
+    /// assert_eq!(padding.inner_size(Size::new(15.0, 5.0)), Size::new(0.0, 0.0));
+    /// ```
+    pub fn inner_size(&self, outer: Size) -> Size {
+        Size::new(
+            (outer.x - self.left - self.right).max(0.0),
+            (outer.y - self.top - self.bottom).max(0.0),
+        )
+    }
+}
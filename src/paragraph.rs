@@ -0,0 +1,307 @@
+//! Paragraph indents, insets, spacing, and tab stops.
+//!
+//! cosmic_text lays every line out at the same horizontal origin and stacks lines without any
+//! gaps, so paragraph styles are applied in two steps: the wrap width is baked into the layout
+//! in [`crate::buffer_utils::update_buffer`], and the remaining offsets are stored in
+//! [`ParagraphOffsets`] and applied by everything that maps between the buffer layout and the
+//! text area: rasterization, caret positioning, selection, and hit testing.
+//!
+//! Explicit tab stops work the same way: tabs are laid out as narrow as a space, and the glyphs
+//! after each tab are moved to its stop by the offsets of their layout line.
+
+use crate::math::Point;
+use crate::style::{HorizontalTextAlignment, TextStyle};
+use cosmic_text::{Buffer, Cursor, LayoutGlyph, LayoutRun};
+
+/// Offsets of a single paragraph, in DEVICE pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct LineOffset {
+    /// Horizontal offset of every layout line of the paragraph.
+    pub x: f32,
+    /// Additional horizontal offset of the paragraph's first layout line. The first line indent
+    /// is applied at the start edge of the paragraph, so this is negative in right-to-left
+    /// paragraphs, and smaller than the indent for lines that aren't aligned to their start.
+    pub first_line_x: f32,
+    /// Horizontal space taken from every layout line by the insets and the hanging indent.
+    pub width: f32,
+    /// Additional horizontal space taken from the first layout line by the first line indent.
+    /// Negative if the first line is wider than the others.
+    pub first_line_indent: f32,
+    /// Total vertical space added above the paragraph's first layout line, including the
+    /// paragraph's own `space_before`.
+    pub y: f32,
+    /// Total vertical space added by the preceding paragraphs.
+    pub block_y: f32,
+}
+
+/// Horizontal offset of the part of a layout line that starts at `end`, in DEVICE pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TabShift {
+    /// Start of the tab glyph in the layout.
+    pub start: f32,
+    /// End of the tab glyph in the layout.
+    pub end: f32,
+    /// Offset of everything from `end` up to the next tab.
+    pub shift: f32,
+}
+
+/// Per-paragraph layout offsets, in DEVICE pixels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ParagraphOffsets {
+    lines: Vec<LineOffset>,
+    total_height: f32,
+    /// Tab shifts of the layout lines that contain tabs, keyed by line and layout line index.
+    tabs: ahash::HashMap<(usize, usize), Vec<TabShift>>,
+}
+
+impl ParagraphOffsets {
+    /// Offsets for the given paragraph styles, with the direction of every paragraph (`true`
+    /// for right-to-left). Returns empty offsets if no paragraph is styled.
+    pub fn new(style: &TextStyle, rtl: &[bool], scale_factor: f32) -> Self {
+        if !style.has_paragraph_styles() {
+            return Self::default();
+        }
+
+        let mut block_y = 0.0;
+        let lines = rtl
+            .iter()
+            .enumerate()
+            .map(|(line_index, &rtl)| {
+                let paragraph = style.paragraph_style(line_index);
+                let device = |value: f32| (value * scale_factor).round();
+                let before = device(paragraph.space_before);
+                let hanging_indent = device(paragraph.hanging_indent);
+                let first_line_indent = device(paragraph.first_line_indent) - hanging_indent;
+                let direction = if rtl { -1.0 } else { 1.0 };
+                let start_share = start_edge_share(style.horizontal_alignment, rtl);
+                let offset = LineOffset {
+                    // The hanging indent is on the left of left-to-right paragraphs, and
+                    // right-to-left layout lines end before it
+                    x: device(paragraph.left_inset) + if rtl { 0.0 } else { hanging_indent },
+                    first_line_x: (direction * first_line_indent * start_share).round(),
+                    width: device(paragraph.left_inset)
+                        + hanging_indent
+                        + device(paragraph.right_inset),
+                    first_line_indent,
+                    y: block_y + before,
+                    block_y,
+                };
+                block_y += before + device(paragraph.space_after);
+                offset
+            })
+            .collect();
+        Self {
+            lines,
+            total_height: block_y,
+            tabs: Default::default(),
+        }
+    }
+
+    /// Returns `true` if the offsets don't change the layout.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.tabs.is_empty()
+    }
+
+    /// Offsets of the paragraph at the given line index. Indices past the last paragraph get
+    /// the offsets of an empty paragraph after it.
+    pub fn line(&self, line_index: usize) -> LineOffset {
+        self.lines.get(line_index).copied().unwrap_or(LineOffset {
+            y: self.total_height,
+            block_y: self.total_height,
+            ..LineOffset::default()
+        })
+    }
+
+    /// Sets the tab shifts of a layout line, sorted by position.
+    pub fn set_tabs(&mut self, line_index: usize, layout_index: usize, tabs: Vec<TabShift>) {
+        if tabs.is_empty() {
+            self.tabs.remove(&(line_index, layout_index));
+        } else {
+            self.tabs.insert((line_index, layout_index), tabs);
+        }
+    }
+
+    /// Vertical space added around the paragraph at the given line index.
+    pub fn spacing(&self, line_index: usize) -> f32 {
+        self.line(line_index + 1).block_y - self.line(line_index).block_y
+    }
+
+    /// Total vertical space added by all paragraphs.
+    pub fn total_height(&self) -> f32 {
+        self.total_height
+    }
+
+    /// Vertical offset of the runs of a paragraph when the buffer is scrolled to `scroll_line`.
+    pub fn y(&self, line_index: usize, scroll_line: usize) -> f32 {
+        self.line(line_index).y - self.line(scroll_line).block_y
+    }
+
+    /// Offset of the caret at the given cursor, relative to the position reported by the
+    /// layout, whose top is at `caret_top`.
+    pub fn caret_offset(&self, buffer: &Buffer, cursor: Cursor, caret_top: f32) -> Point {
+        // The caret is on the layout line whose top is the closest to the caret's
+        let run = runs_with_offsets(buffer, self)
+            .filter(|(run, _)| run.line_i == cursor.line)
+            .min_by(|(a, _), (b, _)| {
+                let distance = |run: &LayoutRun| (run.line_top - caret_top).abs();
+                distance(a).total_cmp(&distance(b))
+            });
+        let Some((run, offset)) = run else {
+            let y = self.y(cursor.line, buffer.scroll().line);
+            return Point::new(self.line(cursor.line).x, y);
+        };
+        // Caret positions are truncated to whole pixels, so the offset is taken from the glyph
+        // at the caret instead of from its position
+        let x = run
+            .glyphs
+            .iter()
+            .find(|glyph| glyph.start == cursor.index)
+            .map(|glyph| offset.glyph_x(glyph))
+            .or_else(|| {
+                let glyph = run.glyphs.iter().find(|glyph| glyph.end == cursor.index)?;
+                let edge = if glyph.level.is_rtl() {
+                    glyph.x
+                } else {
+                    glyph.x + glyph.w
+                };
+                Some(offset.map_x(edge) - edge)
+            })
+            .unwrap_or(offset.x);
+        Point::new(x, offset.y)
+    }
+}
+
+/// Share of the space taken at the start edge of a layout line that its glyphs move by: all of
+/// it for lines aligned to their start, none for lines aligned to their end.
+pub(crate) fn start_edge_share(alignment: HorizontalTextAlignment, rtl: bool) -> f32 {
+    match alignment {
+        HorizontalTextAlignment::None
+        | HorizontalTextAlignment::Start
+        | HorizontalTextAlignment::Justify => 1.0,
+        HorizontalTextAlignment::Center => 0.5,
+        HorizontalTextAlignment::End => 0.0,
+        HorizontalTextAlignment::Left => f32::from(u8::from(!rtl)),
+        HorizontalTextAlignment::Right => f32::from(u8::from(rtl)),
+    }
+}
+
+/// Offset of a single layout run, in DEVICE pixels.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RunOffset<'a> {
+    /// Horizontal offset of every glyph in the run, not counting tabs.
+    pub x: f32,
+    /// Vertical offset of the run.
+    pub y: f32,
+    /// Shifts of the parts of the run that follow its tabs.
+    tabs: &'a [TabShift],
+}
+
+impl RunOffset<'_> {
+    /// Maps a horizontal position in the layout of the run to the text area.
+    pub fn map_x(&self, layout_x: f32) -> f32 {
+        let shift = self
+            .tabs
+            .iter()
+            .rev()
+            .find(|tab| tab.end <= layout_x + TAB_EPSILON)
+            .map_or(0.0, |tab| tab.shift);
+        layout_x + self.x + shift
+    }
+
+    /// Horizontal offset of the given glyph of the run.
+    pub fn glyph_x(&self, glyph: &LayoutGlyph) -> f32 {
+        self.map_x(glyph.x) - glyph.x
+    }
+
+    /// Maps a horizontal position in the text area back to the layout of the run. Positions
+    /// on a tab that was moved to its stop land on the tab's layout box.
+    pub fn layout_x(&self, x: f32) -> f32 {
+        let x = x - self.x;
+        for (index, tab) in self.tabs.iter().enumerate().rev() {
+            if x >= tab.end + tab.shift {
+                return x - tab.shift;
+            }
+            let previous_shift = index
+                .checked_sub(1)
+                .map_or(0.0, |index| self.tabs[index].shift);
+            let start = tab.start + previous_shift;
+            if x >= start {
+                let width = tab.end + tab.shift - start;
+                let fraction = if width > 0.0 {
+                    (x - start) / width
+                } else {
+                    0.0
+                };
+                return tab.start + fraction * (tab.end - tab.start);
+            }
+        }
+        x
+    }
+}
+
+/// Tolerance for matching glyph positions with tab ends, in DEVICE pixels.
+const TAB_EPSILON: f32 = 0.01;
+
+/// Index of the run's layout line within its paragraph.
+pub(crate) fn layout_index(buffer: &Buffer, run: &LayoutRun) -> usize {
+    buffer
+        .lines
+        .get(run.line_i)
+        .and_then(|line| line.layout_opt())
+        .and_then(|layout| {
+            layout
+                .iter()
+                .position(|line| std::ptr::eq(line.glyphs.as_slice(), run.glyphs))
+        })
+        .unwrap_or(0)
+}
+
+/// Iterates over the visible layout runs of the buffer together with their paragraph offsets.
+pub(crate) fn runs_with_offsets<'a>(
+    buffer: &'a Buffer,
+    offsets: &'a ParagraphOffsets,
+) -> impl Iterator<Item = (LayoutRun<'a>, RunOffset<'a>)> + 'a {
+    let scroll_line = buffer.scroll().line;
+    buffer.layout_runs().map(move |run| {
+        let line = offsets.line(run.line_i);
+        let layout_index = if offsets.is_empty() {
+            0
+        } else {
+            layout_index(buffer, &run)
+        };
+        let offset = RunOffset {
+            x: if layout_index == 0 {
+                line.x + line.first_line_x
+            } else {
+                line.x
+            },
+            y: offsets.y(run.line_i, scroll_line),
+            tabs: offsets
+                .tabs
+                .get(&(run.line_i, layout_index))
+                .map_or(&[], Vec::as_slice),
+        };
+        (run, offset)
+    })
+}
+
+/// Hit-tests a DEVICE pixel position in the text area, taking paragraph offsets into account.
+pub(crate) fn hit(buffer: &Buffer, offsets: &ParagraphOffsets, x: f32, y: f32) -> Option<Cursor> {
+    if offsets.is_empty() {
+        return buffer.hit(x, y);
+    }
+
+    let mut target = None;
+    for (run, offset) in runs_with_offsets(buffer, offsets) {
+        let top = run.line_top + offset.y;
+        // Positions in the spacing above a paragraph belong to its first line
+        let layout_y = (y - offset.y).max(run.line_top);
+        let is_below = y >= top + run.line_height;
+        target = Some((offset, layout_y));
+        if !is_below {
+            break;
+        }
+    }
+    let (offset, layout_y) = target?;
+    buffer.hit(offset.layout_x(x), layout_y)
+}
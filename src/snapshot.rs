@@ -0,0 +1,363 @@
+//! PNG encoding and comparison of rasterized textures, for golden-image tests.
+//!
+//! Rasterize a text state, compare the texture with a PNG checked into the repository with
+//! [`RasterizedTexture::diff`], and write the texture and the [`ImageDiff`] as PNGs when they
+//! don't match, so the change can be inspected.
+
+use crate::state::{PixelFormat, RasterizedTexture};
+use crate::utils::{f16_bits_to_f32, f32_to_f16_bits, linear_to_srgb, srgb_to_linear};
+use crate::DirtyRect;
+use png::{BitDepth, ColorType, DecodingError, EncodingError, Transformations};
+
+/// A pixel that differs between two textures by more than the tolerance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelMismatch {
+    /// Column of the pixel.
+    pub x: u32,
+    /// Row of the pixel.
+    pub y: u32,
+    /// RGBA8 value of the pixel in the expected texture.
+    pub expected: [u8; 4],
+    /// RGBA8 value of the pixel in the actual texture.
+    pub actual: [u8; 4],
+}
+
+/// The result of comparing two textures with [`RasterizedTexture::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Width of the actual texture.
+    pub width: u32,
+    /// Height of the actual texture.
+    pub height: u32,
+    /// Whether both textures have the same width and height. Textures of different sizes
+    /// aren't compared pixel by pixel.
+    pub size_matches: bool,
+    /// Pixels that differ by more than the tolerance in any channel, row by row.
+    pub mismatches: Vec<PixelMismatch>,
+    /// Largest difference of any channel of any pixel.
+    pub max_difference: u8,
+}
+
+impl ImageDiff {
+    /// Returns `true` if the textures have the same size and no pixel differs by more than the
+    /// tolerance.
+    pub fn is_match(&self) -> bool {
+        self.size_matches && self.mismatches.is_empty()
+    }
+
+    /// Encodes an image of the mismatched pixels as PNG, red on a transparent background.
+    pub fn to_png(&self) -> Result<Vec<u8>, EncodingError> {
+        let mut pixels = vec![0; self.width as usize * self.height as usize * 4];
+        for mismatch in &self.mismatches {
+            let index = (mismatch.y as usize * self.width as usize + mismatch.x as usize) * 4;
+            pixels[index..index + 4].copy_from_slice(&[255, 0, 0, 255]);
+        }
+        encode(
+            &pixels,
+            self.width,
+            self.height,
+            ColorType::Rgba,
+            BitDepth::Eight,
+        )
+    }
+}
+
+impl RasterizedTexture {
+    /// Encodes the texture as PNG.
+    ///
+    /// RGBA8 and BGRA8 textures are written as 8-bit RGBA, A8 textures as 8-bit grayscale,
+    /// and RGBA16F textures as 16-bit RGBA with the color channels converted to sRGB. Pixels
+    /// are written as they are, so rasterize with [`crate::AlphaMode::Unmultiplied`] for
+    /// images that look right in image viewers.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{AlphaMode, Id, TextManager, math::Size};
+    ///
+    /// let mut manager = TextManager::new();
+    /// let id = Id::new("label");
+    /// manager.create_state(id, "Hello", ());
+    /// let state = manager.text_states.get_mut(&id).unwrap();
+    /// state.set_outer_size(&Size::new(60.0, 20.0));
+    /// state.recalculate(&mut manager.text_context);
+    /// manager.rasterize_all_textures(AlphaMode::Unmultiplied);
+    ///
+    /// let texture = manager.text_states[&id].rasterized_texture();
+    /// let png = texture.to_png().unwrap();
+    /// assert_eq!(&png[1..4], b"PNG");
+    /// ```
+    pub fn to_png(&self) -> Result<Vec<u8>, EncodingError> {
+        match self.format {
+            PixelFormat::Rgba8 => encode(
+                &self.pixels,
+                self.width,
+                self.height,
+                ColorType::Rgba,
+                BitDepth::Eight,
+            ),
+            PixelFormat::Bgra8 => {
+                let pixels: Vec<u8> = self
+                    .pixels
+                    .chunks_exact(4)
+                    .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                    .collect();
+                encode(
+                    &pixels,
+                    self.width,
+                    self.height,
+                    ColorType::Rgba,
+                    BitDepth::Eight,
+                )
+            }
+            PixelFormat::A8 => encode(
+                &self.pixels,
+                self.width,
+                self.height,
+                ColorType::Grayscale,
+                BitDepth::Eight,
+            ),
+            PixelFormat::Rgba16F => {
+                let pixels: Vec<u8> = self
+                    .pixels
+                    .chunks_exact(8)
+                    .flat_map(|pixel| {
+                        let channel = |index: usize| {
+                            f16_bits_to_f32(u16::from_le_bytes([
+                                pixel[index * 2],
+                                pixel[index * 2 + 1],
+                            ]))
+                        };
+                        let values = [
+                            linear_to_srgb(channel(0)),
+                            linear_to_srgb(channel(1)),
+                            linear_to_srgb(channel(2)),
+                            channel(3).clamp(0.0, 1.0),
+                        ];
+                        // PNG stores 16-bit samples in big endian
+                        values
+                            .into_iter()
+                            .flat_map(|value| ((value * 65535.0).round() as u16).to_be_bytes())
+                    })
+                    .collect();
+                encode(
+                    &pixels,
+                    self.width,
+                    self.height,
+                    ColorType::Rgba,
+                    BitDepth::Sixteen,
+                )
+            }
+        }
+    }
+
+    /// Decodes a PNG into a texture of the given pixel format, e.g. the format of the texture
+    /// it's compared with.
+    ///
+    /// Colors are converted from sRGB for [`PixelFormat::Rgba16F`] textures. A8 textures take
+    /// the alpha of every pixel, or the gray value of grayscale images without alpha, which is
+    /// how [`RasterizedTexture::to_png`] writes them. The texture has no offset and is entirely
+    /// dirty.
+    ///
+    /// # Arguments
+    /// * `data` - The encoded PNG
+    /// * `format` - The pixel format of the decoded texture
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{DirtyRect, PixelFormat, RasterizedTexture};
+    ///
+    /// let texture = RasterizedTexture {
+    ///     pixels: vec![255, 0, 0, 128],
+    ///     width: 1,
+    ///     height: 1,
+    ///     format: PixelFormat::Rgba8,
+    ///     offset_x: 0,
+    ///     offset_y: 0,
+    ///     subpixel_offset_x: 0.0,
+    ///     subpixel_offset_y: 0.0,
+    ///     dirty_rect: DirtyRect::full(1, 1),
+    /// };
+    /// let png = texture.to_png().unwrap();
+    ///
+    /// let decoded = RasterizedTexture::from_png(&png, PixelFormat::Bgra8).unwrap();
+    /// assert_eq!(decoded.pixels, [0, 0, 255, 128]);
+    /// ```
+    pub fn from_png(data: &[u8], format: PixelFormat) -> Result<Self, DecodingError> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+        decoder.set_transformations(Transformations::EXPAND);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let channels = info.color_type.samples();
+        let sample_size = if info.bit_depth == BitDepth::Sixteen {
+            2
+        } else {
+            1
+        };
+        let mut pixels = Vec::with_capacity(
+            info.width as usize * info.height as usize * format.bytes_per_pixel(),
+        );
+        for pixel in buffer.chunks_exact(channels * sample_size) {
+            // Samples are scaled to 16 bits, which PNG stores in big endian
+            let sample = |index: usize| {
+                if sample_size == 2 {
+                    u16::from_be_bytes([pixel[index * 2], pixel[index * 2 + 1]])
+                } else {
+                    u16::from(pixel[index]) * 257
+                }
+            };
+            let (color, alpha) = match channels {
+                1 => ([sample(0); 3], u16::MAX),
+                2 => ([sample(0); 3], sample(1)),
+                3 => ([sample(0), sample(1), sample(2)], u16::MAX),
+                _ => ([sample(0), sample(1), sample(2)], sample(3)),
+            };
+            let to_u8 = |value: u16| (f32::from(value) / 257.0).round() as u8;
+            match format {
+                PixelFormat::Rgba8 => {
+                    pixels.extend([color[0], color[1], color[2], alpha].map(to_u8))
+                }
+                PixelFormat::Bgra8 => {
+                    pixels.extend([color[2], color[1], color[0], alpha].map(to_u8))
+                }
+                PixelFormat::A8 => pixels.push(to_u8(if channels == 1 { color[0] } else { alpha })),
+                PixelFormat::Rgba16F => {
+                    let to_f32 = |value: u16| f32::from(value) / 65535.0;
+                    let values = [
+                        srgb_to_linear(to_f32(color[0])),
+                        srgb_to_linear(to_f32(color[1])),
+                        srgb_to_linear(to_f32(color[2])),
+                        to_f32(alpha),
+                    ];
+                    pixels.extend(
+                        values
+                            .into_iter()
+                            .flat_map(|value| f32_to_f16_bits(value).to_le_bytes()),
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            pixels,
+            width: info.width,
+            height: info.height,
+            format,
+            offset_x: 0,
+            offset_y: 0,
+            subpixel_offset_x: 0.0,
+            subpixel_offset_y: 0.0,
+            dirty_rect: DirtyRect::full(info.width, info.height),
+        })
+    }
+
+    /// Compares the texture with an expected one, pixel by pixel.
+    ///
+    /// Pixels of both textures are converted to RGBA8 first, so textures of different formats
+    /// can be compared. A8 pixels are black with the stored alpha.
+    ///
+    /// # Arguments
+    /// * `expected` - The texture this one should look like, e.g. decoded from a golden image
+    /// * `tolerance` - How much each channel of a pixel may differ without being reported
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{DirtyRect, PixelFormat, RasterizedTexture};
+    ///
+    /// let texture = |pixels: Vec<u8>| RasterizedTexture {
+    ///     pixels,
+    ///     width: 2,
+    ///     height: 1,
+    ///     format: PixelFormat::Rgba8,
+    ///     offset_x: 0,
+    ///     offset_y: 0,
+    ///     subpixel_offset_x: 0.0,
+    ///     subpixel_offset_y: 0.0,
+    ///     dirty_rect: DirtyRect::full(2, 1),
+    /// };
+    /// let expected = texture(vec![0, 0, 0, 255, 0, 0, 0, 0]);
+    /// let actual = texture(vec![0, 0, 0, 250, 0, 0, 0, 20]);
+    ///
+    /// let diff = actual.diff(&expected, 8);
+    /// assert_eq!(diff.mismatches.len(), 1);
+    /// assert_eq!((diff.mismatches[0].x, diff.mismatches[0].y), (1, 0));
+    /// assert_eq!(diff.max_difference, 20);
+    /// assert!(actual.diff(&expected, 20).is_match());
+    /// ```
+    pub fn diff(&self, expected: &RasterizedTexture, tolerance: u8) -> ImageDiff {
+        let size_matches = self.width == expected.width && self.height == expected.height;
+        let mut diff = ImageDiff {
+            width: self.width,
+            height: self.height,
+            size_matches,
+            mismatches: Vec::new(),
+            max_difference: 0,
+        };
+        if !size_matches {
+            return diff;
+        }
+        let actual_pixels = self.rgba8_pixels();
+        let expected_pixels = expected.rgba8_pixels();
+        for (index, (actual, expected)) in actual_pixels.zip(expected_pixels).enumerate() {
+            let difference = actual
+                .iter()
+                .zip(expected)
+                .map(|(actual, expected)| actual.abs_diff(expected))
+                .max()
+                .unwrap_or(0);
+            diff.max_difference = diff.max_difference.max(difference);
+            if difference > tolerance {
+                diff.mismatches.push(PixelMismatch {
+                    x: index as u32 % self.width,
+                    y: index as u32 / self.width,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        diff
+    }
+
+    /// The pixels converted to RGBA8.
+    fn rgba8_pixels(&self) -> impl Iterator<Item = [u8; 4]> + '_ {
+        self.pixels
+            .chunks_exact(self.format.bytes_per_pixel())
+            .map(move |pixel| match self.format {
+                PixelFormat::Rgba8 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                PixelFormat::Bgra8 => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                PixelFormat::A8 => [0, 0, 0, pixel[0]],
+                PixelFormat::Rgba16F => {
+                    let channel = |index: usize| {
+                        f16_bits_to_f32(u16::from_le_bytes([
+                            pixel[index * 2],
+                            pixel[index * 2 + 1],
+                        ]))
+                    };
+                    [
+                        (linear_to_srgb(channel(0)) * 255.0).round() as u8,
+                        (linear_to_srgb(channel(1)) * 255.0).round() as u8,
+                        (linear_to_srgb(channel(2)) * 255.0).round() as u8,
+                        (channel(3).clamp(0.0, 1.0) * 255.0).round() as u8,
+                    ]
+                }
+            })
+    }
+}
+
+fn encode(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    color_type: ColorType,
+    bit_depth: BitDepth,
+) -> Result<Vec<u8>, EncodingError> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, width, height);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(data)
+}
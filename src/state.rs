@@ -4,32 +4,75 @@
 //! text content, cursor position, selection, scrolling, and text editing operations.
 
 use crate::action::{Action, ActionResult};
+use crate::atlas::{GlyphAtlas, GlyphContent, GlyphImage, GlyphQuad};
+use crate::bidi::{visual_motion, CaretEdges, VisualMotion};
 use crate::buffer_utils::{
-    adjust_vertical_scroll_to_make_caret_visible, char_under_position, update_buffer,
-    vertical_offset,
+    adjust_vertical_scroll_to_make_caret_visible, char_under_position, layout_cursor_position,
+    update_buffer, vertical_offset,
 };
-use crate::byte_cursor::ByteCursor;
-use crate::math::Size;
-use crate::style::{FontFamily, TextStyle, VerticalTextAlignment};
+use crate::byte_cursor::{grapheme_start, ByteCursor};
+use crate::caret::{CaretShape, CaretStyle};
+use crate::color_glyph::ColorGlyphs;
+use crate::damage::{damaged_bounds, DirtyRect, PixelBounds, RasterRun};
+use crate::display_list::{DecorationMetrics, DisplayGlyph, DrawCommand, Highlight};
+use crate::distance_field::{DistanceField, DistanceFieldKind};
+use crate::effects::{CoverageMap, EffectGeometry};
+use crate::gamma::GammaCorrection;
+use crate::glyph_path::{GlyphPath, PathCommand};
+use crate::hyphenation::{hyphen_glyph, SuppressedSoftHyphens};
+use crate::math::{Padding, Size};
+use crate::paragraph::{runs_with_offsets, ParagraphOffsets};
+use crate::style::{FontFamily, TextEffects, TextStyle, VerticalTextAlignment};
+use crate::subpixel::{Antialiasing, SubpixelOrder};
+use crate::svg::{SvgGlyphs, SvgOptions, SvgText, SvgWriter};
 use crate::text_manager::TextContext;
 use crate::text_params::TextParams;
-use crate::utils::{linear_to_srgb_u8, srgb_to_linear_u8};
+use crate::utils::{
+    blend_premultiplied, blend_subpixel, blend_unmultiplied, f32_to_f16_bits, median,
+    srgb_to_linear_u8,
+};
+use crate::vertical::{self, GlyphPlacement, VerticalAlternates, VerticalFrame};
 use crate::{Point, Rect};
+use ahash::HashMap;
 #[cfg(test)]
 use cosmic_text::LayoutGlyph;
-use cosmic_text::{Buffer, Cursor, Edit, Editor, FontSystem, Motion};
+use cosmic_text::{
+    Buffer, CacheKey, Color, Cursor, Edit, Editor, FontSystem, Motion, PhysicalGlyph, SwashContent,
+};
 use smol_str::SmolStr;
+use std::ops::Range;
 use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Size comparison epsilon for floating-point calculations.
 pub const SIZE_EPSILON: f32 = 0.0001;
 
-/// CPU-side RGBA8 texture holding the rasterized contents of a text buffer.
+/// CPU-side texture holding the rasterized contents of a text buffer.
 #[derive(Debug, Clone)]
 pub struct RasterizedTexture {
+    /// Rows of pixels from top to bottom, in `format`.
     pub pixels: Vec<u8>,
     pub width: u32,
     pub height: u32,
+    /// Layout of the pixels.
+    pub format: PixelFormat,
+    /// Horizontal position of the left texture column relative to the text area, in device
+    /// pixels. Negative when [`TextEffects`] reach out of the left edge of the text area.
+    pub offset_x: i32,
+    /// Vertical position of the top texture row relative to the text area, in device pixels.
+    /// Negative when [`TextEffects`] reach out of the top edge of the text area.
+    pub offset_y: i32,
+    /// Fraction of a device pixel to move the texture by horizontally when drawing it, from
+    /// `-1.0` to `0.0`. The left texture column goes to `offset_x + subpixel_offset_x` relative
+    /// to the text area, and the texture is clipped to the text area. Only
+    /// [`ScrollRendering::Fractional`] sets it.
+    pub subpixel_offset_x: f32,
+    /// Fraction of a device pixel to move the texture by vertically when drawing it, like
+    /// [`RasterizedTexture::subpixel_offset_x`].
+    pub subpixel_offset_y: f32,
+    /// Part of the texture that changed in the last rasterization. Only these pixels need to be
+    /// uploaded again.
+    pub dirty_rect: DirtyRect,
 }
 
 /// Represents a single line of text selection with visual boundaries.
@@ -48,6 +91,70 @@ pub struct SelectionLine {
     pub end_y_pt: Option<f32>,
 }
 
+impl SelectionLine {
+    /// The rectangle covered by the selection on this line, if all its coordinates are known.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::SelectionLine;
+    ///
+    /// assert_eq!(SelectionLine::default().rect(), None);
+    /// ```
+    pub fn rect(&self) -> Option<Rect> {
+        Some(Rect::new(
+            Point::new(self.start_x_pt?, self.start_y_pt?),
+            Point::new(self.end_x_pt?, self.end_y_pt?),
+        ))
+    }
+}
+
+/// Caret drawn in two places at a boundary between left-to-right and right-to-left text.
+///
+/// Positions are in logical pixels relative to the buffer viewport with scroll applied, like
+/// [`TextState::caret_position_relative`].
+#[derive(Clone, Default, Debug, Copy, PartialEq)]
+pub struct SplitCaret {
+    /// Where the caret is drawn, which is the same as [`TextState::caret_position_relative`].
+    pub primary: Point,
+    /// The other side of the direction boundary.
+    pub secondary: Point,
+}
+
+/// Colors used to paint the selection into the rasterized texture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SelectionColors {
+    /// Background of the selected text.
+    pub background: Color,
+    /// Color of the selected text. `None` keeps the text color.
+    pub text: Option<Color>,
+}
+
+impl SelectionColors {
+    /// Selection painted with the given background color, keeping the text color.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::SelectionColors;
+    /// use cosmic_text::Color;
+    ///
+    /// let colors =
+    ///     SelectionColors::new(Color::rgb(51, 102, 204)).with_text(Color::rgb(255, 255, 255));
+    /// assert_eq!(colors.text, Some(Color::rgb(255, 255, 255)));
+    /// ```
+    pub const fn new(background: Color) -> Self {
+        Self {
+            background,
+            text: None,
+        }
+    }
+
+    /// Sets the color of the selected text and returns the modified colors.
+    pub const fn with_text(mut self, text: Color) -> Self {
+        self.text = Some(text);
+        self
+    }
+}
+
 /// Represents the current text selection state.
 ///
 /// A selection is defined by an origin point (where selection started) and an end point
@@ -108,22 +215,49 @@ impl Selection {
 #[derive(Debug)]
 pub struct TextState<T> {
     params: TextParams,
+    // Size of the text area with the padding, the params hold the size inside of the padding
+    outer_size: Size,
+    padding: Padding,
     cursor: ByteCursor,
     // Caret position relative to the buffer viewport with scroll applied
     relative_caret_position: Option<Point>,
-    caret_width: f32,
+    // Both caret positions if the caret is at a direction boundary
+    split_caret: Option<SplitCaret>,
+    // When the caret last moved or the text was edited, to restart the blink
+    caret_activity: Instant,
+    overwrite_mode: bool,
     selection: Selection,
+    highlights: Vec<Highlight>,
     resolved_font_family: FontFamily,
 
     last_scroll_timestamp: Instant,
 
     inner_dimensions: Size,
     buffer: Buffer,
+    // Paragraph indents and spacing of the current layout, in device pixels
+    paragraph_offsets: ParagraphOffsets,
+    // Soft hyphens of the current layout that the buffer lines no longer contain
+    suppressed_soft_hyphens: SuppressedSoftHyphens,
 
     // CPU-side cached rasterized texture of the current buffer (RGBA8, device pixels)
     rasterized_texture: RasterizedTexture,
     // Whether raster content needs to be regenerated
     raster_dirty: bool,
+    // What was drawn for each layout line in the last rasterization, to find damaged pixels
+    raster_runs: Vec<RasterRun>,
+    // Selection and caret rectangles painted in the last rasterization
+    raster_overlay_runs: Vec<RasterRun>,
+    raster_alpha_mode: Option<AlphaMode>,
+    raster_antialiasing: Option<Antialiasing>,
+    raster_effects: Option<TextEffects>,
+    raster_distance_field: Option<Option<DistanceField>>,
+    raster_background: Option<Option<Color>>,
+    raster_padding: Option<Padding>,
+    raster_gamma_correction: Option<GammaCorrection>,
+    raster_color_glyphs: Option<ColorGlyphs>,
+    raster_scroll_rendering: Option<ScrollRendering>,
+    // RGBA8 pixels the text is drawn into if the texture has another format
+    raster_canvas: Vec<u8>,
 
     // Settings
     /// Can text be selected?
@@ -136,6 +270,36 @@ pub struct TextState<T> {
     pub are_actions_enabled: bool,
     /// Interval between scroll updates when dragging the selection
     pub scroll_interval: Duration,
+    /// How [`Action::MoveCursorLeft`] and [`Action::MoveCursorRight`] move the caret
+    pub cursor_movement: CursorMovement,
+    /// How much text [`Action::DeleteBackward`] removes before the caret
+    pub backspace: Backspace,
+    /// Colors to paint the selection with in the rasterized texture. `None` leaves drawing the
+    /// selection to the renderer.
+    pub selection_colors: Option<SelectionColors>,
+    /// Shape, color and blinking of the caret.
+    pub caret: CaretStyle,
+    /// Whether the caret is in the visible phase of its blink. Set it from
+    /// [`TextState::caret_visible`] to make the caret painted into the rasterized texture blink.
+    pub caret_blink_visible: bool,
+    /// How glyph edges are smoothed in the rasterized texture.
+    pub antialiasing: Antialiasing,
+    /// Rasterize distance fields instead of coverage, for renderers that scale the texture.
+    /// Single-channel fields are stored in the alpha channel with the glyph color in the color
+    /// channels, multi-channel fields in all four channels, see [`DistanceFieldKind`]. Where
+    /// glyphs overlap, the pixel of the glyph it's deeper inside of wins. The background,
+    /// selection, caret and text effects aren't painted, and [`AlphaMode`] doesn't apply.
+    pub distance_field: Option<DistanceField>,
+    /// Color to fill the text area with, padding included, behind the text in the rasterized
+    /// texture and at the start of [`TextState::display_list`]. `None` leaves the background
+    /// transparent. Textures with a [`TextState::distance_field`] aren't filled, the fill would
+    /// cover the field.
+    pub background: Option<Color>,
+    /// How color glyphs, such as emoji, are drawn into the rasterized texture.
+    pub color_glyphs: ColorGlyphs,
+    /// How scroll offsets that aren't whole device pixels are rendered into the rasterized
+    /// texture. Use [`ScrollRendering::Fractional`] for smooth scrolling.
+    pub scroll_rendering: ScrollRendering,
 
     /// Doesn't affect anything - just some metadata that you can later use during rendering
     pub metadata: T,
@@ -177,31 +341,66 @@ impl<T> TextState<T> {
 
         Self {
             params,
+            outer_size: Size::ZERO,
+            padding: Padding::ZERO,
 
             is_editing: false,
             are_actions_enabled: false,
 
             cursor: ByteCursor::default(),
             relative_caret_position: None,
+            split_caret: None,
+            caret_activity: Instant::now(),
+            overwrite_mode: false,
 
             resolved_font_family: FontFamily::SansSerif,
 
             selection: Selection::default(),
+            highlights: Vec::new(),
             last_scroll_timestamp: Instant::now(),
             scroll_interval: Duration::from_millis(50),
-            caret_width: 3.0,
+            cursor_movement: CursorMovement::Logical,
+            backspace: Backspace::GraphemeCluster,
+            selection_colors: None,
+            caret: CaretStyle::default(),
+            caret_blink_visible: true,
+            antialiasing: Antialiasing::Grayscale,
+            distance_field: None,
+            background: None,
+            color_glyphs: ColorGlyphs::Color,
+            scroll_rendering: ScrollRendering::Exact,
             is_selectable: false,
             is_editable: false,
 
             inner_dimensions: Size::ZERO,
             buffer: Buffer::new(font_system, metrics),
+            paragraph_offsets: ParagraphOffsets::default(),
+            suppressed_soft_hyphens: SuppressedSoftHyphens::default(),
 
             rasterized_texture: RasterizedTexture {
                 pixels: Vec::new(),
                 width: 0,
                 height: 0,
+                format: PixelFormat::Rgba8,
+                offset_x: 0,
+                offset_y: 0,
+                subpixel_offset_x: 0.0,
+                subpixel_offset_y: 0.0,
+                dirty_rect: DirtyRect::default(),
             },
             raster_dirty: true,
+            raster_runs: Vec::new(),
+            raster_overlay_runs: Vec::new(),
+            raster_alpha_mode: None,
+            raster_antialiasing: None,
+            raster_effects: None,
+            raster_distance_field: None,
+            raster_background: None,
+            raster_padding: None,
+            raster_gamma_correction: None,
+            raster_color_glyphs: None,
+            raster_scroll_rendering: None,
+            raster_canvas: Vec::new(),
 
             metadata,
         }
@@ -222,7 +421,7 @@ impl<T> TextState<T> {
     /// assert_eq!(state.caret_width(), 2.0);
     /// ```
     pub fn set_caret_width(&mut self, width: f32) {
-        self.caret_width = width;
+        self.caret.width = width;
     }
 
     /// Returns the caret width, which is the width of the cursor when editing text.
@@ -239,12 +438,67 @@ impl<T> TextState<T> {
     /// let width = state.caret_width();
     /// ```
     pub const fn caret_width(&self) -> f32 {
-        self.caret_width
+        self.caret.width
+    }
+
+    /// Sets whether typed characters replace the grapheme after the caret instead of being
+    /// inserted. At the end of a line characters are inserted, and typing over a selection
+    /// replaces the selection. The caret is drawn in [`CaretStyle::overwrite_shape`] while
+    /// overwriting. [`Action::ToggleOverwrite`] switches the mode.
+    ///
+    /// # Examples
+    /// ```
+    /// # use protextinator::{CaretShape, TextState};
+    /// # use cosmic_text::FontSystem;
+    /// # let mut font_system = FontSystem::new();
+    /// # let mut state = TextState::new_with_text("", &mut font_system, ());
+    /// state.set_overwrite_mode(true);
+    /// assert!(state.overwrite_mode());
+    /// assert_eq!(state.caret_shape(), CaretShape::Block);
+    /// ```
+    pub fn set_overwrite_mode(&mut self, overwrite: bool) {
+        self.overwrite_mode = overwrite;
+    }
+
+    /// Returns `true` if typed characters replace the grapheme after the caret.
+    pub const fn overwrite_mode(&self) -> bool {
+        self.overwrite_mode
+    }
+
+    /// Shape the caret is drawn in, depending on the overwrite mode.
+    pub const fn caret_shape(&self) -> CaretShape {
+        if self.overwrite_mode {
+            self.caret.overwrite_shape
+        } else {
+            self.caret.shape
+        }
+    }
+
+    /// Whether the caret should be drawn at `now`, following the blink interval of
+    /// [`TextState::caret`]. The blink restarts in the visible phase whenever the caret moves
+    /// or text is typed or deleted. Always `false` if the text isn't editable.
+    ///
+    /// # Examples
+    /// ```
+    /// # use protextinator::TextState;
+    /// # use cosmic_text::FontSystem;
+    /// # use std::time::{Duration, Instant};
+    /// # let mut font_system = FontSystem::new();
+    /// # let mut state = TextState::new_with_text("", &mut font_system, ());
+    /// state.is_editable = true;
+    /// state.caret.blink_interval = Some(Duration::from_millis(500));
+    /// state.caret_blink_visible = state.caret_visible(Instant::now());
+    /// ```
+    pub fn caret_visible(&self, now: Instant) -> bool {
+        self.is_editable && self.caret.is_visible(self.caret_activity, now)
     }
 
     /// Caret position relative to the buffer viewport with scroll applied. Returns `None` if
     /// the caret is not visible or the buffer is not shaped yet.
     ///
+    /// In vertical writing mode the caret lies across the column, so it is as wide as a line
+    /// is high, and the position is its top-left corner.
+    ///
     /// # Returns
     /// The caret position relative to the viewport, or `None` if not visible
     ///
@@ -260,6 +514,65 @@ impl<T> TextState<T> {
     /// ```
     pub fn caret_position_relative(&self) -> Option<Point> {
         self.relative_caret_position
+            .map(|caret| self.caret_to_area(caret))
+    }
+
+    /// Both caret positions if the caret is at a boundary between left-to-right and
+    /// right-to-left text, where it's usually drawn in two places. Returns `None` if the caret
+    /// has a single position.
+    ///
+    /// # Examples
+    /// ```
+    /// # use protextinator::TextState;
+    /// # use cosmic_text::FontSystem;
+    /// # let mut font_system = FontSystem::new();
+    /// # let state = TextState::new_with_text("Hello", &mut font_system, ());
+    /// if let Some(split) = state.split_caret_relative() {
+    ///     println!("Caret at {:?}, secondary at {:?}", split.primary, split.secondary);
+    /// }
+    /// ```
+    pub fn split_caret_relative(&self) -> Option<SplitCaret> {
+        self.split_caret.map(|split| SplitCaret {
+            primary: self.caret_to_area(split.primary),
+            secondary: self.caret_to_area(split.secondary),
+        })
+    }
+
+    /// Frame for converting LOGICAL positions if the text is laid out in columns. The frame
+    /// spans the area inside of the padding.
+    fn vertical_frame(&self) -> Option<VerticalFrame> {
+        self.style()
+            .writing_mode
+            .is_vertical()
+            .then(|| VerticalFrame::new(self.params.size().x))
+    }
+
+    /// Converts the top-left corner of a caret from the layout frame to the text area.
+    fn caret_to_area(&self, caret: Point) -> Point {
+        let caret = match self.vertical_frame() {
+            Some(frame) => frame.caret_to_area(caret, self.style().line_height_pt()),
+            None => caret,
+        };
+        let offset = self.padding.offset();
+        Point::new(caret.x + offset.x, caret.y + offset.y)
+    }
+
+    /// Converts a LOGICAL position in the text area to the layout frame.
+    fn to_layout_point(&self, point: Point) -> Point {
+        let point = point - self.padding.offset();
+        match self.vertical_frame() {
+            Some(frame) => frame.to_layout(point),
+            None => point,
+        }
+    }
+
+    /// Size of the text content in the layout frame.
+    fn layout_inner_size(&self) -> Size {
+        if self.style().writing_mode.is_vertical() {
+            vertical::transpose(self.inner_dimensions)
+        } else {
+            self.inner_dimensions
+        }
     }
 
     /// Returns the position of the selection lines in the buffer viewport.
@@ -282,6 +595,31 @@ impl<T> TextState<T> {
         &self.selection
     }
 
+    /// Sets the ranges of the text that are drawn with a background color in
+    /// [`TextState::display_list`]. The ranges are byte offsets in the current text and aren't
+    /// updated when the text is edited.
+    ///
+    /// # Arguments
+    /// * `highlights` - The highlighted ranges
+    ///
+    /// # Examples
+    /// ```
+    /// # use protextinator::{Highlight, TextState};
+    /// # use cosmic_text::{Color, FontSystem};
+    /// # let mut font_system = FontSystem::new();
+    /// let mut state = TextState::new_with_text("Hello, world!", &mut font_system, ());
+    /// state.set_highlights(vec![Highlight::new(7..12, Color::rgb(255, 255, 0))]);
+    /// assert_eq!(state.highlights().len(), 1);
+    /// ```
+    pub fn set_highlights(&mut self, highlights: Vec<Highlight>) {
+        self.highlights = highlights;
+    }
+
+    /// Returns the highlighted ranges of the text.
+    pub fn highlights(&self) -> &[Highlight] {
+        &self.highlights
+    }
+
     /// Sets the text in the buffer and updates the cursor position if necessary.
     ///
     /// This method only updates the text content without reshaping. You'll need to call
@@ -387,7 +725,8 @@ impl<T> TextState<T> {
     }
 
     /// Sets the visible area of the text buffer. This is going to be used to determine the buffer's
-    /// viewport size and how much text is visible.
+    /// viewport size and how much text is visible. The text is laid out inside of the
+    /// [padding](TextState::set_padding).
     ///
     /// # Arguments
     /// * `size` - The new visible area size
@@ -401,7 +740,45 @@ impl<T> TextState<T> {
     /// state.set_outer_size(&Size::new(400.0, 200.0));
     /// ```
     pub fn set_outer_size(&mut self, size: &Size) {
-        self.params.set_size(size)
+        self.outer_size = *size;
+        self.params.set_size(&self.padding.inner_size(*size));
+    }
+
+    /// Sets the insets between the edges of the text area and the text, in LOGICAL pixels.
+    ///
+    /// The text is laid out, wrapped, aligned and scrolled inside of the padding, and glyphs are
+    /// clipped to it. Pointer positions passed to [`TextState::handle_press`] and
+    /// [`TextState::handle_drag`], and positions returned by the text state, such as the caret,
+    /// the selection and [`TextState::display_list`], stay relative to the whole text area.
+    ///
+    /// # Arguments
+    /// * `padding` - The insets from the edges of the outer size
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{TextContext, TextState, math::{Padding, Size}};
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.is_editable = true;
+    /// state.set_outer_size(&Size::new(200.0, 40.0));
+    /// state.set_padding(Padding::symmetric(12.0, 8.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// let caret = state.caret_position_relative().unwrap();
+    /// assert_eq!((caret.x, caret.y), (12.0, 8.0));
+    /// ```
+    pub fn set_padding(&mut self, padding: Padding) {
+        if self.padding != padding {
+            self.padding = padding;
+            self.params.set_size(&padding.inner_size(self.outer_size));
+            self.raster_dirty = true;
+        }
+    }
+
+    /// Returns the insets between the edges of the text area and the text.
+    pub const fn padding(&self) -> Padding {
+        self.padding
     }
 
     /// Metadata set to a cosmic_text's buffer
@@ -458,11 +835,12 @@ impl<T> TextState<T> {
     /// assert_eq!(state.outer_size(), Size::new(400.0, 200.0));
     /// ```
     pub fn outer_size(&self) -> Size {
-        self.params.size()
+        self.outer_size
     }
 
     /// Returns the inner dimensions of the text buffer. This represents the actual size of the text
     /// content, which may differ from the outer size if the text is larger than the visible area.
+    /// In vertical writing mode the width is the total width of the columns.
     ///
     /// # Returns
     /// The inner dimensions representing the actual text content size
@@ -503,11 +881,13 @@ impl<T> TextState<T> {
         &self.rasterized_texture
     }
 
-    /// Returns the length of the text in characters. Note that this is different from the
-    /// string .len(), which returns the length in bytes.
+    /// Returns the length of the text in user-perceived characters (extended grapheme clusters).
+    /// Note that this is different from the string .len(), which returns the length in bytes,
+    /// and from the number of `char`s: an emoji with a skin tone or a letter with combining
+    /// marks is a single character.
     ///
     /// # Returns
-    /// The number of Unicode characters in the text
+    /// The number of grapheme clusters in the text
     ///
     /// # Examples
     /// ```
@@ -516,13 +896,16 @@ impl<T> TextState<T> {
     /// # let mut font_system = FontSystem::new();
     /// # let state = TextState::new_with_text("Hello 🦀", &mut font_system, ());
     /// assert_eq!(state.text_char_len(), 7); // 5 ASCII chars + 1 space + 1 emoji
+    /// # let state = TextState::new_with_text("👍🏽e\u{301}", &mut font_system, ());
+    /// # assert_eq!(state.text_char_len(), 2);
     /// ```
     pub fn text_char_len(&self) -> usize {
-        self.params.original_text().chars().count()
+        self.params.original_text().graphemes(true).count()
     }
 
-    /// Returns the char index of the cursor in the text buffer. Note that this returns the
-    /// char index, not the char byte index.
+    /// Returns the index of the user-perceived character (extended grapheme cluster) the cursor
+    /// is before, counted the same way as [`TextState::text_char_len`]. Note that this is not
+    /// the byte index of the cursor.
     ///
     /// # Returns
     /// The character index of the cursor, or `None` if the cursor position is invalid
@@ -538,7 +921,8 @@ impl<T> TextState<T> {
     /// }
     /// ```
     pub fn cursor_char_index(&self) -> Option<usize> {
-        self.cursor.char_index(self.params.text_for_internal_use())
+        self.cursor
+            .grapheme_index(self.params.text_for_internal_use())
     }
 
     fn insert_char_at_cursor(&mut self, character: char, ctx: &mut TextContext) -> ActionResult {
@@ -549,18 +933,28 @@ impl<T> TextState<T> {
         // cosmic_text quirk: when inserting a newline at the end of text that doesn't
         // already end with a newline, we need to insert two newlines so the caret can
         // be placed on the new line
+        let insert_at = self.cursor.byte_character_start;
         if character == '\n' && at_end && !ends_with_newline {
-            self.params
-                .insert_char(self.cursor.byte_character_start, '\n');
-            self.params
-                .insert_char(self.cursor.byte_character_start + 1, '\n');
+            self.params.insert_char(insert_at, '\n');
+            self.params.insert_char(insert_at + 1, '\n');
         } else {
-            self.params
-                .insert_char(self.cursor.byte_character_start, character);
+            self.params.insert_char(insert_at, character);
         }
 
         self.reshape_if_params_changed(ctx);
-        self.move_cursor(ctx, Motion::Next);
+        let style = self.params.style();
+        if style.white_space.collapses_spaces() || style.hyphenation_language().is_some() {
+            // Inserted white space can collapse into the white space next to it, and words are
+            // hyphenated anew, so moving to the next glyph could end up in the wrong place
+            self.update_cursor_before_glyph_with_bytes_offset(insert_at + character.len_utf8());
+        } else {
+            self.move_cursor(ctx, Motion::Next);
+            // A combining mark joins the grapheme before it, so the motion can't step over it
+            let inserted_end = insert_at + character.len_utf8();
+            if character != '\n' && self.cursor.byte_character_start != inserted_end {
+                self.update_cursor_before_glyph_with_bytes_offset(inserted_end);
+            }
+        }
 
         ActionResult::TextChanged
     }
@@ -574,19 +968,22 @@ impl<T> TextState<T> {
     // }
 
     fn remove_char_at_cursor(&mut self) {
-        if !self.params.text_for_internal_use().is_empty() {
-            if let Some(prev_char) = self
+        let text = self.params.text_for_internal_use();
+        let end = self.cursor.byte_character_start;
+        let Some(grapheme_start) = self.cursor.prev_grapheme_byte_offset(text) else {
+            return;
+        };
+        let start = match self.backspace {
+            // A line break is removed as a whole even if it's `\r\n`
+            Backspace::CodePoint if &text[grapheme_start..end] != "\r\n" => self
                 .cursor
-                .prev_char_byte_offset(self.params.text_for_internal_use())
-            {
-                self.remove_character(prev_char);
-                if !self
-                    .cursor
-                    .update_byte_offset(prev_char, self.params.text_for_internal_use())
-                {
-                    // TODO: print a warning
-                }
-            }
+                .prev_char_byte_offset(text)
+                .unwrap_or(grapheme_start),
+            _ => grapheme_start,
+        };
+        self.remove_characters(start, end);
+        if !self.cursor.update_byte_offset(start, &self.params) {
+            // TODO: print a warning
         }
     }
 
@@ -599,17 +996,39 @@ impl<T> TextState<T> {
     }
 
     fn update_cursor_before_glyph_with_cursor(&mut self, cursor: Cursor) {
-        self.cursor
-            .update_cursor(cursor, self.params.text_for_internal_use());
+        let cursor = self.grapheme_boundary(cursor);
+        self.cursor.update_cursor(cursor, &self.params);
+    }
+
+    /// Moves a cursor inside of a grapheme cluster, which hit testing and moving over glyphs can
+    /// end up in, to the start of the cluster.
+    fn grapheme_boundary(&self, mut cursor: Cursor) -> Cursor {
+        if let Some(line) = self
+            .suppressed_soft_hyphens
+            .line_text(&self.buffer, cursor.line)
+        {
+            cursor.index = grapheme_start(&line, cursor.index);
+        }
+        cursor
     }
 
     fn update_cursor_before_glyph_with_bytes_offset(&mut self, byte_offset: usize) {
-        self.cursor
-            .update_byte_offset(byte_offset, self.params.text_for_internal_use());
+        self.cursor.update_byte_offset(byte_offset, &self.params);
     }
 
-    fn remove_character(&mut self, byte_offset: usize) -> Option<char> {
-        self.params.remove_char(byte_offset)
+    /// Removes the grapheme after the cursor, unless the cursor is at the end of a line.
+    fn remove_grapheme_after_cursor(&mut self) {
+        let start = self.cursor.byte_character_start;
+        let text = self.params.text_for_internal_use();
+        let Some(grapheme) = text
+            .get(start..)
+            .and_then(|rest| rest.graphemes(true).next())
+        else {
+            return;
+        };
+        if !grapheme.starts_with(['\n', '\r']) {
+            self.remove_characters(start, start + grapheme.len());
+        }
     }
 
     fn remove_selected_text(&mut self) -> Option<()> {
@@ -715,9 +1134,8 @@ impl<T> TextState<T> {
     fn select_all(&mut self) {
         self.selection.origin_character_byte_cursor = Some(ByteCursor::string_start());
         if !self.params.original_text().is_empty() {
-            self.selection.ends_before_character_byte_cursor = Some(
-                ByteCursor::after_last_character(self.params.original_text()),
-            )
+            self.selection.ends_before_character_byte_cursor =
+                Some(ByteCursor::after_last_character(&self.params))
         } else {
             self.selection.ends_before_character_byte_cursor = None;
         }
@@ -766,6 +1184,8 @@ impl<T> TextState<T> {
     ///
     /// The scroll position represents how much the text content has been scrolled
     /// from its original position. This accounts for both horizontal and vertical scrolling.
+    /// In vertical writing mode `x` is the scroll across the columns, which moves the content to
+    /// the right, and `y` is the scroll along the columns.
     ///
     /// # Returns
     /// A `Point` representing the absolute scroll offset
@@ -783,6 +1203,22 @@ impl<T> TextState<T> {
     /// println!("Scrolled by: ({}, {})", scroll.x, scroll.y);
     /// ```
     pub fn absolute_scroll(&self) -> Point {
+        let scale = self.params.scale_factor().max(0.01);
+        let scroll = self.layout_scroll();
+        // Convert to LOGICAL pixels
+        let scroll = Point {
+            x: scroll.x / scale,
+            y: scroll.y / scale,
+        };
+        if self.style().writing_mode.is_vertical() {
+            vertical::transpose(scroll)
+        } else {
+            scroll
+        }
+    }
+
+    /// Absolute scroll position of the buffer in the layout frame, in DEVICE pixels.
+    fn layout_scroll(&self) -> Point {
         let scale = self.params.scale_factor().max(0.01);
         let scroll = self.buffer.scroll();
         let scroll_line = scroll.line;
@@ -802,10 +1238,34 @@ impl<T> TextState<T> {
                 }
             }
         }
-        // Convert to LOGICAL pixels
+        line_vertical_start += self.paragraph_offsets.line(scroll_line).block_y;
         Point {
-            x: scroll_horizontal / scale,
-            y: (scroll_vertical + line_vertical_start) / scale,
+            x: scroll_horizontal,
+            y: scroll_vertical + line_vertical_start,
+        }
+    }
+
+    /// How far the rasterized content is moved to put the scroll offset on whole DEVICE
+    /// pixels, in the layout frame, see [`ScrollRendering`].
+    fn raster_scroll_shift(&self) -> Point {
+        let scroll = self.layout_scroll();
+        let fraction = |value: f32, rounded: f32| value - rounded;
+        match self.scroll_rendering {
+            ScrollRendering::Exact => Point::ZERO,
+            ScrollRendering::Snapped => Point::new(
+                fraction(scroll.x, scroll.x.round()),
+                fraction(scroll.y, scroll.y.round()),
+            ),
+            // The content moves right and down in the text area, into the extra column and row
+            // of the texture. Columns of vertical text move left as the layout y grows.
+            ScrollRendering::Fractional if self.style().writing_mode.is_vertical() => Point::new(
+                fraction(scroll.x, scroll.x.floor()),
+                fraction(scroll.y, scroll.y.ceil()),
+            ),
+            ScrollRendering::Fractional => Point::new(
+                fraction(scroll.x, scroll.x.floor()),
+                fraction(scroll.y, scroll.y.floor()),
+            ),
         }
     }
 
@@ -830,6 +1290,11 @@ impl<T> TextState<T> {
     /// state.set_absolute_scroll(Point::new(0.0, 50.0));
     /// ```
     pub fn set_absolute_scroll(&mut self, scroll: Point) {
+        let scroll = if self.style().writing_mode.is_vertical() {
+            vertical::transpose(scroll)
+        } else {
+            scroll
+        };
         let mut new_scroll = self.buffer.scroll();
         let scale = self.params.scale_factor().max(0.01);
 
@@ -855,7 +1320,12 @@ impl<T> TextState<T> {
                     }
                 }
 
-                if accumulated_height_device + line_height_total_device > target_y_device {
+                // Space around the paragraph scrolls together with its lines
+                let paragraph_start_device =
+                    accumulated_height_device + self.paragraph_offsets.line(i).block_y;
+                let paragraph_height_device =
+                    line_height_total_device + self.paragraph_offsets.spacing(i);
+                if paragraph_start_device + paragraph_height_device > target_y_device {
                     line_index = i;
                     break;
                 }
@@ -866,7 +1336,9 @@ impl<T> TextState<T> {
 
             // Set the line and calculate the remaining vertical offset (device px)
             new_scroll.line = line_index;
-            new_scroll.vertical = target_y_device - accumulated_height_device;
+            new_scroll.vertical = target_y_device
+                - accumulated_height_device
+                - self.paragraph_offsets.line(line_index).block_y;
         }
 
         // Apply only if changed
@@ -881,6 +1353,53 @@ impl<T> TextState<T> {
         }
     }
 
+    /// Rectangles covering the text between the cursors, one per layout line, in LOGICAL pixels
+    /// relative to the text area.
+    fn range_rects(&self, start: Cursor, end: Cursor) -> Vec<Rect> {
+        let mut rects = Vec::new();
+        for (run, offset) in runs_with_offsets(&self.buffer, &self.paragraph_offsets) {
+            if let Some((start_x, width)) = run.highlight(start, end) {
+                let end_x = offset.map_x(start_x + width);
+                let start_x = offset.map_x(start_x);
+                let top = run.line_top + offset.y;
+                rects.push(self.layout_rect_to_area(Rect::new(
+                    Point::new(start_x, top),
+                    Point::new(end_x, top + run.line_height),
+                )));
+            }
+        }
+        rects
+    }
+
+    /// Converts a rectangle in DEVICE pixels in the layout frame to LOGICAL pixels in the text
+    /// area, applying the horizontal scroll and the padding.
+    fn layout_rect_to_area(&self, rect: Rect) -> Rect {
+        let scale = self.params.scale_factor().max(0.01);
+        let horizontal_scroll = self.buffer.scroll().horizontal;
+        let to_logical =
+            |point: Point| Point::new((point.x - horizontal_scroll) / scale, point.y / scale);
+        self.logical_layout_rect_to_area(Rect::new(to_logical(rect.min), to_logical(rect.max)))
+    }
+
+    /// Converts a rectangle in LOGICAL pixels in the layout frame, with the horizontal scroll
+    /// applied, to the text area.
+    fn logical_layout_rect_to_area(&self, rect: Rect) -> Rect {
+        let rect = match self.vertical_frame() {
+            Some(frame) => frame.rect_to_area(rect),
+            None => rect,
+        };
+        let offset = self.padding.offset();
+        let translate = |point: Point| Point::new(point.x + offset.x, point.y + offset.y);
+        Rect::new(translate(rect.min), translate(rect.max))
+    }
+
+    /// The area inside of the padding, in LOGICAL pixels relative to the text area.
+    fn content_rect(&self) -> Rect {
+        let offset = self.padding.offset();
+        let size = self.params.size();
+        Rect::new(offset, Point::new(offset.x + size.x, offset.y + size.y))
+    }
+
     /// Calculates physical selection area based on the selection start and end glyph indices
     fn recalculate_selection_area(&mut self) -> Option<()> {
         if !self.is_selectable {
@@ -901,19 +1420,16 @@ impl<T> TextState<T> {
         let start_cursor = selection_starts_at_index;
         let end_cursor = selection_ends_before_char_index;
 
-        self.selection.lines.clear();
-        for run in self.buffer.layout_runs() {
-            if let Some((start_x, width)) = run.highlight(start_cursor.cursor, end_cursor.cursor) {
-                let scale = self.params.scale_factor().max(0.01);
-                self.selection.lines.push(SelectionLine {
-                    // Convert to LOGICAL pixels
-                    start_x_pt: Some((start_x - self.buffer.scroll().horizontal) / scale),
-                    end_x_pt: Some((start_x + width - self.buffer.scroll().horizontal) / scale),
-                    start_y_pt: Some(run.line_top / scale),
-                    end_y_pt: Some((run.line_top + run.line_height) / scale),
-                });
-            }
-        }
+        self.selection.lines = self
+            .range_rects(start_cursor.cursor, end_cursor.cursor)
+            .into_iter()
+            .map(|rect| SelectionLine {
+                start_x_pt: Some(rect.min.x),
+                end_x_pt: Some(rect.max.x),
+                start_y_pt: Some(rect.min.y),
+                end_y_pt: Some(rect.max.y),
+            })
+            .collect();
         None
     }
 
@@ -923,12 +1439,19 @@ impl<T> TextState<T> {
         update_reason: UpdateReason,
     ) {
         self.reshape_if_params_changed(ctx);
+        if update_reason.is_cursor_updated() {
+            self.caret_activity = Instant::now();
+        }
         self.adjust_scroll_if_cursor_moved(update_reason, &mut ctx.font_system);
         // TODO: do only if scroll/selection changed
         self.recalculate_selection_area();
 
         // TODO: do that if the buffer was reshaped
-        self.relative_caret_position = self.calculate_caret_position();
+        self.split_caret = self.calculate_split_caret();
+        self.relative_caret_position = match self.split_caret {
+            Some(split_caret) => Some(split_caret.primary),
+            None => self.calculate_caret_position(),
+        };
         self.align_vertically();
     }
 
@@ -958,12 +1481,14 @@ impl<T> TextState<T> {
         // Return caret position in LOGICAL pixels relative to viewport
         let horizontal_scroll_device = self.buffer.scroll().horizontal;
         let scale = self.params.scale_factor().max(0.01);
-        let mut editor = Editor::new(&mut self.buffer);
-        editor.set_cursor(self.cursor.cursor);
-
-        editor.cursor_position().map(|pos| {
+        layout_cursor_position(&mut self.buffer, self.cursor.cursor).map(|pos| {
+            let paragraph_offset =
+                self.paragraph_offsets
+                    .caret_offset(&self.buffer, self.cursor.cursor, pos.y);
             // pos from cosmic_text is in DEVICE pixels
-            let mut point_device = Point::from(pos);
+            let mut point_device = pos;
+            point_device.x += paragraph_offset.x;
+            point_device.y += paragraph_offset.y;
             // Adjust by horizontal scroll (device px)
             point_device.x -= horizontal_scroll_device;
             // Convert to logical
@@ -971,15 +1496,37 @@ impl<T> TextState<T> {
         })
     }
 
+    fn calculate_split_caret(&self) -> Option<SplitCaret> {
+        let cursor = self.cursor.cursor;
+        let scroll = self.buffer.scroll();
+        let scale = self.params.scale_factor().max(0.01);
+        runs_with_offsets(&self.buffer, &self.paragraph_offsets)
+            .filter(|(run, _)| run.line_i == cursor.line)
+            .find_map(|(run, offset)| {
+                let (primary, secondary) =
+                    CaretEdges::new(&run, cursor.index).split(cursor.affinity)?;
+                // Convert to LOGICAL pixels relative to the viewport
+                let y = (run.line_top + offset.y) / scale;
+                let x = |edge: f32| (offset.map_x(edge) - scroll.horizontal) / scale;
+                Some(SplitCaret {
+                    primary: Point::new(x(primary), y),
+                    secondary: Point::new(x(secondary), y),
+                })
+            })
+    }
+
     fn align_vertically(&mut self) {
         if matches!(self.style().vertical_alignment, VerticalTextAlignment::None) {
             return;
         }
 
         let mut scroll = self.buffer.scroll();
-        let text_area_size = self.params.size();
-        let vertical_scroll_to_align_text_logical =
-            calculate_vertical_offset(self.params.style(), text_area_size, self.inner_dimensions);
+        let text_area_size = self.params.layout_size();
+        let vertical_scroll_to_align_text_logical = calculate_vertical_offset(
+            self.params.style(),
+            text_area_size,
+            self.layout_inner_size(),
+        );
         let scale = self.params.scale_factor().max(0.01);
         let target_vertical_device = vertical_scroll_to_align_text_logical * scale;
         if (scroll.vertical - target_vertical_device).abs() > SIZE_EPSILON {
@@ -998,7 +1545,7 @@ impl<T> TextState<T> {
         font_system: &mut FontSystem,
     ) -> Option<()> {
         if update_reason.is_cursor_updated() {
-            let text_area_size = self.params.size();
+            let text_area_size = self.params.layout_size();
             let scale = self.params.scale_factor().max(0.01);
             let old_scroll = self.buffer.scroll();
             let old_relative_caret_x_logical = self.relative_caret_position.map_or(0.0, |p| p.x);
@@ -1008,9 +1555,10 @@ impl<T> TextState<T> {
 
             let caret_position_relative_to_buffer = adjust_vertical_scroll_to_make_caret_visible(
                 &mut self.buffer,
+                &self.paragraph_offsets,
                 self.cursor,
                 font_system,
-                self.params.size(),
+                text_area_size,
                 self.params.style(),
                 scale,
             )?;
@@ -1047,13 +1595,13 @@ impl<T> TextState<T> {
                             (old_scroll.horizontal - text_shift_logical * scale).max(0.0);
 
                         // Ensure we don't scroll beyond the text boundaries
-                        let inner_dimensions = self.inner_size();
-                        let area_width = self.outer_size().x;
+                        let inner_dimensions = self.layout_inner_size();
+                        let area_width = text_area_size.x;
 
                         if inner_dimensions.x > area_width {
                             // Text is larger than viewport - clamp scroll to valid range
                             let max_scroll_device =
-                                (inner_dimensions.x - area_width + self.caret_width) * scale;
+                                (inner_dimensions.x - area_width + self.caret.width) * scale;
                             new_scroll.horizontal = new_scroll.horizontal.min(max_scroll_device);
                         } else {
                             // Text fits within the viewport - no scroll needed
@@ -1063,7 +1611,7 @@ impl<T> TextState<T> {
                 }
             } else if new_absolute_caret_offset > max {
                 new_scroll.horizontal =
-                    (new_absolute_caret_offset - text_area_width + self.caret_width) * scale;
+                    (new_absolute_caret_offset - text_area_width + self.caret.width) * scale;
             } else if new_absolute_caret_offset < min {
                 new_scroll.horizontal = new_absolute_caret_offset * scale;
             } else if new_absolute_caret_offset < 0.0 {
@@ -1103,141 +1651,1272 @@ impl<T> TextState<T> {
         }
         let params_changed = self.params.changed_since_last_shape();
         if params_changed {
-            let new_size = update_buffer(
+            let (new_size, paragraph_offsets, suppressed_soft_hyphens) = update_buffer(
                 &self.params,
                 &mut self.buffer,
                 &mut ctx.font_system,
                 &self.resolved_font_family,
             );
-            self.inner_dimensions = new_size;
+            self.inner_dimensions = if self.style().writing_mode.is_vertical() {
+                vertical::transpose(new_size)
+            } else {
+                new_size
+            };
+            self.paragraph_offsets = paragraph_offsets;
+            self.suppressed_soft_hyphens = suppressed_soft_hyphens;
             self.params.reset_changed();
             // Any layout/text/style/size change requires re-rasterization
             self.raster_dirty = true;
         }
     }
 
-    /// Rasterizes the current text buffer into an RGBA8 CPU texture using device-pixel dimensions.
+    /// Collects the glyphs to draw with their positions in DEVICE pixels relative to the text
+    /// area, including the hyphens at hyphenated line breaks.
     ///
-    /// Returns true if rasterization was performed (and texture updated), false if skipped
-    /// (e.g., zero-sized target).
-    pub(crate) fn rasterize_into_texture(
-        &mut self,
+    /// The glyphs are moved by `scroll_shift` in the layout frame, see
+    /// [`TextState::raster_scroll_shift`].
+    fn positioned_glyphs(
+        &self,
         ctx: &mut TextContext,
-        alpha_mode: AlphaMode,
-    ) -> bool {
-        // Compute device-pixel texture size from the logical outer size and scale factor
-        let size = self.outer_size();
+        scroll_shift: Point,
+    ) -> Vec<PositionedGlyph> {
+        let base_color = cosmic_text::Color::rgba(0, 0, 0, 0);
         let scale = ctx.scale_factor.max(0.01);
-        let width = (size.x * scale).ceil().max(0.0) as u32;
-        let height = (size.y * scale).ceil().max(0.0) as u32;
-        if width == 0 || height == 0 {
-            // No room to rasterize; clear texture and mark clean
-            self.rasterized_texture.width = 0;
-            self.rasterized_texture.height = 0;
-            self.rasterized_texture.pixels.clear();
-            self.raster_dirty = false;
-            return false;
-        }
-
-        let dims_changed =
-            self.rasterized_texture.width != width || self.rasterized_texture.height != height;
+        let vertical_frame = self
+            .params
+            .style()
+            .writing_mode
+            .is_vertical()
+            .then(|| VerticalFrame::new(self.params.size().x * scale));
+        // Glyphs stay on whole pixels, so the padding is rounded
+        let padding_offset = (
+            (self.padding.left * scale).round() as i32,
+            (self.padding.top * scale).round() as i32,
+        );
 
-        // Skip if nothing changed and dimensions match
-        if !dims_changed && !self.raster_dirty {
-            return false;
+        // Same as `Buffer::draw`, but with glyphs moved by the paragraph offsets and the
+        // horizontal scroll
+        let horizontal_scroll = self.buffer.scroll().horizontal;
+        let selected_text = self
+            .selection_colors
+            .and_then(|colors| colors.text)
+            .zip(self.selection_cursors());
+        let mut vertical_alternates = VerticalAlternates::default();
+        let mut glyphs = Vec::new();
+        let runs = runs_with_offsets(&self.buffer, &self.paragraph_offsets);
+        for (run_index, (run, offset)) in runs.enumerate() {
+            let hyphen = hyphen_glyph(&mut ctx.font_system, &run);
+            let glyph_count = run.glyphs.len();
+            for (index, glyph) in run.glyphs.iter().chain(hyphen.as_ref()).enumerate() {
+                let glyph_x = offset.glyph_x(glyph) - horizontal_scroll + scroll_shift.x;
+                let placement = vertical_frame.map(|frame| {
+                    let character = run.text[glyph.start..].chars().next().unwrap_or(' ');
+                    let cell_center = Point::new(
+                        glyph_x + glyph.x + glyph.w / 2.0,
+                        run.line_top + offset.y + scroll_shift.y + run.line_height / 2.0,
+                    );
+                    let upright =
+                        vertical_alternates.is_upright(&mut ctx.font_system, glyph, character);
+                    GlyphPlacement::new(frame, upright, cell_center)
+                });
+                let is_selected = selected_text.is_some_and(|(_, (start, end))| {
+                    (run.line_i, glyph.start) >= (start.line, start.index)
+                        && (run.line_i, glyph.end) <= (end.line, end.index)
+                });
+                let color = match selected_text {
+                    Some((color, _)) if is_selected => color,
+                    _ => glyph.color_opt.unwrap_or(base_color),
+                };
+                glyphs.push(PositionedGlyph {
+                    run_index,
+                    physical: glyph
+                        .physical((glyph_x, run.line_y + offset.y + scroll_shift.y), 1.0),
+                    color,
+                    placement,
+                    offset: padding_offset,
+                    text: (index < glyph_count).then_some((run.line_i, glyph.start..glyph.end)),
+                });
+            }
         }
+        glyphs
+    }
 
-        let required_len = width as usize * height as usize * 4;
-        // Ensure capacity and set length; reuse allocation when possible
-        if self.rasterized_texture.pixels.len() != required_len {
-            self.rasterized_texture.pixels.resize(required_len, 0);
-        }
+    /// Describes the text as quads that sample glyph bitmaps from a shared atlas, for renderers
+    /// that draw many text states from one texture instead of using a texture per state.
+    ///
+    /// Glyphs missing from the atlas are rasterized and inserted into it. Glyphs that don't fit
+    /// into the atlas are left out. Call [`TextState::recalculate`] first to make sure that the
+    /// layout is up to date.
+    ///
+    /// # Arguments
+    /// * `ctx` - The text context used to rasterize missing glyphs
+    /// * `atlas` - The atlas that stores the glyph bitmaps
+    ///
+    /// # Returns
+    /// The glyph quads, in DEVICE pixels relative to the text area
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{CpuGlyphAtlas, TextContext, TextState, math::Size};
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Size::new(200.0, 50.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// let mut atlas = CpuGlyphAtlas::new(256, 256);
+    /// let quads = state.glyph_quads(&mut ctx, &mut atlas);
+    /// assert_eq!(quads.len(), 5);
+    /// ```
+    pub fn glyph_quads(
+        &self,
+        ctx: &mut TextContext,
+        atlas: &mut impl GlyphAtlas,
+    ) -> Vec<GlyphQuad> {
+        self.atlas_quads(ctx, atlas, None)
+    }
 
-        // Clear to transparent before drawing (fast fill)
-        self.rasterized_texture.pixels.fill(0);
+    /// Describes the text as quads that sample distance field glyphs from a shared atlas, which
+    /// stay sharp when the renderer scales them. Works like [`TextState::glyph_quads`], except
+    /// that the glyphs are generated from their outlines.
+    ///
+    /// The atlas is keyed by glyph only, so keep distance field glyphs in a different atlas than
+    /// coverage glyphs, and use one atlas per [`DistanceField`] setting.
+    ///
+    /// # Arguments
+    /// * `ctx` - The text context used to generate missing glyphs
+    /// * `atlas` - The atlas that stores the distance field bitmaps
+    /// * `field` - Kind and spread of the distance fields
+    ///
+    /// # Returns
+    /// The glyph quads, in DEVICE pixels relative to the text area
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{CpuGlyphAtlas, DistanceField, GlyphContent, TextContext, TextState};
+    /// use protextinator::math::Size;
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Size::new(200.0, 50.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// let mut atlas = CpuGlyphAtlas::new(512, 512);
+    /// let field = DistanceField::msdf(4);
+    /// let quads = state.distance_field_quads(&mut ctx, &mut atlas, field);
+    /// assert_eq!(quads.len(), 5);
+    /// assert_eq!(quads[0].glyph.content, GlyphContent::DistanceField(field));
+    /// ```
+    pub fn distance_field_quads(
+        &self,
+        ctx: &mut TextContext,
+        atlas: &mut impl GlyphAtlas,
+        field: DistanceField,
+    ) -> Vec<GlyphQuad> {
+        self.atlas_quads(ctx, atlas, Some(field))
+    }
 
-        let base_color = cosmic_text::Color::rgba(0, 0, 0, 0);
-        let text_width = width;
-        let text_height = height;
-        // TODO: make an atlas via an adapter trait or something that can be passed to here from the renderer
-        self.buffer.draw(
-            &mut ctx.font_system,
-            &mut ctx.swash_cache,
-            base_color,
-            |x, y, mut w, mut h, color| {
-                // Clip to buffer bounds
-                let (x0, y0) = ((x as u32).min(text_width), (y as u32).min(text_height));
-                if x0 >= text_width || y0 >= text_height || w == 0 || h == 0 {
-                    return;
-                }
-                if x0 + w > text_width {
-                    w = text_width - x0;
-                }
-                if y0 + h > text_height {
-                    h = text_height - y0;
-                }
-                // Precompute the 4-byte pixel once per rectangle and use row-wise fills
-                let mut packed_px = [0u8; 4];
-                match alpha_mode {
-                    AlphaMode::Premultiplied => {
-                        let r_lin = srgb_to_linear_u8(color.r());
-                        let g_lin = srgb_to_linear_u8(color.g());
-                        let b_lin = srgb_to_linear_u8(color.b());
-                        let a = color.a() as f32 / 255.0;
-                        let r_pma = r_lin * a;
-                        let g_pma = g_lin * a;
-                        let b_pma = b_lin * a;
-                        packed_px[0] = linear_to_srgb_u8(r_pma);
-                        packed_px[1] = linear_to_srgb_u8(g_pma);
-                        packed_px[2] = linear_to_srgb_u8(b_pma);
-                        packed_px[3] = color.a();
-                    }
-                    AlphaMode::Unmultiplied => {
-                        packed_px[0] = color.r();
-                        packed_px[1] = color.g();
-                        packed_px[2] = color.b();
-                        packed_px[3] = color.a();
-                    }
-                }
+    fn atlas_quads(
+        &self,
+        ctx: &mut TextContext,
+        atlas: &mut impl GlyphAtlas,
+        distance_field: Option<DistanceField>,
+    ) -> Vec<GlyphQuad> {
+        let content = self.content_rect();
+        let scale = ctx.scale_factor.max(0.01);
+        let clip = Rect::new(
+            Point::new(
+                (content.min.x * scale).round(),
+                (content.min.y * scale).round(),
+            ),
+            Point::new(
+                (content.max.x * scale).ceil(),
+                (content.max.y * scale).ceil(),
+            ),
+        );
 
-                // Fill each destination row with the precomputed pixel
-                for row in 0..h {
-                    let dst_row_start = ((y0 + row) * text_width * 4 + x0 * 4) as usize;
-                    let row_slice = &mut self.rasterized_texture.pixels
-                        [dst_row_start..dst_row_start + (w as usize) * 4];
-
-                    // Repeat-copy packed_px across the row
-                    // Avoid per-pixel math; just copy the 4-byte pattern
-                    let mut i = 0usize;
-                    while i + 4 <= row_slice.len() {
-                        row_slice[i..i + 4].copy_from_slice(&packed_px);
-                        i += 4;
+        let mut quads = Vec::new();
+        for glyph in self.positioned_glyphs(ctx, Point::ZERO) {
+            let cache_key = glyph.physical.cache_key;
+            let atlas_glyph = match atlas.get(&cache_key) {
+                Some(atlas_glyph) => atlas_glyph,
+                None => {
+                    let image = if let Some(field) = distance_field {
+                        let Some(bitmap) = ctx.distance_field_cache.get(
+                            &mut ctx.font_system,
+                            &mut ctx.swash_cache,
+                            cache_key,
+                            field,
+                        ) else {
+                            continue;
+                        };
+                        GlyphImage {
+                            width: bitmap.width,
+                            height: bitmap.height,
+                            left: bitmap.left,
+                            top: bitmap.top,
+                            content: GlyphContent::DistanceField(field),
+                            data: &bitmap.data,
+                        }
+                    } else {
+                        let Some(image) =
+                            ctx.swash_cache.get_image(&mut ctx.font_system, cache_key)
+                        else {
+                            continue;
+                        };
+                        let content = match image.content {
+                            SwashContent::Mask => GlyphContent::Mask,
+                            SwashContent::Color => GlyphContent::Color,
+                            SwashContent::SubpixelMask => continue,
+                        };
+                        GlyphImage {
+                            width: image.placement.width,
+                            height: image.placement.height,
+                            left: image.placement.left,
+                            top: image.placement.top,
+                            content,
+                            data: &image.data,
+                        }
+                    };
+                    if image.width == 0 || image.height == 0 {
+                        continue;
                     }
+                    let Some(atlas_glyph) = atlas.insert(cache_key, &image) else {
+                        continue;
+                    };
+                    atlas_glyph
                 }
-            },
-        );
-
-        // Update texture dimensions and clear dirty flag
-        self.rasterized_texture.width = width;
-        self.rasterized_texture.height = height;
-        self.raster_dirty = false;
-
-        true
-    }
-
-    /// Updates the internal scale factor in params; will trigger reshape on next recalc if changed.
-    pub fn set_scale_factor(&mut self, scale: f32) {
-        self.params.set_scale_factor(scale);
-    }
+            };
 
-    fn copy_selected_text(&mut self) -> ActionResult {
-        let selected_text = self.selected_text().unwrap_or("");
-        ActionResult::TextCopied(selected_text.to_string())
+            let (x, y, rotated) =
+                glyph.bitmap_origin(atlas_glyph.left, atlas_glyph.top, atlas_glyph.height);
+            let (width, height) = if rotated {
+                (atlas_glyph.height, atlas_glyph.width)
+            } else {
+                (atlas_glyph.width, atlas_glyph.height)
+            };
+            quads.push(GlyphQuad {
+                cache_key,
+                glyph: atlas_glyph,
+                x,
+                y,
+                width,
+                height,
+                rotated,
+                color: glyph.color,
+                clip,
+            });
+        }
+        quads
     }
 
-    fn paste_text_at_cursor(&mut self, ctx: &mut TextContext, text: &str) -> ActionResult {
-        if !text.is_empty() {
-            self.reset_selection_end();
+    /// Describes how to draw the text state as a list of renderer-agnostic commands: the
+    /// background, highlight and selection backgrounds, glyph runs, decoration lines and the
+    /// caret, in the order they are painted.
+    ///
+    /// All positions are in LOGICAL pixels relative to the text area, with scroll, vertical
+    /// alignment and padding applied. The caret is only included if the text is editable. A
+    /// [`CaretShape::Block`] caret comes before the glyphs, so that the grapheme it covers stays
+    /// visible. Call [`TextState::recalculate`] first to make sure that the layout is up to date.
+    ///
+    /// # Arguments
+    /// * `ctx` - The text context with the fonts of the text
+    ///
+    /// # Returns
+    /// The drawing commands
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{DrawCommand, TextContext, TextState, math::Size};
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Size::new(200.0, 50.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// for command in state.display_list(&mut ctx) {
+    ///     match command {
+    ///         DrawCommand::GlyphRun { glyphs } => assert_eq!(glyphs.len(), 5),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// ```
+    pub fn display_list(&self, ctx: &mut TextContext) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+
+        if let Some(color) = self.background {
+            let size = self.outer_size();
+            commands.push(DrawCommand::Background {
+                rect: Rect::new(Point::ZERO, size),
+                color,
+            });
+        }
+
+        for highlight in &self.highlights {
+            let start = self.params.cursor_for_byte_offset(highlight.range.start);
+            let end = self.params.cursor_for_byte_offset(highlight.range.end);
+            let (Some(start), Some(end)) = (start, end) else {
+                continue;
+            };
+            commands.extend(self.range_rects(start, end).into_iter().map(|rect| {
+                DrawCommand::Highlight {
+                    rect,
+                    color: highlight.color,
+                }
+            }));
+        }
+
+        commands.extend(
+            self.selection
+                .lines
+                .iter()
+                .filter_map(SelectionLine::rect)
+                .map(|rect| DrawCommand::Selection { rect }),
+        );
+
+        let caret = self
+            .caret_rects()
+            .into_iter()
+            .map(|rect| DrawCommand::Caret { rect });
+        if !self.is_caret_above_text() {
+            commands.extend(caret.clone());
+        }
+
+        let scale = self.params.scale_factor().max(0.01);
+        let glyphs = self.positioned_glyphs(ctx, Point::ZERO);
+        for run in glyphs.chunk_by(|a, b| a.run_index == b.run_index) {
+            let glyphs = run
+                .iter()
+                .map(|glyph| {
+                    let (x, y, rotated) = glyph.origin();
+                    DisplayGlyph {
+                        cache_key: glyph.physical.cache_key,
+                        position: Point::new(x as f32 / scale, y as f32 / scale),
+                        color: glyph.color,
+                        rotated,
+                    }
+                })
+                .collect();
+            commands.push(DrawCommand::GlyphRun { glyphs });
+        }
+
+        let decoration = self.style().decoration;
+        if !decoration.is_none() {
+            for (run, offset) in runs_with_offsets(&self.buffer, &self.paragraph_offsets) {
+                let Some(first_glyph) = run.glyphs.first() else {
+                    continue;
+                };
+                let metrics = DecorationMetrics::new(&mut ctx.font_system, first_glyph);
+                let color = first_glyph.color_opt.unwrap_or(self.style().font_color.0);
+                let start_x = run
+                    .glyphs
+                    .iter()
+                    .map(|glyph| offset.glyph_x(glyph) + glyph.x)
+                    .fold(f32::INFINITY, f32::min);
+                let end_x = run
+                    .glyphs
+                    .iter()
+                    .map(|glyph| offset.glyph_x(glyph) + glyph.x + glyph.w)
+                    .fold(f32::NEG_INFINITY, f32::max);
+                let lines = [
+                    decoration.overline.then_some(metrics.overline_offset),
+                    decoration.underline.then_some(metrics.underline_offset),
+                    decoration
+                        .line_through
+                        .then_some(metrics.line_through_offset),
+                ];
+                for line_offset in lines.into_iter().flatten() {
+                    let top = run.line_y + offset.y + line_offset;
+                    let rect = Rect::new(
+                        Point::new(start_x, top),
+                        Point::new(end_x, top + metrics.thickness),
+                    );
+                    commands.push(DrawCommand::Decoration {
+                        rect: self.layout_rect_to_area(rect),
+                        color,
+                    });
+                }
+            }
+        }
+
+        if self.is_caret_above_text() {
+            commands.extend(caret);
+        }
+
+        commands
+    }
+
+    /// Returns the outlines of the laid-out glyphs as vector paths, for exporting text to
+    /// vector formats or tessellating it.
+    ///
+    /// Paths are in LOGICAL pixels relative to the text area, at the same positions the glyphs
+    /// are drawn at, with scroll, alignment, letter spacing and vertical writing applied. Glyphs
+    /// without an outline, such as spaces and bitmap emoji, are left out. Call
+    /// [`TextState::recalculate`] first to make sure that the layout is up to date.
+    ///
+    /// # Arguments
+    /// * `ctx` - The text context with the fonts of the text
+    ///
+    /// # Returns
+    /// One path per glyph, in drawing order
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{PathCommand, TextContext, TextState, math::Size};
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hi there", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Size::new(200.0, 50.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// let paths = state.glyph_paths(&mut ctx);
+    /// // The space has no outline
+    /// assert_eq!(paths.len(), 7);
+    /// assert!(matches!(paths[0].commands[0], PathCommand::MoveTo(_)));
+    /// ```
+    pub fn glyph_paths(&self, ctx: &mut TextContext) -> Vec<GlyphPath> {
+        let scale = self.params.scale_factor().max(0.01);
+        let mut paths = Vec::new();
+        for glyph in self.positioned_glyphs(ctx, Point::ZERO) {
+            let cache_key = glyph.physical.cache_key;
+            let Some(outline) = ctx
+                .swash_cache
+                .get_outline_commands(&mut ctx.font_system, cache_key)
+            else {
+                continue;
+            };
+            if outline.is_empty() {
+                continue;
+            }
+            // Outlines are in DEVICE pixels with y going up, relative to the glyph origin
+            // without its subpixel offset
+            let (offset_x, offset_y) = (cache_key.x_bin.as_float(), cache_key.y_bin.as_float());
+            let point = |point: &zeno::Vector| {
+                let point = glyph.outline_point(point.x + offset_x, -(point.y + offset_y));
+                Point::new(point.x / scale, point.y / scale)
+            };
+            let commands = outline
+                .iter()
+                .map(|command| match command {
+                    zeno::Command::MoveTo(to) => PathCommand::MoveTo(point(to)),
+                    zeno::Command::LineTo(to) => PathCommand::LineTo(point(to)),
+                    zeno::Command::QuadTo(control, to) => {
+                        PathCommand::QuadTo(point(control), point(to))
+                    }
+                    zeno::Command::CurveTo(control1, control2, to) => {
+                        PathCommand::CurveTo(point(control1), point(control2), point(to))
+                    }
+                    zeno::Command::Close => PathCommand::Close,
+                })
+                .collect();
+            paths.push(GlyphPath {
+                cache_key,
+                color: glyph.color,
+                commands,
+            });
+        }
+        paths
+    }
+
+    /// Writes the text state as a standalone SVG document the size of the text area, for visual
+    /// regression artifacts or exporting text into vector design tools.
+    ///
+    /// The document contains what [`TextState::display_list`] describes, in LOGICAL pixels:
+    /// the background, highlights, the selection, glyphs, decoration lines and the caret, each
+    /// included as set in the options. Anything outside of the text area is clipped by the
+    /// document bounds. Call [`TextState::recalculate`] first to make sure that the layout is up
+    /// to date.
+    ///
+    /// # Arguments
+    /// * `ctx` - The text context with the fonts of the text
+    /// * `options` - What to write into the document, and how glyphs are written
+    ///
+    /// # Returns
+    /// The SVG document
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{SvgGlyphs, SvgOptions, TextContext, TextState, math::Size};
+    ///
+    /// let mut ctx = TextContext::default();
+    /// let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Size::new(200.0, 50.0));
+    /// state.recalculate(&mut ctx);
+    ///
+    /// let svg = state.to_svg(&mut ctx, &SvgOptions::DEFAULT);
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(svg.matches("<path").count(), 5);
+    ///
+    /// let svg = state.to_svg(&mut ctx, &SvgOptions::DEFAULT.with_glyphs(SvgGlyphs::Text));
+    /// assert!(svg.contains(">H</text>"));
+    /// ```
+    pub fn to_svg(&self, ctx: &mut TextContext, options: &SvgOptions) -> String {
+        let size = self.outer_size();
+        let mut svg = SvgWriter::new(size.x, size.y);
+        let commands = self.display_list(ctx);
+
+        for command in &commands {
+            match *command {
+                DrawCommand::Background { rect, color } => svg.rect(rect, color),
+                DrawCommand::Highlight { rect, color } if options.highlights => {
+                    svg.rect(rect, color);
+                }
+                DrawCommand::Selection { rect } => {
+                    if let Some(color) = options.selection {
+                        svg.rect(rect, color);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        match options.glyphs {
+            SvgGlyphs::Paths => {
+                for path in self.glyph_paths(ctx) {
+                    svg.path(&path);
+                }
+            }
+            SvgGlyphs::Text => {
+                let scale = self.params.scale_factor().max(0.01);
+                let mut previous_cluster = None;
+                for glyph in self.positioned_glyphs(ctx, Point::ZERO) {
+                    let line_text;
+                    let text = match &glyph.text {
+                        // Only the first glyph of a cluster carries its text
+                        Some((line, range)) => {
+                            if previous_cluster == Some((*line, range.start)) {
+                                continue;
+                            }
+                            previous_cluster = Some((*line, range.start));
+                            line_text = self
+                                .suppressed_soft_hyphens
+                                .line_text(&self.buffer, *line)
+                                .unwrap_or_default();
+                            &line_text[range.clone()]
+                        }
+                        None => "-",
+                    };
+                    if text.trim().is_empty() {
+                        continue;
+                    }
+                    let cache_key = glyph.physical.cache_key;
+                    let Some(face) = ctx.font_system.db().face(cache_key.font_id) else {
+                        continue;
+                    };
+                    let position = glyph
+                        .outline_point(cache_key.x_bin.as_float(), -cache_key.y_bin.as_float());
+                    svg.text(&SvgText {
+                        text,
+                        position: Point::new(position.x / scale, position.y / scale),
+                        rotated: glyph.is_rotated(),
+                        font_family: face
+                            .families
+                            .first()
+                            .map_or("sans-serif", |(name, _)| name.as_str()),
+                        font_size: f32::from_bits(cache_key.font_size_bits) / scale,
+                        font_weight: cache_key.font_weight.0,
+                        italic: face.style != cosmic_text::Style::Normal,
+                        color: glyph.color,
+                    });
+                }
+            }
+        }
+
+        for command in &commands {
+            match *command {
+                DrawCommand::Decoration { rect, color } if options.decorations => {
+                    svg.rect(rect, color);
+                }
+                DrawCommand::Caret { rect } => {
+                    if let Some(color) = options.caret {
+                        svg.rect(rect, color);
+                    }
+                }
+                _ => {}
+            }
+        }
+        svg.finish()
+    }
+
+    /// Rectangles of the caret in its current [`TextState::caret_shape`], in LOGICAL pixels
+    /// relative to the text area. A split bar caret has two rectangles, block and underline
+    /// carets cover the grapheme after the primary caret. Empty if the text isn't editable.
+    ///
+    /// This is the geometry drawn by [`TextState::display_list`] and the rasterized texture.
+    ///
+    /// # Examples
+    /// ```
+    /// # use protextinator::{CaretShape, TextState, TextContext, Point};
+    /// # use cosmic_text::FontSystem;
+    /// # let mut ctx = TextContext::default();
+    /// # let mut state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    /// state.set_outer_size(&Point::new(100.0, 30.0));
+    /// state.is_editable = true;
+    /// state.caret.shape = CaretShape::Block;
+    /// state.recalculate(&mut ctx);
+    /// for rect in state.caret_rects() {
+    ///     println!("Caret block from {:?} to {:?}", rect.min, rect.max);
+    /// }
+    /// ```
+    pub fn caret_rects(&self) -> Vec<Rect> {
+        if !self.is_editable {
+            return Vec::new();
+        }
+        let line_height = self.style().line_height_pt();
+        let shape = self.caret_shape();
+        if shape != CaretShape::Bar {
+            let Some(caret) = self.relative_caret_position else {
+                return Vec::new();
+            };
+            let (start, end) = self.caret_grapheme_span().unwrap_or_else(|| {
+                let width = self.style().font_size.0 / 2.0;
+                (caret.x, caret.x + width)
+            });
+            let bottom = caret.y + line_height;
+            let top = match shape {
+                CaretShape::Underline => bottom - self.caret.width,
+                _ => caret.y,
+            };
+            let rect = Rect::new(Point::new(start, top), Point::new(end, bottom));
+            return vec![self.logical_layout_rect_to_area(rect)];
+        }
+        let caret_size = if self.style().writing_mode.is_vertical() {
+            Size::new(line_height, self.caret.width)
+        } else {
+            Size::new(self.caret.width, line_height)
+        };
+        let carets = match self.split_caret_relative() {
+            Some(split) => vec![split.primary, split.secondary],
+            None => self.caret_position_relative().into_iter().collect(),
+        };
+        carets
+            .into_iter()
+            .map(|caret| {
+                Rect::new(
+                    caret,
+                    Point::new(caret.x + caret_size.x, caret.y + caret_size.y),
+                )
+            })
+            .collect()
+    }
+
+    /// Whether the caret is painted over the glyphs. A block caret is painted behind them, it
+    /// would hide the grapheme it covers otherwise.
+    fn is_caret_above_text(&self) -> bool {
+        self.caret_shape() != CaretShape::Block
+    }
+
+    /// Horizontal extent of the glyphs of the grapheme after the caret, in LOGICAL pixels in
+    /// the layout frame with the horizontal scroll applied. Returns `None` at the end of a line.
+    fn caret_grapheme_span(&self) -> Option<(f32, f32)> {
+        let cursor = self.cursor.cursor;
+        let line = self
+            .suppressed_soft_hyphens
+            .line_text(&self.buffer, cursor.line)?;
+        let grapheme = line.get(cursor.index..)?.graphemes(true).next()?;
+        let end = cursor.index + grapheme.len();
+        let scroll = self.buffer.scroll();
+        let scale = self.params.scale_factor().max(0.01);
+        runs_with_offsets(&self.buffer, &self.paragraph_offsets)
+            .filter(|(run, _)| run.line_i == cursor.line)
+            .flat_map(|(run, offset)| {
+                run.glyphs
+                    .iter()
+                    .filter(move |glyph| glyph.start < end && glyph.end > cursor.index)
+                    .map(move |glyph| {
+                        let x = offset.glyph_x(glyph) + glyph.x - scroll.horizontal;
+                        (x / scale, (x + glyph.w) / scale)
+                    })
+            })
+            .reduce(|(start, end), (glyph_start, glyph_end)| {
+                (start.min(glyph_start), end.max(glyph_end))
+            })
+    }
+
+    /// Selection backgrounds and carets to paint into the rasterized texture, in DEVICE pixels.
+    ///
+    /// The rectangles are moved by `shift`, in DEVICE pixels in the text area.
+    fn raster_overlay(&self, scale: f32, shift: Point) -> Vec<OverlayRect> {
+        let shift = Point::new(shift.x / scale, shift.y / scale);
+        let moved = |rect: Rect| {
+            Rect::new(
+                Point::new(rect.min.x + shift.x, rect.min.y + shift.y),
+                Point::new(rect.max.x + shift.x, rect.max.y + shift.y),
+            )
+        };
+        let mut overlay = Vec::new();
+        if let Some(colors) = self.selection_colors {
+            for rect in self.selection.lines.iter().filter_map(SelectionLine::rect) {
+                overlay.push(OverlayRect::new(
+                    moved(rect),
+                    scale,
+                    colors.background,
+                    false,
+                ));
+            }
+        }
+        if let Some(color) = self.caret.color.filter(|_| self.caret_blink_visible) {
+            let above_text = self.is_caret_above_text();
+            for rect in self.caret_rects() {
+                overlay.push(OverlayRect::new(moved(rect), scale, color, above_text));
+            }
+        }
+        overlay
+    }
+
+    /// Range of the selection in buffer cursors, if there is a selection.
+    fn selection_cursors(&self) -> Option<(Cursor, Cursor)> {
+        if !self.is_selectable {
+            return None;
+        }
+        let origin = self.selection.origin_character_byte_cursor?.cursor;
+        let end = self.selection.ends_before_character_byte_cursor?.cursor;
+        if (origin.line, origin.index) <= (end.line, end.index) {
+            Some((origin, end))
+        } else {
+            Some((end, origin))
+        }
+    }
+
+    /// Rasterizes the current text buffer into a CPU texture with the given pixel format using
+    /// device-pixel dimensions.
+    ///
+    /// Only the pixels of layout lines that changed since the last rasterization are drawn again,
+    /// see [`RasterizedTexture::dirty_rect`]. The texture covers the text area, grown to fit the
+    /// [`TextEffects`] of the style.
+    ///
+    /// Returns true if rasterization was performed (and texture updated) or only the subpixel
+    /// offset of the texture changed, false if skipped (e.g., zero-sized target, or nothing
+    /// visible changed).
+    pub(crate) fn rasterize_into_texture(
+        &mut self,
+        ctx: &mut TextContext,
+        alpha_mode: AlphaMode,
+        format: PixelFormat,
+    ) -> bool {
+        // Compute device-pixel texture size from the logical outer size and scale factor
+        let size = self.outer_size();
+        let scale = ctx.scale_factor.max(0.01);
+        let area_width = (size.x * scale).ceil().max(0.0) as u32;
+        let area_height = (size.y * scale).ceil().max(0.0) as u32;
+        if area_width == 0 || area_height == 0 {
+            // No room to rasterize; clear texture and mark clean
+            self.rasterized_texture.width = 0;
+            self.rasterized_texture.height = 0;
+            self.rasterized_texture.pixels.clear();
+            self.rasterized_texture.format = format;
+            self.rasterized_texture.offset_x = 0;
+            self.rasterized_texture.offset_y = 0;
+            self.rasterized_texture.subpixel_offset_x = 0.0;
+            self.rasterized_texture.subpixel_offset_y = 0.0;
+            self.rasterized_texture.dirty_rect = DirtyRect::default();
+            self.raster_canvas = Vec::new();
+            self.raster_runs.clear();
+            self.raster_overlay_runs.clear();
+            self.raster_dirty = false;
+            return false;
+        }
+
+        // Fractional scroll offsets are either rounded away, or left to the compositor, which
+        // moves the texture that is one pixel wider and taller than the text area
+        let scroll_shift = self.raster_scroll_shift();
+        let area_shift = if self.style().writing_mode.is_vertical() {
+            Point::new(-scroll_shift.y, scroll_shift.x)
+        } else {
+            scroll_shift
+        };
+        let (subpixel_offset_x, subpixel_offset_y, oversize) = match self.scroll_rendering {
+            ScrollRendering::Fractional => (-area_shift.x, -area_shift.y, 1),
+            ScrollRendering::Exact | ScrollRendering::Snapped => (0.0, 0.0, 0),
+        };
+        let subpixel_offset_changed = self.rasterized_texture.subpixel_offset_x
+            != subpixel_offset_x
+            || self.rasterized_texture.subpixel_offset_y != subpixel_offset_y;
+        self.rasterized_texture.subpixel_offset_x = subpixel_offset_x;
+        self.rasterized_texture.subpixel_offset_y = subpixel_offset_y;
+        let area_width = area_width + oversize;
+        let area_height = area_height + oversize;
+
+        // Effects can reach out of the text area, the texture grows to fit them. Distance fields
+        // have no effects.
+        let effects = match self.distance_field {
+            Some(_) => TextEffects::NONE,
+            None => self.style().effects,
+        };
+        let geometry = EffectGeometry::new(&effects, scale);
+        let [margin_left, margin_top, margin_right, margin_bottom] = geometry.margins;
+        let width = area_width + (margin_left + margin_right) as u32;
+        let height = area_height + (margin_top + margin_bottom) as u32;
+        let area_bounds = PixelBounds::new(margin_left, margin_top, area_width, area_height);
+        // Glyphs are clipped to the area inside of the padding, which is rounded to whole pixels
+        // like the glyph positions
+        let content = self.content_rect();
+        let content_min_x = (content.min.x * scale).round() as i32;
+        let content_min_y = (content.min.y * scale).round() as i32;
+        let content_bounds = PixelBounds::new(
+            content_min_x + margin_left,
+            content_min_y + margin_top,
+            ((content.max.x * scale).ceil() as i32 - content_min_x + oversize as i32).max(0) as u32,
+            ((content.max.y * scale).ceil() as i32 - content_min_y + oversize as i32).max(0) as u32,
+        )
+        .intersection(area_bounds);
+
+        let dims_changed = self.rasterized_texture.width != width
+            || self.rasterized_texture.height != height
+            || self.rasterized_texture.format != format
+            || self.rasterized_texture.offset_x != -margin_left
+            || self.rasterized_texture.offset_y != -margin_top;
+
+        let mut overlay = match self.distance_field {
+            Some(_) => Vec::new(),
+            None => self.raster_overlay(scale, area_shift),
+        };
+        for rect in &mut overlay {
+            rect.bounds = rect.bounds.translate(margin_left, margin_top);
+        }
+        let overlay_runs: Vec<RasterRun> = overlay.iter().map(OverlayRect::raster_run).collect();
+
+        // Skip if nothing changed and dimensions match
+        if !dims_changed
+            && !self.raster_dirty
+            && self.raster_alpha_mode == Some(alpha_mode)
+            && self.raster_antialiasing == Some(self.antialiasing)
+            && self.raster_effects == Some(effects)
+            && self.raster_distance_field == Some(self.distance_field)
+            && self.raster_background == Some(self.background)
+            && self.raster_padding == Some(self.padding)
+            && self.raster_gamma_correction == Some(ctx.gamma_correction)
+            && self.raster_color_glyphs == Some(self.color_glyphs)
+            && self.raster_scroll_rendering == Some(self.scroll_rendering)
+            && overlay_runs == self.raster_overlay_runs
+        {
+            self.rasterized_texture.dirty_rect = DirtyRect::default();
+            return subpixel_offset_changed;
+        }
+
+        let glyphs = self.positioned_glyphs(ctx, scroll_shift);
+        let distance_field = self.distance_field;
+        let subpixel_order = match self.antialiasing {
+            _ if distance_field.is_some() => None,
+            Antialiasing::Grayscale => None,
+            Antialiasing::Subpixel { order, .. } => Some(order),
+        };
+        let glyph_bounds: Vec<PixelBounds> = glyphs
+            .iter()
+            .map(|glyph| {
+                let bounds = match distance_field {
+                    Some(field) => glyph.distance_field_bounds(ctx, field),
+                    None => subpixel_order
+                        .and_then(|order| glyph.subpixel_bounds(ctx, order))
+                        .unwrap_or_else(|| glyph.bounds(ctx)),
+                };
+                bounds.translate(margin_left, margin_top)
+            })
+            .collect();
+        let mut runs = Vec::new();
+        let mut run_start = 0;
+        for run in glyphs.chunk_by(|a, b| a.run_index == b.run_index) {
+            let bounds = glyph_bounds[run_start..run_start + run.len()]
+                .iter()
+                .fold(PixelBounds::EMPTY, |bounds, glyph| bounds.union(*glyph));
+            let bounds = geometry.grow(bounds.intersection(content_bounds));
+            let content: Vec<_> = run.iter().map(PositionedGlyph::raster_key).collect();
+            runs.push(RasterRun::new(content, bounds));
+            run_start += run.len();
+        }
+
+        let pixel_count = width as usize * height as usize;
+        let required_len = pixel_count * format.bytes_per_pixel();
+        let redraw_all = dims_changed
+            || self.rasterized_texture.pixels.len() != required_len
+            || self.raster_alpha_mode != Some(alpha_mode)
+            || self.raster_antialiasing != Some(self.antialiasing)
+            || self.raster_effects != Some(effects)
+            || self.raster_distance_field != Some(distance_field)
+            || self.raster_background != Some(self.background)
+            || self.raster_padding != Some(self.padding)
+            || self.raster_gamma_correction != Some(ctx.gamma_correction)
+            || self.raster_color_glyphs != Some(self.color_glyphs)
+            || self.raster_scroll_rendering != Some(self.scroll_rendering);
+        let dirty_rect = if redraw_all {
+            DirtyRect::full(width, height)
+        } else {
+            damaged_bounds(&self.raster_runs, &runs)
+                .union(damaged_bounds(&self.raster_overlay_runs, &overlay_runs))
+                .clip(width, height)
+        };
+        self.raster_runs = runs;
+        self.raster_overlay_runs = overlay_runs;
+        self.raster_alpha_mode = Some(alpha_mode);
+        self.raster_antialiasing = Some(self.antialiasing);
+        self.raster_effects = Some(effects);
+        self.raster_distance_field = Some(distance_field);
+        self.raster_background = Some(self.background);
+        self.raster_padding = Some(self.padding);
+        self.raster_gamma_correction = Some(ctx.gamma_correction);
+        self.raster_color_glyphs = Some(self.color_glyphs);
+        self.raster_scroll_rendering = Some(self.scroll_rendering);
+        self.raster_dirty = false;
+        self.rasterized_texture.dirty_rect = dirty_rect;
+        if dirty_rect.is_empty() {
+            return subpixel_offset_changed;
+        }
+
+        // Ensure capacity and set length; reuse allocation when possible
+        if self.rasterized_texture.pixels.len() != required_len {
+            self.rasterized_texture.pixels.resize(required_len, 0);
+        }
+        // RGBA8 is drawn directly into the texture, other formats are converted from a canvas
+        let mut canvas = if format == PixelFormat::Rgba8 {
+            self.raster_canvas = Vec::new();
+            std::mem::take(&mut self.rasterized_texture.pixels)
+        } else {
+            let mut canvas = std::mem::take(&mut self.raster_canvas);
+            canvas.resize(pixel_count * 4, 0);
+            canvas
+        };
+
+        // Clear the damaged pixels before drawing, subpixel antialiased text is drawn onto an
+        // opaque background
+        let clear_color = match self.antialiasing {
+            _ if distance_field.is_some() => [0; 4],
+            Antialiasing::Grayscale => [0; 4],
+            Antialiasing::Subpixel { background, .. } => {
+                [background.r(), background.g(), background.b(), 255]
+            }
+        };
+        for row in dirty_rect.y..dirty_rect.y + dirty_rect.height {
+            let row_start = (row * width + dirty_rect.x) as usize * 4;
+            for pixel in
+                canvas[row_start..row_start + dirty_rect.width as usize * 4].chunks_exact_mut(4)
+            {
+                pixel.copy_from_slice(&clear_color);
+            }
+        }
+        let dirty_bounds = PixelBounds::new(
+            dirty_rect.x as i32,
+            dirty_rect.y as i32,
+            dirty_rect.width,
+            dirty_rect.height,
+        );
+        // Glyphs and the selection are only drawn inside the padding, effects also outside
+        let draw_bounds = dirty_bounds.intersection(content_bounds);
+        // Half float textures are premultiplied in linear space when they are converted, so their
+        // canvas is composited unmultiplied
+        let canvas_alpha_mode = match format {
+            PixelFormat::Rgba16F => AlphaMode::Unmultiplied,
+            _ => alpha_mode,
+        };
+        // Blends the color with the coverage over the pixel
+        let blend =
+            |pixels: &mut [u8], x: i32, y: i32, color: cosmic_text::Color, coverage: f32| {
+                let alpha = (f32::from(color.a()) * coverage).round() as u8;
+                if alpha == 0 {
+                    return;
+                }
+                let source = [color.r(), color.g(), color.b(), alpha];
+                let index = (y as usize * width as usize + x as usize) * 4;
+                let pixel = &mut pixels[index..index + 4];
+                match canvas_alpha_mode {
+                    AlphaMode::Premultiplied => blend_premultiplied(pixel, source),
+                    AlphaMode::Unmultiplied => blend_unmultiplied(pixel, source),
+                }
+            };
+        // Blends the color over the pixels of the rectangle that are inside the damaged area
+        let fill_rect = |pixels: &mut [u8], bounds: PixelBounds, color: cosmic_text::Color| {
+            let bounds = bounds.intersection(draw_bounds);
+            for y in bounds.min_y..bounds.max_y {
+                for x in bounds.min_x..bounds.max_x {
+                    blend(pixels, x, y, color, 1.0);
+                }
+            }
+        };
+
+        // The background covers the text area with the padding, but not the margins of effects.
+        // Distance fields are kept on a transparent texture, an opaque fill would hide them.
+        if let Some(color) = self.background.filter(|_| distance_field.is_none()) {
+            let bounds = dirty_bounds.intersection(area_bounds);
+            for y in bounds.min_y..bounds.max_y {
+                for x in bounds.min_x..bounds.max_x {
+                    blend(&mut canvas, x, y, color, 1.0);
+                }
+            }
+        }
+
+        // Selection backgrounds go behind the glyphs
+        for rect in overlay.iter().filter(|rect| !rect.above_text) {
+            fill_rect(&mut canvas, rect.bounds, rect.color);
+        }
+
+        // The shadow and outline go behind the glyphs. They are computed from the coverage of
+        // every glyph that can reach the damaged area.
+        if !geometry.is_none() {
+            let reach = geometry.reach();
+            let mut coverage = CoverageMap::new(PixelBounds {
+                min_x: dirty_bounds.min_x - reach,
+                min_y: dirty_bounds.min_y - reach,
+                max_x: dirty_bounds.max_x + reach,
+                max_y: dirty_bounds.max_y + reach,
+            });
+            for (glyph, bounds) in glyphs.iter().zip(&glyph_bounds) {
+                if !bounds.intersects(&coverage.bounds()) {
+                    continue;
+                }
+                if let Some(image) = ctx.color_glyph_cache.get(
+                    &mut ctx.font_system,
+                    &mut ctx.swash_cache,
+                    glyph.physical.cache_key,
+                    glyph.color,
+                ) {
+                    let opacity = f32::from(glyph.color.a()) / 255.0;
+                    for (index, source) in image.data.iter().enumerate() {
+                        let (x, y) = glyph.pixel(
+                            image.left + (index % image.width as usize) as i32,
+                            (index / image.width as usize) as i32 - image.top,
+                        );
+                        let (x, y) = (x + margin_left, y + margin_top);
+                        if PixelBounds::new(x, y, 1, 1).intersects(&content_bounds) {
+                            coverage.add(x, y, f32::from(source[3]) / 255.0 * opacity);
+                        }
+                    }
+                    continue;
+                }
+                ctx.swash_cache.with_pixels(
+                    &mut ctx.font_system,
+                    glyph.physical.cache_key,
+                    glyph.color,
+                    |x, y, color| {
+                        let (x, y) = glyph.pixel(x, y);
+                        let (x, y) = (x + margin_left, y + margin_top);
+                        if PixelBounds::new(x, y, 1, 1).intersects(&content_bounds) {
+                            coverage.add(x, y, f32::from(color.a()) / 255.0);
+                        }
+                    },
+                );
+            }
+            let outline = geometry
+                .outline
+                .map(|(radius, color)| (coverage.dilate(radius), color));
+            if let Some((offset_x, offset_y, box_radius, color)) = geometry.shadow {
+                let shadow = outline
+                    .as_ref()
+                    .map_or(&coverage, |(outline, _)| outline)
+                    .blur(box_radius);
+                for y in dirty_bounds.min_y..dirty_bounds.max_y {
+                    for x in dirty_bounds.min_x..dirty_bounds.max_x {
+                        let coverage = shadow.get(x - offset_x, y - offset_y);
+                        blend(&mut canvas, x, y, color, coverage);
+                    }
+                }
+            }
+            if let Some((outline, color)) = &outline {
+                for y in dirty_bounds.min_y..dirty_bounds.max_y {
+                    for x in dirty_bounds.min_x..dirty_bounds.max_x {
+                        blend(&mut canvas, x, y, *color, outline.get(x, y));
+                    }
+                }
+            }
+        }
+
+        // Coverage corrections depend on the glyph color, one table is built per color
+        let gamma_correction = ctx.gamma_correction;
+        let mut coverage_tables: HashMap<u32, [u8; 256]> = HashMap::default();
+        let mut coverage_table = |color: cosmic_text::Color| {
+            (!gamma_correction.is_none()).then(|| {
+                *coverage_tables
+                    .entry(color.0)
+                    .or_insert_with(|| gamma_correction.table(color))
+            })
+        };
+
+        // Glyphs are drawn in the same order as in a full rasterization, so that overlapping
+        // glyphs end up with the same pixels
+        let color_glyphs = self.color_glyphs;
+        for (glyph, bounds) in glyphs.iter().zip(&glyph_bounds) {
+            if !bounds.intersects(&dirty_bounds) {
+                continue;
+            }
+            if let Some(field) = distance_field {
+                let Some(bitmap) = ctx.distance_field_cache.get(
+                    &mut ctx.font_system,
+                    &mut ctx.swash_cache,
+                    glyph.physical.cache_key,
+                    field,
+                ) else {
+                    continue;
+                };
+                let color = glyph.color;
+                let bytes_per_pixel = field.bytes_per_pixel();
+                for (index, source) in bitmap.data.chunks_exact(bytes_per_pixel).enumerate() {
+                    let (x, y) = glyph.pixel(
+                        bitmap.left + (index % bitmap.width as usize) as i32,
+                        (index / bitmap.width as usize) as i32 - bitmap.top,
+                    );
+                    let (x, y) = (x + margin_left, y + margin_top);
+                    if !PixelBounds::new(x, y, 1, 1).intersects(&draw_bounds) {
+                        continue;
+                    }
+                    let index = (y as usize * width as usize + x as usize) * 4;
+                    let pixel = &mut canvas[index..index + 4];
+                    match field.kind {
+                        DistanceFieldKind::Sdf => {
+                            if source[0] > pixel[3] {
+                                pixel.copy_from_slice(&[
+                                    color.r(),
+                                    color.g(),
+                                    color.b(),
+                                    source[0],
+                                ]);
+                            }
+                        }
+                        // Channels of different glyphs can't be mixed, the pixel is taken from
+                        // the glyph it's deeper inside of
+                        DistanceFieldKind::Msdf => {
+                            let depth =
+                                |pixel: &[u8]| (median(pixel[0], pixel[1], pixel[2]), pixel[3]);
+                            if depth(source) > depth(pixel) {
+                                pixel.copy_from_slice(source);
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            // Color glyphs are drawn with grayscale antialiasing and without coverage correction
+            if let Some(image) = ctx.color_glyph_cache.get(
+                &mut ctx.font_system,
+                &mut ctx.swash_cache,
+                glyph.physical.cache_key,
+                glyph.color,
+            ) {
+                let opacity = f32::from(glyph.color.a()) / 255.0;
+                for (index, source) in image.data.iter().enumerate() {
+                    if source[3] == 0 {
+                        continue;
+                    }
+                    let (x, y) = glyph.pixel(
+                        image.left + (index % image.width as usize) as i32,
+                        (index / image.width as usize) as i32 - image.top,
+                    );
+                    let (x, y) = (x + margin_left, y + margin_top);
+                    if !PixelBounds::new(x, y, 1, 1).intersects(&draw_bounds) {
+                        continue;
+                    }
+                    let coverage = f32::from(source[3]) / 255.0;
+                    match color_glyphs {
+                        ColorGlyphs::Color => {
+                            let color = cosmic_text::Color::rgba(
+                                source[0], source[1], source[2], source[3],
+                            );
+                            blend(&mut canvas, x, y, color, opacity);
+                        }
+                        ColorGlyphs::Monochrome => {
+                            blend(&mut canvas, x, y, glyph.color, coverage);
+                        }
+                    }
+                }
+                continue;
+            }
+            if let Some(order) = subpixel_order.filter(|_| !glyph.is_rotated()) {
+                if let Some(mask) = ctx.subpixel_cache.get(
+                    &mut ctx.font_system,
+                    &mut ctx.swash_cache,
+                    glyph.physical.cache_key,
+                    order,
+                ) {
+                    let color = glyph.color;
+                    let source = [color.r(), color.g(), color.b(), color.a()];
+                    let table = coverage_table(color);
+                    for (index, coverage) in mask.data.iter().enumerate() {
+                        if *coverage == [0; 3] {
+                            continue;
+                        }
+                        let coverage = match &table {
+                            Some(table) => coverage.map(|channel| table[usize::from(channel)]),
+                            None => *coverage,
+                        };
+                        let (x, y) = glyph.pixel(
+                            mask.left + (index % mask.width as usize) as i32,
+                            (index / mask.width as usize) as i32 - mask.top,
+                        );
+                        let (x, y) = (x + margin_left, y + margin_top);
+                        if !PixelBounds::new(x, y, 1, 1).intersects(&draw_bounds) {
+                            continue;
+                        }
+                        let index = (y as usize * width as usize + x as usize) * 4;
+                        blend_subpixel(&mut canvas[index..index + 4], source, coverage);
+                    }
+                    continue;
+                }
+            }
+            // Only the coverage of masks is corrected
+            let is_mask = ctx
+                .swash_cache
+                .get_image(&mut ctx.font_system, glyph.physical.cache_key)
+                .as_ref()
+                .is_some_and(|image| image.content == SwashContent::Mask);
+            let table = coverage_table(glyph.color).filter(|_| is_mask);
+            ctx.swash_cache.with_pixels(
+                &mut ctx.font_system,
+                glyph.physical.cache_key,
+                glyph.color,
+                |x, y, color| {
+                    let (x, y) = glyph.pixel(x, y);
+                    let (x, y) = (x + margin_left, y + margin_top);
+                    let color = match &table {
+                        Some(table) => cosmic_text::Color(
+                            (u32::from(table[usize::from(color.a())]) << 24)
+                                | (color.0 & 0xFF_FF_FF),
+                        ),
+                        None => color,
+                    };
+                    fill_rect(&mut canvas, PixelBounds::new(x, y, 1, 1), color);
+                },
+            );
+        }
+
+        // The caret goes on top of the glyphs
+        for rect in overlay.iter().filter(|rect| rect.above_text) {
+            fill_rect(&mut canvas, rect.bounds, rect.color);
+        }
+
+        if format == PixelFormat::Rgba8 {
+            self.rasterized_texture.pixels = canvas;
+        } else {
+            convert_pixels(
+                &canvas,
+                &mut self.rasterized_texture.pixels,
+                width,
+                dirty_rect,
+                format,
+                alpha_mode,
+            );
+            self.raster_canvas = canvas;
+        }
+
+        // Update texture dimensions
+        self.rasterized_texture.width = width;
+        self.rasterized_texture.height = height;
+        self.rasterized_texture.format = format;
+        self.rasterized_texture.offset_x = -margin_left;
+        self.rasterized_texture.offset_y = -margin_top;
+
+        true
+    }
+
+    /// Updates the internal scale factor in params; will trigger reshape on next recalc if changed.
+    pub fn set_scale_factor(&mut self, scale: f32) {
+        self.params.set_scale_factor(scale);
+    }
+
+    fn copy_selected_text(&mut self) -> ActionResult {
+        let selected_text = self.selected_text().unwrap_or("");
+        ActionResult::TextCopied(selected_text.to_string())
+    }
+
+    fn paste_text_at_cursor(&mut self, ctx: &mut TextContext, text: &str) -> ActionResult {
+        if !text.is_empty() {
+            self.reset_selection_end();
         }
 
         self.params
@@ -1276,7 +2955,7 @@ impl<T> TextState<T> {
         if self.is_text_selected() {
             self.move_cursor_to_selection_right();
         } else {
-            self.move_cursor(ctx, Motion::Right);
+            self.move_cursor_horizontally(ctx, true);
         }
         self.reset_selection();
         self.recalculate_with_update_reason(ctx, UpdateReason::MoveCaret);
@@ -1287,20 +2966,57 @@ impl<T> TextState<T> {
         if self.is_text_selected() {
             self.move_cursor_to_selection_left();
         } else {
-            self.move_cursor(ctx, Motion::Left);
+            self.move_cursor_horizontally(ctx, false);
         }
         self.reset_selection();
         self.recalculate_with_update_reason(ctx, UpdateReason::MoveCaret);
         ActionResult::CursorUpdated
     }
 
+    fn move_cursor_horizontally(&mut self, ctx: &mut TextContext, to_right: bool) -> ActionResult {
+        if self.cursor_movement == CursorMovement::Visual {
+            let old_cursor = self.cursor;
+            match visual_motion(&self.buffer, self.cursor.cursor, to_right) {
+                Some(VisualMotion::Moved(cursor)) => {
+                    self.update_cursor_before_glyph_with_cursor(cursor);
+                    if self.cursor != old_cursor {
+                        return ActionResult::CursorUpdated;
+                    }
+                }
+                Some(VisualMotion::LineEdge(cursor, motion)) => {
+                    self.update_cursor_before_glyph_with_cursor(cursor);
+                    let result = self.move_cursor(ctx, motion);
+                    if result == ActionResult::None {
+                        // There's no line to move to
+                        self.set_cursor_before_glyph(old_cursor);
+                    }
+                    return result;
+                }
+                None => {}
+            }
+        }
+        let motion = if to_right {
+            Motion::Right
+        } else {
+            Motion::Left
+        };
+        self.move_cursor(ctx, motion)
+    }
+
     fn move_cursor(&mut self, ctx: &mut TextContext, motion: Motion) -> ActionResult {
         let buffer = &mut self.buffer;
         let old_cursor = self.cursor.cursor;
         let mut edit = Editor::new(buffer);
         edit.set_cursor(self.cursor.cursor);
         edit.action(&mut ctx.font_system, cosmic_text::Action::Motion(motion));
-        let new_cursor = edit.cursor();
+        let mut new_cursor = edit.cursor();
+        let byte_offset = |cursor| self.params.byte_offset_for_cursor(cursor);
+        if new_cursor != old_cursor && byte_offset(new_cursor) == byte_offset(old_cursor) {
+            // The motion has only stepped over a direction mark or a soft hyphen added to the
+            // display text, which have no position of their own in the text
+            edit.action(&mut ctx.font_system, cosmic_text::Action::Motion(motion));
+            new_cursor = edit.cursor();
+        }
         self.update_cursor_before_glyph_with_cursor(new_cursor);
 
         if self.cursor.cursor == old_cursor {
@@ -1318,19 +3034,136 @@ impl<T> TextState<T> {
     }
 
     fn insert_character(&mut self, character: &SmolStr, ctx: &mut TextContext) -> ActionResult {
+        // Typing over a selection replaces just the selection, even in overwrite mode
+        let overwrite = self.overwrite_mode && !self.is_text_selected();
         if self.is_text_selected() {
             self.move_cursor(ctx, Motion::Left);
             self.remove_selected_text();
         }
-        for character in character.chars() {
-            self.insert_char_at_cursor(character, ctx);
-            self.reset_selection_end();
+        for grapheme in character.graphemes(true) {
+            if overwrite && !grapheme.starts_with(['\n', '\r']) {
+                self.remove_grapheme_after_cursor();
+            }
+            for character in grapheme.chars() {
+                self.insert_char_at_cursor(character, ctx);
+                self.reset_selection_end();
+            }
+        }
+
+        self.recalculate_with_update_reason(ctx, UpdateReason::InsertedText);
+        ActionResult::TextChanged
+    }
+
+    /// Byte offsets of the starts of the lines touched by the selection, or of the line with the
+    /// cursor if nothing is selected. A line that starts exactly where a multi-line selection
+    /// ends is not considered selected.
+    fn selected_line_starts(&self) -> Vec<usize> {
+        let text = self.params.text_for_internal_use();
+        let (start, end) = match (
+            self.selection.origin_character_byte_cursor,
+            self.selection.ends_before_character_byte_cursor,
+        ) {
+            (Some(origin), Some(end)) => {
+                let (a, b) = (origin.byte_character_start, end.byte_character_start);
+                (a.min(b), a.max(b))
+            }
+            _ => (
+                self.cursor.byte_character_start,
+                self.cursor.byte_character_start,
+            ),
+        };
+        let start = start.min(text.len());
+        let end = end.min(text.len());
+
+        let first_line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let mut starts = vec![first_line_start];
+        starts.extend(
+            text[first_line_start..end]
+                .match_indices('\n')
+                .map(|(i, _)| first_line_start + i + 1)
+                .filter(|line_start| *line_start < end && *line_start < text.len()),
+        );
+        starts
+    }
+
+    /// Moves the cursor and the selection ends to new byte offsets after an edit.
+    fn remap_cursors(&mut self, map: impl Fn(usize) -> usize) {
+        let params = &self.params;
+        let cursors = [
+            Some(&mut self.cursor),
+            self.selection.origin_character_byte_cursor.as_mut(),
+            self.selection.ends_before_character_byte_cursor.as_mut(),
+        ];
+        for cursor in cursors.into_iter().flatten() {
+            cursor.update_byte_offset(map(cursor.byte_character_start), params);
         }
+    }
+
+    fn insert_tab(&mut self, ctx: &mut TextContext) -> ActionResult {
+        let is_multiline_selection = self.selected_text().is_some_and(|text| text.contains('\n'));
+        if !is_multiline_selection {
+            return self.insert_character(&SmolStr::new_static("\t"), ctx);
+        }
+
+        let line_starts = self.selected_line_starts();
+        for line_start in line_starts.iter().rev() {
+            self.params.insert_char(*line_start, '\t');
+        }
+        self.remap_cursors(|offset| {
+            offset
+                + line_starts
+                    .iter()
+                    .filter(|line_start| **line_start < offset)
+                    .count()
+        });
 
         self.recalculate_with_update_reason(ctx, UpdateReason::InsertedText);
         ActionResult::TextChanged
     }
 
+    fn outdent(&mut self, ctx: &mut TextContext) -> ActionResult {
+        let tab_width = usize::from(self.style().tab_width.max(1));
+        let text = self.params.text_for_internal_use();
+        let removals: Vec<(usize, usize)> = self
+            .selected_line_starts()
+            .into_iter()
+            .filter_map(|line_start| {
+                let line = &text[line_start..];
+                let indent_len = if line.starts_with('\t') {
+                    1
+                } else {
+                    line.bytes()
+                        .take(tab_width)
+                        .take_while(|byte| *byte == b' ')
+                        .count()
+                };
+                (indent_len > 0).then_some((line_start, indent_len))
+            })
+            .collect();
+        if removals.is_empty() {
+            return ActionResult::None;
+        }
+
+        for (line_start, indent_len) in removals.iter().rev() {
+            self.params
+                .remove_range(*line_start, *line_start + *indent_len);
+        }
+        self.remap_cursors(|offset| {
+            let mut removed = 0;
+            for (line_start, indent_len) in &removals {
+                if offset >= line_start + indent_len {
+                    removed += indent_len;
+                } else if offset > *line_start {
+                    removed += offset - line_start;
+                }
+            }
+            offset - removed
+        });
+
+        self.recalculate_with_update_reason(ctx, UpdateReason::DeletedTextAtCursor);
+        ActionResult::TextChanged
+    }
+
     /// Applies a text editing action and returns the result.
     ///
     /// This is the main method for processing text editing operations like inserting text,
@@ -1365,6 +3198,15 @@ impl<T> TextState<T> {
             return ActionResult::ActionsDisabled;
         }
 
+        // Arrow keys move the caret on screen, so they turn together with the layout
+        let layout_action = self
+            .style()
+            .writing_mode
+            .is_vertical()
+            .then(|| vertical::layout_action(action))
+            .flatten();
+        let action = layout_action.as_ref().unwrap_or(action);
+
         if self.is_selectable {
             let res = if self.is_editable {
                 match action {
@@ -1376,6 +3218,12 @@ impl<T> TextState<T> {
                     Action::MoveCursorUp => self.move_cursor_recalculate(ctx, Motion::Up),
                     Action::MoveCursorDown => self.move_cursor_recalculate(ctx, Motion::Down),
                     Action::InsertChar(character) => self.insert_character(character, ctx),
+                    Action::InsertTab => self.insert_tab(ctx),
+                    Action::Outdent => self.outdent(ctx),
+                    Action::ToggleOverwrite => {
+                        self.overwrite_mode = !self.overwrite_mode;
+                        ActionResult::CursorUpdated
+                    }
                     _ => ActionResult::None,
                 }
             } else {
@@ -1431,7 +3279,8 @@ impl<T> TextState<T> {
 
             let byte_offset_cursor = char_under_position(
                 &self.buffer,
-                click_position_relative_to_area,
+                &self.paragraph_offsets,
+                self.to_layout_point(click_position_relative_to_area),
                 self.params.scale_factor(),
             )?;
             self.update_cursor_before_glyph_with_cursor(byte_offset_cursor);
@@ -1480,23 +3329,25 @@ impl<T> TextState<T> {
             return None;
         }
         if self.is_selectable {
+            let pointer_relative_position = self.to_layout_point(pointer_relative_position);
             let byte_cursor_under_position = char_under_position(
                 &self.buffer,
+                &self.paragraph_offsets,
                 pointer_relative_position,
                 self.params.scale_factor(),
             )?;
 
             if let Some(_origin) = self.selection.origin_character_byte_cursor {
                 self.selection.ends_before_character_byte_cursor = ByteCursor::from_cursor(
-                    byte_cursor_under_position,
-                    self.params.text_for_internal_use(),
+                    self.grapheme_boundary(byte_cursor_under_position),
+                    &self.params,
                 );
             }
 
             // Simple debounce to make scroll speed consistent
             let now = std::time::Instant::now();
             if now > self.last_scroll_timestamp + self.scroll_interval && is_dragging {
-                let element_area = self.params.size();
+                let element_area = self.params.layout_size();
                 let is_dragging_to_the_right = pointer_relative_position.x > 0.0;
                 let is_dragging_to_the_left = pointer_relative_position.x < element_area.x;
 
@@ -1521,6 +3372,203 @@ impl<T> TextState<T> {
     }
 }
 
+/// A solid rectangle painted into the rasterized texture, in DEVICE pixels.
+struct OverlayRect {
+    bounds: PixelBounds,
+    color: cosmic_text::Color,
+    /// Painted over the glyphs instead of behind them.
+    above_text: bool,
+}
+
+impl OverlayRect {
+    /// Converts a rectangle in LOGICAL pixels to whole DEVICE pixels, keeping it at least one
+    /// pixel wide and tall.
+    fn new(rect: Rect, scale: f32, color: cosmic_text::Color, above_text: bool) -> Self {
+        let min_x = (rect.min.x * scale).round() as i32;
+        let min_y = (rect.min.y * scale).round() as i32;
+        let max_x = ((rect.max.x * scale).round() as i32).max(min_x + 1);
+        let max_y = ((rect.max.y * scale).round() as i32).max(min_y + 1);
+        Self {
+            bounds: PixelBounds {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            },
+            color,
+            above_text,
+        }
+    }
+
+    fn raster_run(&self) -> RasterRun {
+        RasterRun::new((self.color.0, self.above_text), self.bounds)
+    }
+}
+
+/// A glyph positioned in the layout frame, in DEVICE pixels.
+struct PositionedGlyph {
+    /// Index of the layout run the glyph belongs to.
+    run_index: usize,
+    physical: PhysicalGlyph,
+    color: cosmic_text::Color,
+    /// How the glyph is moved into the area inside of the padding in vertical writing mode.
+    placement: Option<GlyphPlacement>,
+    /// Offset of the area inside of the padding from the text area, in whole DEVICE pixels.
+    offset: (i32, i32),
+    /// Buffer line and byte range of the text the glyph shows, `None` for the hyphen drawn at
+    /// a soft hyphen line break.
+    text: Option<(usize, Range<usize>)>,
+}
+
+impl PositionedGlyph {
+    /// Position in the text area of the glyph bitmap pixel at the given offset from the glyph
+    /// origin.
+    fn pixel(&self, x: i32, y: i32) -> (i32, i32) {
+        let (x, y) = (self.physical.x + x, self.physical.y + y);
+        let (x, y) = match &self.placement {
+            Some(placement) => placement.pixel(x, y),
+            None => (x, y),
+        };
+        (x + self.offset.0, y + self.offset.1)
+    }
+
+    /// Position in the text area of the glyph origin, and whether the glyph is rotated.
+    fn origin(&self) -> (i32, i32, bool) {
+        let (x, y) = (self.physical.x, self.physical.y);
+        let (x, y, rotated) = match &self.placement {
+            Some(placement @ GlyphPlacement::Upright { .. }) => {
+                let (x, y) = placement.origin(x, y);
+                (x, y, false)
+            }
+            Some(placement @ GlyphPlacement::Sideways(_)) => {
+                let (x, y) = placement.origin(x, y);
+                (x, y, true)
+            }
+            None => (x, y, false),
+        };
+        (x + self.offset.0, y + self.offset.1, rotated)
+    }
+
+    /// Position in the text area of a point of the glyph outline at the given offset from the
+    /// glyph origin, in DEVICE pixels with y going down.
+    fn outline_point(&self, x: f32, y: f32) -> Point {
+        let (origin_x, origin_y) = (self.physical.x, self.physical.y);
+        let point = Point::new(origin_x as f32 + x, origin_y as f32 + y);
+        let point = match &self.placement {
+            Some(placement) => placement.outline_point((origin_x, origin_y), point),
+            None => point,
+        };
+        Point::new(
+            point.x + self.offset.0 as f32,
+            point.y + self.offset.1 as f32,
+        )
+    }
+
+    /// Position in the text area of the top-left corner of a glyph bitmap with the given
+    /// placement, and whether the bitmap is rotated.
+    fn bitmap_origin(&self, left: i32, top: i32, height: u32) -> (i32, i32, bool) {
+        let x = self.physical.x + left;
+        let y = self.physical.y - top;
+        let (x, y, rotated) = match &self.placement {
+            Some(placement) => placement.bitmap_origin(x, y, height),
+            None => (x, y, false),
+        };
+        (x + self.offset.0, y + self.offset.1, rotated)
+    }
+
+    /// Whether the glyph is rotated sideways in vertical text.
+    fn is_rotated(&self) -> bool {
+        matches!(self.placement, Some(GlyphPlacement::Sideways(_)))
+    }
+
+    /// Pixels in the text area covered by the glyph with subpixel antialiasing, or `None` if
+    /// the glyph is drawn with grayscale antialiasing.
+    fn subpixel_bounds(&self, ctx: &mut TextContext, order: SubpixelOrder) -> Option<PixelBounds> {
+        if self.is_rotated() {
+            return None;
+        }
+        let is_color = ctx
+            .color_glyph_cache
+            .get(
+                &mut ctx.font_system,
+                &mut ctx.swash_cache,
+                self.physical.cache_key,
+                self.color,
+            )
+            .is_some();
+        if is_color {
+            return None;
+        }
+        let mask = ctx.subpixel_cache.get(
+            &mut ctx.font_system,
+            &mut ctx.swash_cache,
+            self.physical.cache_key,
+            order,
+        )?;
+        let (x, y, _) = self.bitmap_origin(mask.left, mask.top, mask.height);
+        Some(PixelBounds::new(x, y, mask.width, mask.height))
+    }
+
+    /// Pixels in the text area covered by the distance field of the glyph.
+    fn distance_field_bounds(&self, ctx: &mut TextContext, field: DistanceField) -> PixelBounds {
+        let Some(bitmap) = ctx.distance_field_cache.get(
+            &mut ctx.font_system,
+            &mut ctx.swash_cache,
+            self.physical.cache_key,
+            field,
+        ) else {
+            return PixelBounds::EMPTY;
+        };
+        let (x, y, rotated) = self.bitmap_origin(bitmap.left, bitmap.top, bitmap.height);
+        if rotated {
+            PixelBounds::new(x, y, bitmap.height, bitmap.width)
+        } else {
+            PixelBounds::new(x, y, bitmap.width, bitmap.height)
+        }
+    }
+
+    /// Pixels in the text area covered by the glyph bitmap.
+    fn bounds(&self, ctx: &mut TextContext) -> PixelBounds {
+        if let Some(image) = ctx.color_glyph_cache.get(
+            &mut ctx.font_system,
+            &mut ctx.swash_cache,
+            self.physical.cache_key,
+            self.color,
+        ) {
+            let (x, y, rotated) = self.bitmap_origin(image.left, image.top, image.height);
+            return if rotated {
+                PixelBounds::new(x, y, image.height, image.width)
+            } else {
+                PixelBounds::new(x, y, image.width, image.height)
+            };
+        }
+        let Some(image) = ctx
+            .swash_cache
+            .get_image(&mut ctx.font_system, self.physical.cache_key)
+        else {
+            return PixelBounds::EMPTY;
+        };
+        let placement = image.placement;
+        let (x, y, rotated) = self.bitmap_origin(placement.left, placement.top, placement.height);
+        if rotated {
+            PixelBounds::new(x, y, placement.height, placement.width)
+        } else {
+            PixelBounds::new(x, y, placement.width, placement.height)
+        }
+    }
+
+    /// Everything that affects how the glyph is drawn.
+    fn raster_key(&self) -> (CacheKey, i32, i32, u32, (i32, i32, bool)) {
+        (
+            self.physical.cache_key,
+            self.physical.x,
+            self.physical.y,
+            self.color.0,
+            self.origin(),
+        )
+    }
+}
+
 /// Takes element height, text buffer height, and vertical alignment and returns the vertical offset
 /// needed to align the text vertically.
 ///
@@ -1603,7 +3651,134 @@ impl UpdateReason {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Defines how the caret moves left and right through bidirectional text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CursorMovement {
+    /// The caret moves in logical order: forward or backward through the text depending on the
+    /// direction of the paragraph. Inside runs of the opposite direction it moves the opposite
+    /// way on screen.
+    #[default]
+    Logical,
+    /// The caret always moves in the direction of the pressed key on screen.
+    Visual,
+}
+
+/// Defines how much text [`Action::DeleteBackward`] removes before the caret when nothing is
+/// selected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Backspace {
+    /// The whole user-perceived character (extended grapheme cluster) is removed, such as an
+    /// emoji with a skin tone, a flag or a letter with its combining marks.
+    #[default]
+    GraphemeCluster,
+    /// Only the last code point is removed, so a vowel sign or a virama of an Indic syllable can
+    /// be corrected without retyping the consonant. A `\r\n` line break is still removed as a
+    /// whole.
+    CodePoint,
+}
+
+/// How scroll offsets that aren't whole DEVICE pixels are rendered into the rasterized texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ScrollRendering {
+    /// Glyphs are moved by the exact scroll offset. As the offset changes they are rasterized
+    /// at different subpixel positions, which makes them shimmer during smooth scrolling.
+    #[default]
+    Exact,
+    /// The content is rasterized with the scroll offset rounded to whole device pixels, into a
+    /// texture that is one pixel wider and taller than the text area. The rest of the offset is
+    /// reported in [`RasterizedTexture::subpixel_offset_x`] and
+    /// [`RasterizedTexture::subpixel_offset_y`] for the compositor to move the texture by.
+    /// Scrolling within a pixel only changes the reported offset, and glyphs are always
+    /// rasterized at the same subpixel positions.
+    Fractional,
+    /// The scroll offset is rounded to whole device pixels, so the content moves in steps of
+    /// whole pixels. Hit testing and the caret still use the exact offset.
+    Snapped,
+}
+
+/// Layout of the pixels of a [`RasterizedTexture`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum PixelFormat {
+    /// Four bytes per pixel in red, green, blue, alpha order, with sRGB encoded color.
+    #[default]
+    Rgba8,
+    /// Four bytes per pixel in blue, green, red, alpha order, with sRGB encoded color.
+    Bgra8,
+    /// One byte of coverage per pixel without color, to be tinted by the renderer.
+    A8,
+    /// Four little-endian 16-bit floats per pixel in red, green, blue, alpha order, with
+    /// linear-light color. The text is still composited in 8-bit sRGB and then converted, so the
+    /// values only have the precision of [`PixelFormat::Rgba8`]: this format saves the renderer
+    /// a conversion on upload, it doesn't add precision. With [`AlphaMode::Premultiplied`] the
+    /// color is multiplied by alpha after it is converted to linear light.
+    Rgba16F,
+}
+
+impl PixelFormat {
+    /// Number of bytes per pixel.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::PixelFormat;
+    ///
+    /// assert_eq!(PixelFormat::A8.bytes_per_pixel(), 1);
+    /// assert_eq!(PixelFormat::Rgba16F.bytes_per_pixel(), 8);
+    /// ```
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Bgra8 => 4,
+            Self::A8 => 1,
+            Self::Rgba16F => 8,
+        }
+    }
+}
+
+/// Converts the pixels in the rectangle from an RGBA8 canvas to the pixel format. Half float
+/// pixels are read from an unmultiplied canvas and premultiplied in linear space if `alpha_mode`
+/// asks for it, the other formats keep the alpha mode of the canvas.
+fn convert_pixels(
+    canvas: &[u8],
+    target: &mut [u8],
+    width: u32,
+    rect: DirtyRect,
+    format: PixelFormat,
+    alpha_mode: AlphaMode,
+) {
+    let bytes_per_pixel = format.bytes_per_pixel();
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            let index = (y * width + x) as usize;
+            let source = &canvas[index * 4..index * 4 + 4];
+            let pixel = &mut target[index * bytes_per_pixel..(index + 1) * bytes_per_pixel];
+            match format {
+                PixelFormat::Rgba8 => pixel.copy_from_slice(source),
+                PixelFormat::Bgra8 => {
+                    pixel.copy_from_slice(&[source[2], source[1], source[0], source[3]])
+                }
+                PixelFormat::A8 => pixel[0] = source[3],
+                PixelFormat::Rgba16F => {
+                    let alpha = f32::from(source[3]) / 255.0;
+                    let scale = match alpha_mode {
+                        AlphaMode::Premultiplied => alpha,
+                        AlphaMode::Unmultiplied => 1.0,
+                    };
+                    let channels = [
+                        srgb_to_linear_u8(source[0]) * scale,
+                        srgb_to_linear_u8(source[1]) * scale,
+                        srgb_to_linear_u8(source[2]) * scale,
+                        alpha,
+                    ];
+                    for (channel, value) in channels.into_iter().enumerate() {
+                        pixel[channel * 2..channel * 2 + 2]
+                            .copy_from_slice(&f32_to_f16_bits(value).to_le_bytes());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum AlphaMode {
     /// Use premultiplied alpha for rendering. This is generally preferred for performance
     /// and quality, especially when blending with other premultiplied content.
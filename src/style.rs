@@ -8,6 +8,7 @@ use crate::utils::ArcCowStr;
 use cosmic_text::{Align, Color, Family};
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::hash::Hash;
 
 /// Defines how text should wrap within its container.
@@ -23,6 +24,358 @@ pub enum TextWrap {
     BreakWord,
 }
 
+/// Defines how white space and line breaks in the text are handled, similar to the CSS
+/// `white-space` property.
+///
+/// Collapsing only affects how the text is shaped: [`crate::TextState::text`] still returns the
+/// text as it was set, and caret positions and selections map back to it.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WhiteSpace {
+    /// Sequences of spaces, tabs, and line breaks collapse into a single space, and are removed
+    /// at the start and the end of the text. Text wraps according to [`TextStyle::wrap`].
+    Normal,
+    /// Like [`WhiteSpace::Normal`], but text never wraps.
+    NoWrap,
+    /// White space and line breaks are preserved, and text never wraps.
+    Pre,
+    /// White space and line breaks are preserved. Text wraps according to [`TextStyle::wrap`].
+    #[default]
+    PreWrap,
+    /// Sequences of spaces and tabs collapse into a single space, and are removed at the start
+    /// and the end of every line. Line breaks are preserved. Text wraps according to
+    /// [`TextStyle::wrap`].
+    PreLine,
+}
+
+impl WhiteSpace {
+    /// Returns `true` if sequences of spaces and tabs collapse into a single space.
+    pub const fn collapses_spaces(self) -> bool {
+        matches!(self, Self::Normal | Self::NoWrap | Self::PreLine)
+    }
+
+    /// Returns `true` if line breaks are treated as spaces.
+    pub const fn collapses_line_breaks(self) -> bool {
+        matches!(self, Self::Normal | Self::NoWrap)
+    }
+
+    /// Returns `true` if text can wrap.
+    pub const fn allows_wrap(self) -> bool {
+        !matches!(self, Self::NoWrap | Self::Pre)
+    }
+}
+
+/// Base direction of paragraphs.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextDirection {
+    /// The direction of each paragraph is detected from its first strong character.
+    #[default]
+    Auto,
+    /// Paragraphs are laid out left to right.
+    Ltr,
+    /// Paragraphs are laid out right to left.
+    Rtl,
+}
+
+impl TextDirection {
+    /// The invisible mark that forces the direction when put at the start of a paragraph.
+    pub(crate) const fn mark(self) -> Option<char> {
+        match self {
+            Self::Auto => None,
+            Self::Ltr => Some('\u{200E}'),
+            Self::Rtl => Some('\u{200F}'),
+        }
+    }
+}
+
+/// Direction in which lines flow and lines stack.
+///
+/// In vertical mode the text is laid out in a frame rotated by 90 degrees clockwise: alignments,
+/// wrapping, paragraph styles and scroll positions apply along the columns as they would along
+/// lines. [`HorizontalTextAlignment`] positions the text within a column, and
+/// [`VerticalTextAlignment::Start`] puts the first column at the right edge of the text area.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WritingMode {
+    /// Lines run left to right and stack top to bottom.
+    #[default]
+    HorizontalTb,
+    /// Lines run top to bottom as columns that stack right to left, as in Japanese and Chinese.
+    /// CJK characters are drawn upright, other characters are rotated sideways.
+    VerticalRl,
+}
+
+impl WritingMode {
+    /// Returns `true` if lines are laid out as columns.
+    pub const fn is_vertical(self) -> bool {
+        matches!(self, Self::VerticalRl)
+    }
+}
+
+/// Language of the text, identified by its two-letter ISO 639-1 code.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Language([u8; 2]);
+
+impl Language {
+    /// English.
+    pub const ENGLISH: Self = Self(*b"en");
+    /// German.
+    pub const GERMAN: Self = Self(*b"de");
+    /// French.
+    pub const FRENCH: Self = Self(*b"fr");
+    /// Spanish.
+    pub const SPANISH: Self = Self(*b"es");
+
+    /// Parses a BCP 47 language tag such as `"en-US"` or `"de"`. Only the primary language
+    /// subtag is used, and it must be a two-letter code.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::Language;
+    ///
+    /// assert_eq!(Language::from_tag("en-US"), Some(Language::ENGLISH));
+    /// assert_eq!(Language::from_tag("english"), None);
+    /// ```
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?;
+        match primary.as_bytes() {
+            &[first, second] if first.is_ascii_alphabetic() && second.is_ascii_alphabetic() => {
+                Some(Self([
+                    first.to_ascii_lowercase(),
+                    second.to_ascii_lowercase(),
+                ]))
+            }
+            _ => None,
+        }
+    }
+
+    /// The lowercase ISO 639-1 code of the language.
+    pub const fn code(self) -> [u8; 2] {
+        self.0
+    }
+}
+
+/// Where words can be broken with a hyphen when text wraps.
+///
+/// A hyphen is drawn at the end of a line that breaks inside a word. The text returned by
+/// [`crate::TextState::text`] is never modified.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Hyphens {
+    /// Words only break at soft hyphens (U+00AD) that are already in the text.
+    #[default]
+    Manual,
+    /// Words also break at the hyphenation points found in the dictionary for
+    /// [`TextStyle::language`]. Requires the `hyphenation` feature; without it, or without a
+    /// dictionary for the language, this is the same as `Manual`.
+    Auto,
+}
+
+/// Lines drawn along the text, see [`crate::TextState::display_list`].
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextDecoration {
+    /// Line below the baseline.
+    pub underline: bool,
+    /// Line above the text.
+    pub overline: bool,
+    /// Line through the middle of the text.
+    pub line_through: bool,
+}
+
+impl TextDecoration {
+    /// No decoration lines.
+    pub const NONE: Self = Self {
+        underline: false,
+        overline: false,
+        line_through: false,
+    };
+    /// Only an underline.
+    pub const UNDERLINE: Self = Self::NONE.with_underline(true);
+    /// Only a line through the text.
+    pub const LINE_THROUGH: Self = Self::NONE.with_line_through(true);
+
+    /// Sets whether the text is underlined and returns the modified decoration.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TextDecoration;
+    ///
+    /// let decoration = TextDecoration::LINE_THROUGH.with_underline(true);
+    /// assert!(decoration.underline && decoration.line_through);
+    /// ```
+    pub const fn with_underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Sets whether the text has an overline and returns the modified decoration.
+    pub const fn with_overline(mut self, overline: bool) -> Self {
+        self.overline = overline;
+        self
+    }
+
+    /// Sets whether the text is struck through and returns the modified decoration.
+    pub const fn with_line_through(mut self, line_through: bool) -> Self {
+        self.line_through = line_through;
+        self
+    }
+
+    /// Returns `true` if no lines are drawn.
+    pub const fn is_none(&self) -> bool {
+        !self.underline && !self.overline && !self.line_through
+    }
+}
+
+/// A stroke around the glyphs, drawn behind them by the CPU rasterizer.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextOutline {
+    /// Distance the outline reaches out of the glyphs, in logical pixels.
+    pub width: f32,
+    /// Color of the outline.
+    pub color: FontColor,
+}
+
+impl TextOutline {
+    /// Creates an outline of the given width in logical pixels.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TextOutline;
+    /// use cosmic_text::Color;
+    ///
+    /// let outline = TextOutline::new(1.5, Color::rgb(0, 0, 0));
+    /// ```
+    pub const fn new(width: f32, color: Color) -> Self {
+        Self {
+            width,
+            color: FontColor(color),
+        }
+    }
+}
+
+impl Hash for TextOutline {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.width.to_bits().hash(state);
+        self.color.hash(state);
+    }
+}
+
+impl Eq for TextOutline {}
+
+/// A blurred copy of the glyphs and their outline, drawn behind them by the CPU rasterizer.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextShadow {
+    /// Horizontal offset of the shadow in logical pixels, positive to the right.
+    pub offset_x: f32,
+    /// Vertical offset of the shadow in logical pixels, positive downwards.
+    pub offset_y: f32,
+    /// Blur radius in logical pixels, as in CSS `text-shadow`. Zero gives a sharp shadow.
+    pub blur: f32,
+    /// Color of the shadow.
+    pub color: FontColor,
+}
+
+impl TextShadow {
+    /// Creates a shadow with the given offset and blur radius in logical pixels.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TextShadow;
+    /// use cosmic_text::Color;
+    ///
+    /// let shadow = TextShadow::new(1.0, 2.0, 3.0, Color::rgba(0, 0, 0, 160));
+    /// ```
+    pub const fn new(offset_x: f32, offset_y: f32, blur: f32, color: Color) -> Self {
+        Self {
+            offset_x,
+            offset_y,
+            blur,
+            color: FontColor(color),
+        }
+    }
+
+    /// Creates a glow, which is a shadow right behind the glyphs.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TextShadow;
+    /// use cosmic_text::Color;
+    ///
+    /// let glow = TextShadow::glow(4.0, Color::rgb(255, 255, 0));
+    /// assert_eq!(glow.offset_x, 0.0);
+    /// ```
+    pub const fn glow(blur: f32, color: Color) -> Self {
+        Self::new(0.0, 0.0, blur, color)
+    }
+}
+
+impl Hash for TextShadow {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.offset_x.to_bits().hash(state);
+        self.offset_y.to_bits().hash(state);
+        self.blur.to_bits().hash(state);
+        self.color.hash(state);
+    }
+}
+
+impl Eq for TextShadow {}
+
+/// Effects that keep text legible over busy backgrounds, such as video or maps.
+///
+/// Effects are painted by the CPU rasterizer only, see
+/// [`crate::TextManager::rasterize_all_textures`]. They can reach out of the text area, in
+/// which case the rasterized texture is grown to fit them, see
+/// [`crate::RasterizedTexture::offset_x`].
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextEffects {
+    /// Stroke around the glyphs.
+    pub outline: Option<TextOutline>,
+    /// Shadow or glow behind the glyphs and their outline.
+    pub shadow: Option<TextShadow>,
+}
+
+impl TextEffects {
+    /// No effects.
+    pub const NONE: Self = Self {
+        outline: None,
+        shadow: None,
+    };
+
+    /// Sets the outline and returns the modified effects.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextEffects, TextOutline, TextShadow};
+    /// use cosmic_text::Color;
+    ///
+    /// let effects = TextEffects::NONE
+    ///     .with_outline(TextOutline::new(1.0, Color::rgb(0, 0, 0)))
+    ///     .with_shadow(TextShadow::new(2.0, 2.0, 2.0, Color::rgba(0, 0, 0, 128)));
+    /// assert!(!effects.is_none());
+    /// ```
+    pub const fn with_outline(mut self, outline: TextOutline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Sets the shadow and returns the modified effects.
+    pub const fn with_shadow(mut self, shadow: TextShadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Returns `true` if no effects are drawn.
+    pub const fn is_none(&self) -> bool {
+        self.outline.is_none() && self.shadow.is_none()
+    }
+}
+
 /// Represents the line height as a multiplier of the font size.
 ///
 /// A line height of 1.0 means the line height equals the font size.
@@ -181,6 +534,183 @@ impl LetterSpacing {
     }
 }
 
+/// Width of a tab in spaces, unless a style sets another one.
+const DEFAULT_TAB_WIDTH: u16 = 2;
+
+/// Explicit tab stop positions in logical pixels, measured from the left edge of each line.
+///
+/// A tab advances to the first stop that lies past the current pen position. Tabs that are
+/// past the last explicit stop fall back to the regular stops defined by
+/// [`TextStyle::tab_width`]. Lines are wrapped as if every tab was at most as wide as a space,
+/// so a wrapped line with tabs can be wider than the text area.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serialization", serde(from = "Vec<f32>", into = "Vec<f32>"))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TabStops(Vec<f32>);
+
+impl TabStops {
+    /// Creates tab stops from a list of positions in logical pixels. Non-finite and negative
+    /// positions are dropped, and the remaining ones are sorted, with duplicates removed.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TabStops;
+    ///
+    /// let stops = TabStops::new([120.0, 40.0, -5.0, f32::NAN, 40.0]);
+    /// assert_eq!(stops.positions(), [40.0, 120.0]);
+    /// ```
+    pub fn new(stops: impl IntoIterator<Item = f32>) -> Self {
+        // Adding zero turns -0.0 into 0.0, so that equal stops have equal bits
+        let mut stops: Vec<f32> = stops
+            .into_iter()
+            .filter(|stop| stop.is_finite() && *stop >= 0.0)
+            .map(|stop| stop + 0.0)
+            .collect();
+        stops.sort_by(f32::total_cmp);
+        stops.dedup();
+        Self(stops)
+    }
+
+    /// The stop positions in logical pixels, sorted.
+    pub fn positions(&self) -> &[f32] {
+        &self.0
+    }
+
+    /// Returns the first stop that lies strictly after `x`, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TabStops;
+    ///
+    /// let stops = TabStops::new([40.0, 120.0]);
+    /// assert_eq!(stops.next_stop_after(0.0), Some(40.0));
+    /// assert_eq!(stops.next_stop_after(40.0), Some(120.0));
+    /// assert_eq!(stops.next_stop_after(130.0), None);
+    /// ```
+    pub fn next_stop_after(&self, x: f32) -> Option<f32> {
+        self.0.iter().copied().find(|stop| *stop > x)
+    }
+}
+
+impl Hash for TabStops {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for stop in &self.0 {
+            stop.to_bits().hash(state);
+        }
+    }
+}
+
+impl Eq for TabStops {}
+
+impl From<Vec<f32>> for TabStops {
+    fn from(stops: Vec<f32>) -> Self {
+        Self::new(stops)
+    }
+}
+
+impl From<TabStops> for Vec<f32> {
+    fn from(stops: TabStops) -> Self {
+        stops.0
+    }
+}
+
+/// Paragraph layout settings. All values are in logical pixels.
+///
+/// A paragraph is a line of text as separated by line breaks; wrapped lines belong to the
+/// paragraph they were wrapped from.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ParagraphStyle {
+    /// Indent of the first line, relative to the inset at the start edge of the paragraph: the
+    /// left inset in left-to-right paragraphs, the right inset in right-to-left ones. Can be
+    /// negative to make the first line start before the rest of the paragraph.
+    pub first_line_indent: f32,
+    /// Indent of every line except the first one, relative to the inset at the start edge of the
+    /// paragraph.
+    pub hanging_indent: f32,
+    /// Space between the left edge of the text area and the paragraph.
+    pub left_inset: f32,
+    /// Space between the paragraph and the right edge of the text area.
+    pub right_inset: f32,
+    /// Vertical space above the paragraph.
+    pub space_before: f32,
+    /// Vertical space below the paragraph.
+    pub space_after: f32,
+}
+
+impl ParagraphStyle {
+    /// A paragraph style that doesn't change the layout.
+    pub const NONE: Self = Self {
+        first_line_indent: 0.0,
+        hanging_indent: 0.0,
+        left_inset: 0.0,
+        right_inset: 0.0,
+        space_before: 0.0,
+        space_after: 0.0,
+    };
+
+    /// Sets the first line indent and returns the modified style.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::ParagraphStyle;
+    ///
+    /// let style = ParagraphStyle::NONE.with_first_line_indent(24.0);
+    /// assert_eq!(style.first_line_indent, 24.0);
+    /// ```
+    pub const fn with_first_line_indent(mut self, indent: f32) -> Self {
+        self.first_line_indent = indent;
+        self
+    }
+
+    /// Sets the hanging indent and returns the modified style.
+    pub const fn with_hanging_indent(mut self, indent: f32) -> Self {
+        self.hanging_indent = indent;
+        self
+    }
+
+    /// Sets the left and right insets and returns the modified style.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::ParagraphStyle;
+    ///
+    /// let style = ParagraphStyle::NONE.with_insets(16.0, 8.0);
+    /// assert_eq!(style.left_inset, 16.0);
+    /// assert_eq!(style.right_inset, 8.0);
+    /// ```
+    pub const fn with_insets(mut self, left: f32, right: f32) -> Self {
+        self.left_inset = left;
+        self.right_inset = right;
+        self
+    }
+
+    /// Sets the space before and after the paragraph and returns the modified style.
+    pub const fn with_spacing(mut self, before: f32, after: f32) -> Self {
+        self.space_before = before;
+        self.space_after = after;
+        self
+    }
+
+    /// Returns `true` if the style doesn't change the layout.
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+impl Hash for ParagraphStyle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.first_line_indent.to_bits().hash(state);
+        self.hanging_indent.to_bits().hash(state);
+        self.left_inset.to_bits().hash(state);
+        self.right_inset.to_bits().hash(state);
+        self.space_before.to_bits().hash(state);
+        self.space_after.to_bits().hash(state);
+    }
+}
+
+impl Eq for ParagraphStyle {}
+
 /// Wrapper around [`cosmic_text::Color`] for text color representation.
 ///
 /// Provides convenient constructors for creating colors from RGB and RGBA values.
@@ -513,6 +1043,21 @@ pub struct TextStyle {
     pub vertical_alignment: VerticalTextAlignment,
     /// Text wrapping behavior.
     pub wrap: Option<TextWrap>,
+    /// White space collapsing and line break handling. [`WhiteSpace::Pre`] and
+    /// [`WhiteSpace::NoWrap`] disable wrapping regardless of `wrap`.
+    pub white_space: WhiteSpace,
+    /// Base direction of paragraphs.
+    pub direction: TextDirection,
+    /// Whether lines are laid out horizontally or as vertical columns.
+    pub writing_mode: WritingMode,
+    /// Language of the text, used to pick the hyphenation dictionary.
+    pub language: Option<Language>,
+    /// Where words can be broken with a hyphen when text wraps.
+    pub hyphens: Hyphens,
+    /// Lines drawn along the text.
+    pub decoration: TextDecoration,
+    /// Outline and shadow drawn behind the text.
+    pub effects: TextEffects,
     /// The font family to use for rendering. Can be a generic family created with an enum, or
     /// you can use a CSS-like font family query string to specify custom fonts:
     /// `"Helvetica, 'Segoe UI', sans-serif".into()`.
@@ -521,6 +1066,15 @@ pub struct TextStyle {
     pub weight: Weight,
     /// The spacing between characters as a multiplier of the font size.
     pub letter_spacing: Option<LetterSpacing>,
+    /// The distance between regular tab stops, in widths of a space character. Defaults to 2.
+    pub tab_width: u16,
+    /// Explicit tab stop positions. Tabs past the last explicit stop use `tab_width`.
+    pub tab_stops: Option<TabStops>,
+    /// Paragraph layout settings applied to every paragraph.
+    pub paragraph: ParagraphStyle,
+    /// Paragraph layout settings for specific paragraphs, keyed by line index. These replace
+    /// `paragraph` for the given lines.
+    pub paragraph_overrides: BTreeMap<usize, ParagraphStyle>,
 }
 
 impl Hash for TextStyle {
@@ -531,6 +1085,17 @@ impl Hash for TextStyle {
         self.horizontal_alignment.hash(state);
         self.vertical_alignment.hash(state);
         self.wrap.hash(state);
+        self.white_space.hash(state);
+        self.direction.hash(state);
+        self.writing_mode.hash(state);
+        self.language.hash(state);
+        self.hyphens.hash(state);
+        self.decoration.hash(state);
+        self.effects.hash(state);
+        self.tab_width.hash(state);
+        self.tab_stops.hash(state);
+        self.paragraph.hash(state);
+        self.paragraph_overrides.hash(state);
     }
 }
 
@@ -544,8 +1109,14 @@ impl Default for TextStyle {
     /// - No overflow handling
     /// - Start horizontal alignment
     /// - Start vertical alignment  
-    /// - No text wrapping
+    /// - No text wrapping, white space is preserved
+    /// - Paragraph direction detected from the text
+    /// - Horizontal writing mode
+    /// - No language, words only break at soft hyphens
+    /// - No decoration lines or effects
     /// - Sans-serif font family
+    /// - Tab width of 2 spaces, no explicit tab stops
+    /// - No paragraph indents or spacing
     fn default() -> Self {
         Self::DEFAULT
     }
@@ -559,9 +1130,20 @@ impl TextStyle {
         horizontal_alignment: HorizontalTextAlignment::Start,
         vertical_alignment: VerticalTextAlignment::Start,
         wrap: None,
+        white_space: WhiteSpace::PreWrap,
+        direction: TextDirection::Auto,
+        writing_mode: WritingMode::HorizontalTb,
+        language: None,
+        hyphens: Hyphens::Manual,
+        decoration: TextDecoration::NONE,
+        effects: TextEffects::NONE,
         font_family: FontFamily::SansSerif,
         weight: Weight::NORMAL,
         letter_spacing: None,
+        tab_width: DEFAULT_TAB_WIDTH,
+        tab_stops: None,
+        paragraph: ParagraphStyle::NONE,
+        paragraph_overrides: BTreeMap::new(),
     };
 
     /// Creates a new `TextStyle` with the specified font size and color.
@@ -587,9 +1169,20 @@ impl TextStyle {
             horizontal_alignment: HorizontalTextAlignment::Start,
             vertical_alignment: VerticalTextAlignment::Start,
             wrap: None,
+            white_space: WhiteSpace::PreWrap,
+            direction: TextDirection::Auto,
+            writing_mode: WritingMode::HorizontalTb,
+            language: None,
+            hyphens: Hyphens::Manual,
+            decoration: TextDecoration::NONE,
+            effects: TextEffects::NONE,
             font_family: FontFamily::SansSerif,
             weight: Weight::NORMAL,
             letter_spacing: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+            tab_stops: None,
+            paragraph: ParagraphStyle::NONE,
+            paragraph_overrides: BTreeMap::new(),
         }
     }
 
@@ -714,6 +1307,227 @@ impl TextStyle {
         self
     }
 
+    /// Sets how white space and line breaks are handled and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `white_space` - The white space handling mode
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextStyle, WhiteSpace};
+    ///
+    /// let style = TextStyle::default().with_white_space(WhiteSpace::Normal);
+    /// ```
+    pub const fn with_white_space(mut self, white_space: WhiteSpace) -> Self {
+        self.white_space = white_space;
+        self
+    }
+
+    /// Sets the base direction of paragraphs and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `direction` - The paragraph direction
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextDirection, TextStyle};
+    ///
+    /// let style = TextStyle::default().with_direction(TextDirection::Rtl);
+    /// ```
+    pub const fn with_direction(mut self, direction: TextDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the writing mode and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `writing_mode` - Whether lines are laid out horizontally or as vertical columns
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextStyle, WritingMode};
+    ///
+    /// let style = TextStyle::default().with_writing_mode(WritingMode::VerticalRl);
+    /// ```
+    pub const fn with_writing_mode(mut self, writing_mode: WritingMode) -> Self {
+        self.writing_mode = writing_mode;
+        self
+    }
+
+    /// Sets the language of the text and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `language` - The language of the text
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{Language, TextStyle};
+    ///
+    /// let style = TextStyle::default().with_language(Language::GERMAN);
+    /// ```
+    pub const fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Sets where words can be broken with a hyphen and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `hyphens` - The hyphenation mode
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{Hyphens, Language, TextStyle, TextWrap};
+    ///
+    /// let style = TextStyle::default()
+    ///     .with_wrap(TextWrap::Wrap)
+    ///     .with_language(Language::ENGLISH)
+    ///     .with_hyphens(Hyphens::Auto);
+    /// ```
+    pub const fn with_hyphens(mut self, hyphens: Hyphens) -> Self {
+        self.hyphens = hyphens;
+        self
+    }
+
+    /// Sets the lines drawn along the text and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `decoration` - The decoration lines
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextDecoration, TextStyle};
+    ///
+    /// let style = TextStyle::default().with_decoration(TextDecoration::UNDERLINE);
+    /// ```
+    pub const fn with_decoration(mut self, decoration: TextDecoration) -> Self {
+        self.decoration = decoration;
+        self
+    }
+
+    /// Sets the outline and shadow drawn behind the text and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `effects` - The text effects
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TextEffects, TextShadow, TextStyle};
+    /// use cosmic_text::Color;
+    ///
+    /// let shadow = TextShadow::glow(3.0, Color::rgb(0, 0, 0));
+    /// let style = TextStyle::default().with_effects(TextEffects::NONE.with_shadow(shadow));
+    /// ```
+    pub const fn with_effects(mut self, effects: TextEffects) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Returns the language to hyphenate the text with, if it is hyphenated automatically.
+    pub(crate) fn hyphenation_language(&self) -> Option<Language> {
+        match self.hyphens {
+            Hyphens::Manual => None,
+            Hyphens::Auto => self.language,
+        }
+    }
+
+    /// Returns the wrapping behavior after applying the white space mode.
+    pub(crate) fn effective_wrap(&self) -> TextWrap {
+        if self.white_space.allows_wrap() {
+            self.wrap.unwrap_or_default()
+        } else {
+            TextWrap::NoWrap
+        }
+    }
+
+    /// Sets the tab width in spaces and returns the modified style. A width of 0 is treated
+    /// as 1.
+    ///
+    /// # Arguments
+    /// * `tab_width` - The distance between tab stops, in widths of a space character
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::TextStyle;
+    ///
+    /// let style = TextStyle::default().with_tab_width(4);
+    /// assert_eq!(style.tab_width, 4);
+    /// ```
+    pub const fn with_tab_width(mut self, tab_width: u16) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Sets explicit tab stop positions in logical pixels and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `tab_stops` - The tab stop positions, measured from the left edge of each line
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{TabStops, TextStyle};
+    ///
+    /// let style = TextStyle::default().with_tab_stops(TabStops::new([80.0, 160.0]));
+    /// ```
+    pub fn with_tab_stops(mut self, tab_stops: TabStops) -> Self {
+        self.tab_stops = Some(tab_stops);
+        self
+    }
+
+    /// Sets the paragraph style used for every paragraph and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `paragraph` - The paragraph layout settings
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{ParagraphStyle, TextStyle};
+    ///
+    /// let style = TextStyle::default().with_paragraph(
+    ///     ParagraphStyle::NONE
+    ///         .with_first_line_indent(24.0)
+    ///         .with_spacing(0.0, 8.0),
+    /// );
+    /// ```
+    pub const fn with_paragraph(mut self, paragraph: ParagraphStyle) -> Self {
+        self.paragraph = paragraph;
+        self
+    }
+
+    /// Overrides the paragraph style of a single paragraph and returns the modified style.
+    ///
+    /// # Arguments
+    /// * `line_index` - Index of the paragraph, i.e. the number of line breaks before it
+    /// * `paragraph` - The paragraph layout settings for that paragraph
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::style::{ParagraphStyle, TextStyle};
+    ///
+    /// let quote = ParagraphStyle::NONE.with_insets(32.0, 32.0);
+    /// let style = TextStyle::default().with_paragraph_override(2, quote);
+    /// assert_eq!(style.paragraph_style(2), quote);
+    /// assert_eq!(style.paragraph_style(0), ParagraphStyle::NONE);
+    /// ```
+    pub fn with_paragraph_override(mut self, line_index: usize, paragraph: ParagraphStyle) -> Self {
+        self.paragraph_overrides.insert(line_index, paragraph);
+        self
+    }
+
+    /// Returns the paragraph style used for the paragraph at the given line index.
+    pub fn paragraph_style(&self, line_index: usize) -> ParagraphStyle {
+        self.paragraph_overrides
+            .get(&line_index)
+            .copied()
+            .unwrap_or(self.paragraph)
+    }
+
+    /// Returns `true` if any paragraph has indents, insets, or spacing.
+    pub(crate) fn has_paragraph_styles(&self) -> bool {
+        !self.paragraph.is_none() || self.paragraph_overrides.values().any(|p| !p.is_none())
+    }
+
     /// Calculates the line height in points based on the font size and line height multiplier.
     ///
     /// # Returns
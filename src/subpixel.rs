@@ -0,0 +1,156 @@
+//! Subpixel (LCD) antialiasing.
+//!
+//! Glyph outlines are rendered at three times the horizontal resolution, so that every color
+//! channel of an LCD pixel gets its own coverage sample. The samples are smoothed with the
+//! same five-tap filter FreeType uses by default, which trades a little sharpness for much less
+//! color fringing.
+
+use ahash::HashMap;
+use cosmic_text::{CacheKey, CacheKeyFlags, Color, FontSystem, SwashCache, SwashContent};
+use zeno::{Format, Mask, Origin, Transform, Vector};
+
+/// Weights of the LCD filter, which add up to 256.
+const LCD_FILTER: [u32; 5] = [8, 77, 86, 77, 8];
+
+/// Order of the color channels of the physical pixels of a display, from left to right.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum SubpixelOrder {
+    /// Red on the left, blue on the right. Most displays use this order.
+    #[default]
+    Rgb,
+    /// Blue on the left, red on the right.
+    Bgr,
+}
+
+/// How glyph edges are smoothed in the rasterized texture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Antialiasing {
+    /// Every pixel has a single coverage value. The texture can be drawn over anything.
+    #[default]
+    Grayscale,
+    /// Every color channel has its own coverage value, which makes text sharper on low-DPI LCD
+    /// displays. Color channels can't be blended with what's behind the texture, so glyphs are
+    /// blended onto `background` and the texture is opaque.
+    ///
+    /// Color glyphs, such as emoji, and glyphs rotated in vertical text are drawn with grayscale
+    /// antialiasing.
+    Subpixel {
+        /// Channel order of the display the texture is shown on.
+        order: SubpixelOrder,
+        /// Opaque color the text is drawn onto.
+        background: Color,
+    },
+}
+
+/// Per-channel coverage of a glyph.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SubpixelMask {
+    /// Offset of the left edge from the glyph origin.
+    pub left: i32,
+    /// Offset of the top edge above the baseline.
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Coverage of the red, green and blue channels of each pixel, rows from top to bottom.
+    pub data: Vec<[u8; 3]>,
+}
+
+/// Cache for per-channel glyph coverage.
+#[derive(Debug, Default)]
+pub struct SubpixelCache {
+    masks: HashMap<(CacheKey, SubpixelOrder), Option<SubpixelMask>>,
+}
+
+impl SubpixelCache {
+    /// Creates an empty cache. Masks are rendered the first time a glyph is drawn with subpixel
+    /// antialiasing, see [`crate::TextContext::subpixel_cache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the per-channel coverage of the glyph, or `None` if the glyph has to be drawn
+    /// with grayscale antialiasing.
+    pub(crate) fn get(
+        &mut self,
+        font_system: &mut FontSystem,
+        swash_cache: &mut SwashCache,
+        cache_key: CacheKey,
+        order: SubpixelOrder,
+    ) -> Option<&SubpixelMask> {
+        self.masks
+            .entry((cache_key, order))
+            .or_insert_with(|| render_mask(font_system, swash_cache, cache_key, order))
+            .as_ref()
+    }
+}
+
+fn render_mask(
+    font_system: &mut FontSystem,
+    swash_cache: &mut SwashCache,
+    cache_key: CacheKey,
+    order: SubpixelOrder,
+) -> Option<SubpixelMask> {
+    // Color glyphs keep their colors, and pixel fonts are meant to have sharp pixels
+    if cache_key.flags.contains(CacheKeyFlags::PIXEL_FONT) {
+        return None;
+    }
+    let image = swash_cache.get_image(font_system, cache_key).as_ref()?;
+    if image.content != SwashContent::Mask {
+        return None;
+    }
+    let commands = swash_cache.get_outline_commands(font_system, cache_key)?;
+
+    // Render one sample per color channel, y goes up in the outline
+    let offset = Vector::new(cache_key.x_bin.as_float() * 3.0, cache_key.y_bin.as_float());
+    let mut mask = Mask::new(commands);
+    mask.format(Format::Alpha)
+        .origin(Origin::BottomLeft)
+        .transform(Some(Transform::scale(3.0, 1.0)))
+        .offset(offset)
+        .render_offset(offset);
+    let (samples, placement) = mask.render();
+
+    // The filter spreads every sample over two neighbours on each side
+    let sample_left = placement.left - 2;
+    let sample_right = placement.left + placement.width as i32 + 2;
+    let left = sample_left.div_euclid(3);
+    let width = (sample_right + 2).div_euclid(3) - left;
+    let height = placement.height;
+    let sample = |row: &[u8], index: i32| {
+        let index = index - placement.left;
+        if index < 0 || index >= placement.width as i32 {
+            0
+        } else {
+            u32::from(row[index as usize])
+        }
+    };
+    let mut data = Vec::with_capacity(width as usize * height as usize);
+    for row in samples.chunks_exact(placement.width.max(1) as usize) {
+        for x in left..left + width {
+            let mut coverage = [0; 3];
+            for (channel, value) in coverage.iter_mut().enumerate() {
+                let center = x * 3 + channel as i32;
+                let filtered: u32 = LCD_FILTER
+                    .iter()
+                    .zip(center - 2..)
+                    .map(|(weight, index)| weight * sample(row, index))
+                    .sum();
+                *value = (filtered / 256) as u8;
+            }
+            if order == SubpixelOrder::Bgr {
+                coverage.reverse();
+            }
+            data.push(coverage);
+        }
+    }
+    if data.is_empty() {
+        return Some(SubpixelMask::default());
+    }
+    Some(SubpixelMask {
+        left,
+        top: placement.top,
+        width: width as u32,
+        height,
+        data,
+    })
+}
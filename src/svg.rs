@@ -0,0 +1,240 @@
+//! Export of text states to SVG documents.
+//!
+//! [`crate::TextState::to_svg`] writes the layout as a standalone SVG document, with glyphs as
+//! outlines or as positioned text, for visual regression artifacts and for moving text into
+//! vector design tools.
+
+use crate::glyph_path::{GlyphPath, PathCommand};
+use crate::math::{Point, Rect};
+use cosmic_text::Color;
+use std::fmt::Write;
+
+/// How glyphs are written to an SVG document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SvgGlyphs {
+    /// One `<path>` per glyph with its outline. The document looks the same everywhere, but the
+    /// text can't be edited or searched.
+    #[default]
+    Paths,
+    /// One `<text>` element per glyph cluster, positioned where the layout put it and naming
+    /// the font it was shaped with. The text stays editable, but is drawn with whatever font
+    /// the viewer finds under that name.
+    Text,
+}
+
+/// What [`crate::TextState::to_svg`] writes into the document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// How glyphs are written.
+    pub glyphs: SvgGlyphs,
+    /// Background color of the selection, `None` leaves the selection out.
+    pub selection: Option<Color>,
+    /// Color of the caret, `None` leaves the caret out. The caret is only written if the text
+    /// is editable.
+    pub caret: Option<Color>,
+    /// Write underlines, overlines and lines through the text.
+    pub decorations: bool,
+    /// Write the backgrounds of [`crate::TextState::highlights`].
+    pub highlights: bool,
+}
+
+impl SvgOptions {
+    /// Glyph outlines with decorations and highlights, without the selection and caret.
+    pub const DEFAULT: Self = Self {
+        glyphs: SvgGlyphs::Paths,
+        selection: None,
+        caret: None,
+        decorations: true,
+        highlights: true,
+    };
+
+    /// Sets how glyphs are written and returns the modified options.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{SvgGlyphs, SvgOptions};
+    /// use cosmic_text::Color;
+    ///
+    /// let options = SvgOptions::DEFAULT
+    ///     .with_glyphs(SvgGlyphs::Text)
+    ///     .with_selection(Color::rgba(51, 102, 204, 128));
+    /// assert_eq!(options.glyphs, SvgGlyphs::Text);
+    /// ```
+    pub const fn with_glyphs(mut self, glyphs: SvgGlyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Writes the selection background in the given color and returns the modified options.
+    pub const fn with_selection(mut self, color: Color) -> Self {
+        self.selection = Some(color);
+        self
+    }
+
+    /// Writes the caret in the given color and returns the modified options.
+    pub const fn with_caret(mut self, color: Color) -> Self {
+        self.caret = Some(color);
+        self
+    }
+
+    /// Sets whether decoration lines are written and returns the modified options.
+    pub const fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Sets whether highlights are written and returns the modified options.
+    pub const fn with_highlights(mut self, highlights: bool) -> Self {
+        self.highlights = highlights;
+        self
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A glyph cluster written as text, in LOGICAL pixels.
+pub(crate) struct SvgText<'a> {
+    pub text: &'a str,
+    /// Position of the glyph origin on the baseline.
+    pub position: Point,
+    pub rotated: bool,
+    pub font_family: &'a str,
+    pub font_size: f32,
+    pub font_weight: u16,
+    pub italic: bool,
+    pub color: Color,
+}
+
+/// Writes the elements of an SVG document.
+pub(crate) struct SvgWriter {
+    svg: String,
+}
+
+impl SvgWriter {
+    /// Starts a document of the given size in LOGICAL pixels.
+    pub fn new(width: f32, height: f32) -> Self {
+        let (width, height) = (number(width), number(height));
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">"
+        );
+        Self { svg }
+    }
+
+    pub fn rect(&mut self, rect: Rect, color: Color) {
+        let _ = writeln!(
+            self.svg,
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/>",
+            number(rect.min.x),
+            number(rect.min.y),
+            number(rect.max.x - rect.min.x),
+            number(rect.max.y - rect.min.y),
+            fill(color),
+        );
+    }
+
+    pub fn path(&mut self, path: &GlyphPath) {
+        let point = |point: Point| format!("{},{}", number(point.x), number(point.y));
+        let data: Vec<String> = path
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(to) => format!("M{}", point(to)),
+                PathCommand::LineTo(to) => format!("L{}", point(to)),
+                PathCommand::QuadTo(control, to) => format!("Q{} {}", point(control), point(to)),
+                PathCommand::CurveTo(control1, control2, to) => {
+                    format!("C{} {} {}", point(control1), point(control2), point(to))
+                }
+                PathCommand::Close => "Z".to_string(),
+            })
+            .collect();
+        let _ = writeln!(
+            self.svg,
+            "  <path d=\"{}\"{}/>",
+            data.join(" "),
+            fill(path.color)
+        );
+    }
+
+    pub fn text(&mut self, text: &SvgText) {
+        let (x, y) = (number(text.position.x), number(text.position.y));
+        let rotation = if text.rotated {
+            format!(" transform=\"rotate(90 {x} {y})\"")
+        } else {
+            String::new()
+        };
+        let style = if text.italic {
+            " font-style=\"italic\""
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            self.svg,
+            "  <text x=\"{x}\" y=\"{y}\" font-family=\"{}\" font-size=\"{}\" \
+             font-weight=\"{}\"{style}{rotation} xml:space=\"preserve\"{}>{}</text>",
+            escape(text.font_family),
+            number(text.font_size),
+            text.font_weight,
+            fill(text.color),
+            escape(text.text),
+        );
+    }
+
+    /// Ends the document and returns it.
+    pub fn finish(mut self) -> String {
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+}
+
+/// Formats a number with at most three decimals and without trailing zeros.
+fn number(value: f32) -> String {
+    let formatted = format!("{value:.3}");
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Fill attributes of the color, with the opacity only if it's translucent.
+fn fill(color: Color) -> String {
+    let mut attributes = format!(
+        " fill=\"#{:02x}{:02x}{:02x}\"",
+        color.r(),
+        color.g(),
+        color.b()
+    );
+    if color.a() < 255 {
+        let _ = write!(
+            attributes,
+            " fill-opacity=\"{}\"",
+            number(f32::from(color.a()) / 255.0)
+        );
+    }
+    attributes
+}
+
+/// Escapes text for XML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters aren't allowed in XML
+            character if character.is_control() => {}
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
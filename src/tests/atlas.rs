@@ -0,0 +1,57 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::WritingMode;
+use crate::tests::{editable_state, mono_style_test};
+use crate::{CpuGlyphAtlas, GlyphAtlas, Point, TextContext, TextState};
+
+/// Draws the quads of the state with the atlas and compares them to the state's own texture.
+fn assert_quads_match_texture(ctx: &mut TextContext, text_state: &mut TextState<()>) {
+    let mut atlas = CpuGlyphAtlas::new(256, 256);
+    let quads = text_state.glyph_quads(ctx, &mut atlas);
+    assert!(!quads.is_empty());
+
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    let texture = text_state.rasterized_texture();
+    let mut pixels = vec![0; texture.pixels.len()];
+    atlas.draw_quads(&quads, &mut pixels, texture.width, texture.height);
+    assert!(pixels == texture.pixels);
+}
+
+#[test]
+pub fn test_glyph_quads_match_rasterized_texture() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "a b c", (100.0, 30.0), &mono_style_test());
+    assert_quads_match_texture(&mut ctx, &mut text_state);
+
+    let mut text_state = editable_state(&mut ctx, "a\nb", (60.0, 60.0), &mono_style_test());
+    text_state.set_style(&mono_style_test().with_writing_mode(WritingMode::VerticalRl));
+    text_state.recalculate(&mut ctx);
+    let quads = text_state.glyph_quads(&mut ctx, &mut CpuGlyphAtlas::new(256, 256));
+    assert!(quads.iter().all(|quad| quad.rotated));
+    assert_quads_match_texture(&mut ctx, &mut text_state);
+}
+
+#[test]
+pub fn test_glyph_quads_share_atlas() {
+    let mut ctx = TextContext::default();
+    let first = editable_state(&mut ctx, "abab", (100.0, 30.0), &mono_style_test());
+    let second = editable_state(&mut ctx, "abab", (100.0, 30.0), &mono_style_test());
+
+    let mut atlas = CpuGlyphAtlas::new(256, 256);
+    let first_quads = first.glyph_quads(&mut ctx, &mut atlas);
+    let glyph_count = atlas.len();
+    assert!(glyph_count > 0 && glyph_count <= 4);
+    // The second state's glyphs are already in the atlas
+    let second_quads = second.glyph_quads(&mut ctx, &mut atlas);
+    assert_eq!(atlas.len(), glyph_count);
+    assert_eq!(first_quads, second_quads);
+    assert_eq!(
+        first_quads[0].glyph,
+        atlas.get(&first_quads[0].cache_key).unwrap()
+    );
+    assert_eq!(first_quads[0].clip.max, Point::new(100.0, 30.0));
+
+    // Glyphs that don't fit into the atlas are left out
+    let mut tiny_atlas = CpuGlyphAtlas::new(2, 2);
+    assert!(first.glyph_quads(&mut ctx, &mut tiny_atlas).is_empty());
+    assert!(tiny_atlas.is_empty());
+}
@@ -0,0 +1,109 @@
+use crate::style::TextDirection;
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, CursorMovement, Point, TextContext, TextState};
+
+fn caret_x(text_state: &TextState<()>) -> f32 {
+    text_state.caret_position_relative().unwrap().x
+}
+
+#[test]
+pub fn test_forced_paragraph_direction() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "abc",
+        (400.0, 25.0),
+        &mono_style_test().with_direction(TextDirection::Rtl),
+    );
+    assert_eq!(text_state.text(), "abc");
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+
+    // The text starts on the right, so moving forward moves the caret to the left
+    let start_x = caret_x(&text_state);
+    text_state.apply_action(&mut ctx, &Action::MoveCursorLeft);
+    assert_eq!(text_state.cursor_char_index(), Some(1));
+    assert!(caret_x(&text_state) < start_x);
+
+    // Moving back to the start doesn't stop at the direction mark
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+    assert!((caret_x(&text_state) - start_x).abs() < 0.5);
+
+    text_state.apply_action(&mut ctx, &Action::InsertChar("d".into()));
+    assert_eq!(text_state.text(), "dabc");
+
+    // Hebrew text is right-to-left on its own, but can be forced to be laid out left to right
+    let mut text_state = editable_state(
+        &mut ctx,
+        "אבג\nאבג",
+        (400.0, 25.0),
+        &mono_style_test().with_direction(TextDirection::Auto),
+    );
+    let rtl_start_x = caret_x(&text_state);
+    text_state.set_style(&mono_style_test().with_direction(TextDirection::Ltr));
+    text_state.recalculate(&mut ctx);
+    assert!(caret_x(&text_state) < rtl_start_x);
+    text_state.apply_action(&mut ctx, &Action::MoveCursorDown);
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+}
+
+#[test]
+pub fn test_visual_cursor_movement() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "ab אבג",
+        (400.0, 25.0),
+        &mono_style_test().with_direction(TextDirection::Ltr),
+    );
+    text_state.cursor_movement = CursorMovement::Visual;
+
+    let mut positions = vec![(text_state.cursor_char_index(), caret_x(&text_state))];
+    for _ in 0..7 {
+        text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+        positions.push((text_state.cursor_char_index(), caret_x(&text_state)));
+    }
+
+    // The caret moves right on every step, going backwards through the Hebrew word
+    let indices: Vec<_> = positions.iter().map(|(index, _)| index.unwrap()).collect();
+    assert_eq!(indices, vec![0, 1, 2, 3, 5, 4, 3, 3]);
+    for pair in positions[..7].windows(2) {
+        assert!(pair[1].1 > pair[0].1);
+    }
+
+    for _ in 0..6 {
+        text_state.apply_action(&mut ctx, &Action::MoveCursorLeft);
+    }
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+    assert!(caret_x(&text_state).abs() < 0.5);
+
+    // Logical movement goes through the text in order instead
+    text_state.cursor_movement = CursorMovement::Logical;
+    for _ in 0..4 {
+        text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    }
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+}
+
+#[test]
+pub fn test_split_caret_at_direction_boundary() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "ab אבג",
+        (400.0, 25.0),
+        &mono_style_test().with_direction(TextDirection::Auto),
+    );
+    let mono_width = text_state.first_glyph().map(|glyph| glyph.w).unwrap();
+    assert_eq!(text_state.split_caret_relative(), None);
+
+    // Clicking on the right half of the space puts the caret after it, where the Hebrew word
+    // starts on the other side of the word
+    text_state.handle_press(&mut ctx, Point::new(mono_width * 2.75, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(3));
+    let split = text_state.split_caret_relative().unwrap();
+    assert!((split.primary.x - mono_width * 3.0).abs() < 1.0);
+    assert!(split.secondary.x > split.primary.x + mono_width);
+    assert_eq!(split.primary.y, split.secondary.y);
+    assert_eq!(text_state.caret_position_relative(), Some(split.primary));
+}
@@ -0,0 +1,91 @@
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, CaretShape, DrawCommand, TextContext};
+use std::time::{Duration, Instant};
+
+#[test]
+pub fn test_block_and_underline_carets_cover_the_next_grapheme() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "ab", (100.0, 30.0), &mono_style_test());
+    let line_height = text_state.style().line_height_pt();
+    let start = text_state.caret_position_relative().unwrap();
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    // Caret positions are rounded to whole pixels
+    let advance = text_state.caret_position_relative().unwrap().x - start.x;
+    text_state.apply_action(&mut ctx, &Action::MoveCursorLeft);
+
+    text_state.caret.shape = CaretShape::Block;
+    let [block] = text_state.caret_rects()[..] else {
+        panic!("expected a single block");
+    };
+    assert_eq!(block.min, start);
+    assert!((block.width() - advance).abs() < 1.0);
+    assert!((block.height() - line_height).abs() < 0.01);
+
+    text_state.caret.shape = CaretShape::Underline;
+    let [underline] = text_state.caret_rects()[..] else {
+        panic!("expected a single underline");
+    };
+    assert!((underline.width() - block.width()).abs() < 0.01);
+    assert!((underline.height() - text_state.caret_width()).abs() < 0.01);
+    assert!((underline.max.y - block.max.y).abs() < 0.01);
+
+    // At the end of the line the block is as wide as half of the font size
+    text_state.caret.shape = CaretShape::Block;
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    let [block] = text_state.caret_rects()[..] else {
+        panic!("expected a single block");
+    };
+    assert_eq!(block.min, text_state.caret_position_relative().unwrap());
+    assert!((block.width() - 7.0).abs() < 0.01);
+
+    // The display list draws the same geometry
+    let commands = text_state.display_list(&mut ctx);
+    assert!(commands
+        .iter()
+        .any(|command| matches!(command, DrawCommand::Caret { rect } if *rect == block)));
+}
+
+#[test]
+pub fn test_overwrite_mode_uses_the_overwrite_shape() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "ab", (100.0, 30.0), &mono_style_test());
+    let bar = text_state.caret_rects();
+    assert!((bar[0].width() - text_state.caret_width()).abs() < 0.01);
+
+    text_state.set_overwrite_mode(true);
+    assert_eq!(text_state.caret_shape(), CaretShape::Block);
+    assert!(text_state.caret_rects()[0].width() > text_state.caret_width());
+
+    text_state.caret.overwrite_shape = CaretShape::Underline;
+    assert_eq!(text_state.caret_shape(), CaretShape::Underline);
+    text_state.set_overwrite_mode(false);
+    assert_eq!(text_state.caret_rects(), bar);
+}
+
+#[test]
+pub fn test_caret_blink_restarts_visible_when_typing() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "ab", (100.0, 30.0), &mono_style_test());
+    let now = Instant::now();
+    assert!(text_state.caret_visible(now + Duration::from_secs(5)));
+
+    let interval = Duration::from_millis(200);
+    text_state.caret.blink_interval = Some(interval);
+    assert!(text_state.caret_visible(Instant::now()));
+
+    // Wait for the hidden phase of the blink
+    let deadline = Instant::now() + interval * 10;
+    while text_state.caret_visible(Instant::now()) {
+        assert!(Instant::now() < deadline);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    text_state.apply_action(&mut ctx, &Action::InsertChar("x".into()));
+    let typed = Instant::now();
+    assert!(text_state.caret_visible(typed));
+    assert!(!text_state.caret_visible(typed + interval + interval / 2));
+
+    text_state.is_editable = false;
+    assert!(!text_state.caret_visible(typed));
+}
@@ -0,0 +1,164 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::{FontColor, FontFamily};
+use crate::tests::mono_style_test;
+use crate::{ColorGlyphs, Point, TextContext, TextState};
+use cosmic_text::{CacheKey, Color};
+
+/// Bitmap (CBDT) emoji for the digits, `#`, `*`, © and ®.
+const EMOJI_FONT: &[u8] = include_bytes!("fonts/NotoColorEmoji-Partial.ttf");
+/// COLRv1 test glyphs in the private use area from U+F0100.
+const COLR_FONT: &[u8] = include_bytes!("fonts/colr_1.ttf");
+
+fn color_glyph_state(
+    ctx: &mut TextContext,
+    family: &'static str,
+    text: &str,
+    color: Color,
+) -> TextState<()> {
+    ctx.font_system.db_mut().load_font_data(EMOJI_FONT.to_vec());
+    ctx.font_system.db_mut().load_font_data(COLR_FONT.to_vec());
+    let mut style = mono_style_test()
+        .with_font_size(32.0)
+        .with_font_color(FontColor(color));
+    style.font_family = FontFamily::new(family);
+    let mut text_state = TextState::new_with_text(text, &mut ctx.font_system, ());
+    text_state.set_style(&style);
+    text_state.set_outer_size(&Point::from((120.0, 40.0)));
+    text_state.recalculate(ctx);
+    text_state
+}
+
+fn first_cache_key(text_state: &TextState<()>) -> CacheKey {
+    let run = text_state.buffer().layout_runs().next().unwrap();
+    run.glyphs[0].physical((0.0, 0.0), 1.0).cache_key
+}
+
+fn rasterized_pixels(
+    text_state: &mut TextState<()>,
+    ctx: &mut TextContext,
+    alpha_mode: AlphaMode,
+) -> Vec<[u8; 4]> {
+    assert!(text_state.rasterize_into_texture(ctx, alpha_mode, PixelFormat::Rgba8));
+    text_state
+        .rasterized_texture()
+        .pixels
+        .chunks_exact(4)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+        .collect()
+}
+
+#[test]
+pub fn test_bitmap_emoji_keep_their_colors_in_both_alpha_modes() {
+    let mut ctx = TextContext::default();
+    let blue = Color::rgb(0, 0, 255);
+    let mut text_state = color_glyph_state(&mut ctx, "Noto Color Emoji", "#0", blue);
+
+    // The keycaps are gray in the font, they aren't tinted with the text color
+    let unmultiplied = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Unmultiplied);
+    let painted: Vec<_> = unmultiplied.iter().filter(|pixel| pixel[3] > 0).collect();
+    assert!(painted.len() > 100);
+    assert!(painted
+        .iter()
+        .all(|pixel| pixel[0].abs_diff(pixel[2]) < 40 && pixel[1].abs_diff(pixel[2]) < 40));
+
+    // Premultiplying keeps the alpha and the colors of opaque pixels, and darkens the rest
+    let premultiplied = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Premultiplied);
+    for (straight, premultiplied) in unmultiplied.iter().zip(&premultiplied) {
+        assert_eq!(straight[3], premultiplied[3]);
+        if straight[3] == 255 {
+            assert_eq!(straight, premultiplied);
+        }
+        for channel in 0..3 {
+            assert!(premultiplied[channel] <= straight[channel]);
+        }
+    }
+}
+
+#[test]
+pub fn test_monochrome_emoji_are_drawn_in_the_text_color() {
+    let mut ctx = TextContext::default();
+    let blue = Color::rgb(0, 0, 255);
+    let mut text_state = color_glyph_state(&mut ctx, "Noto Color Emoji", "#0", blue);
+    let colored = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Unmultiplied);
+
+    // Switching to monochrome redraws the whole texture
+    text_state.color_glyphs = ColorGlyphs::Monochrome;
+    let monochrome = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Unmultiplied);
+    let texture = text_state.rasterized_texture();
+    assert_eq!(texture.dirty_rect.width, texture.width);
+
+    // The alpha of the emoji is used as coverage
+    for (colored, monochrome) in colored.iter().zip(&monochrome) {
+        assert_eq!(colored[3], monochrome[3]);
+        if monochrome[3] > 0 {
+            assert_eq!(monochrome[..3], [0, 0, 255]);
+        }
+    }
+}
+
+#[test]
+pub fn test_colr_v1_glyphs_are_painted_with_gradients() {
+    let mut ctx = TextContext::default();
+    let black = Color::rgb(0, 0, 0);
+    let mut text_state =
+        color_glyph_state(&mut ctx, "COLRv1 Static Test Glyphs", "\u{F0100}", black);
+    let pixels = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Unmultiplied);
+
+    // A linear gradient from blue to red, not the outline filled with the text color
+    let opaque: Vec<_> = pixels.iter().filter(|pixel| pixel[3] == 255).collect();
+    assert!(opaque.len() > 100);
+    assert!(opaque.iter().any(|pixel| pixel[2] > 200 && pixel[0] < 50));
+    assert!(opaque.iter().any(|pixel| pixel[0] > 200 && pixel[2] < 50));
+
+    // The alpha of the text color fades color glyphs
+    let translucent = Color::rgba(0, 0, 0, 128);
+    let mut text_state = color_glyph_state(
+        &mut ctx,
+        "COLRv1 Static Test Glyphs",
+        "\u{F0100}",
+        translucent,
+    );
+    let faded = rasterized_pixels(&mut text_state, &mut ctx, AlphaMode::Unmultiplied);
+    assert!(faded.iter().all(|pixel| pixel[3] <= 128));
+    assert!(faded.iter().any(|pixel| pixel[3] == 128));
+}
+
+#[test]
+pub fn test_only_glyphs_in_the_foreground_color_are_cached_per_color() {
+    let mut ctx = TextContext::default();
+    let red = Color::rgb(255, 0, 0);
+    let blue = Color::rgb(0, 0, 255);
+    let image = |ctx: &mut TextContext, cache_key: CacheKey, color: Color| {
+        ctx.color_glyph_cache
+            .get(&mut ctx.font_system, &mut ctx.swash_cache, cache_key, color)
+            .map(|image| (image as *const _, image.data.clone()))
+    };
+
+    // The gradient glyph has no foreground layers, one image serves every text color
+    let text_state = color_glyph_state(&mut ctx, "COLRv1 Static Test Glyphs", "\u{F0100}", red);
+    let gradient = first_cache_key(&text_state);
+    let (red_image, _) = image(&mut ctx, gradient, red).unwrap();
+    let (blue_image, _) = image(&mut ctx, gradient, blue).unwrap();
+    assert_eq!(red_image, blue_image);
+
+    // The layers of this glyph are painted in the text color
+    let text_state = color_glyph_state(&mut ctx, "COLRv1 Static Test Glyphs", "\u{F0B00}", red);
+    let foreground = first_cache_key(&text_state);
+    let (_, red_pixels) = image(&mut ctx, foreground, red).unwrap();
+    let (_, blue_pixels) = image(&mut ctx, foreground, blue).unwrap();
+    assert_ne!(red_pixels, blue_pixels);
+    for gray in 0..32 {
+        image(&mut ctx, foreground, Color::rgb(gray, gray, gray));
+    }
+    assert_eq!(image(&mut ctx, foreground, red).unwrap().1, red_pixels);
+
+    // Glyphs without color are cached once too
+    let text_state = color_glyph_state(&mut ctx, "Noto Color Emoji", "a", red);
+    let outline = first_cache_key(&text_state);
+    assert!(image(&mut ctx, outline, red).is_none());
+    assert!(image(&mut ctx, outline, blue).is_none());
+    assert_eq!(ctx.color_glyph_cache.len(), 3);
+
+    ctx.color_glyph_cache.clear();
+    assert!(ctx.color_glyph_cache.is_empty());
+}
@@ -0,0 +1,91 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, DirtyRect, Id, Point, TextContext, TextManager};
+
+#[test]
+pub fn test_only_changed_lines_are_rasterized() {
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "abc\ndef\nghi",
+        (200.0, 100.0),
+        &mono_style_test(),
+    );
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert_eq!(
+        text_state.rasterized_texture().dirty_rect,
+        DirtyRect::full(200, 100)
+    );
+
+    // Nothing visible changed
+    text_state.set_text("abc\ndef\nghi");
+    text_state.recalculate(&mut ctx);
+    assert!(!text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert!(text_state.rasterized_texture().dirty_rect.is_empty());
+
+    // Typing on the second line only damages the second line
+    text_state.handle_press(&mut ctx, Point::new(200.0, line_height * 1.5));
+    text_state.apply_action(&mut ctx, &Action::InsertChar("x".into()));
+    assert_eq!(text_state.text(), "abc\ndefx\nghi");
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let dirty_rect = text_state.rasterized_texture().dirty_rect;
+    assert!(!dirty_rect.is_empty());
+    assert!(dirty_rect.y as f32 >= line_height - 2.0);
+    assert!((dirty_rect.y + dirty_rect.height) as f32 <= line_height * 2.0 + 2.0);
+    assert!(dirty_rect.width < 200);
+
+    // The partially updated texture is the same as a full rasterization
+    let mut fresh = editable_state(
+        &mut ctx,
+        "abc\ndefx\nghi",
+        (200.0, 100.0),
+        &mono_style_test(),
+    );
+    assert!(fresh.rasterize_into_texture(&mut ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    assert!(fresh.rasterized_texture().pixels == text_state.rasterized_texture().pixels);
+
+    // Switching the alpha mode redraws everything
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Premultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert_eq!(
+        text_state.rasterized_texture().dirty_rect,
+        DirtyRect::full(200, 100)
+    );
+}
+
+#[test]
+pub fn test_rasterize_all_textures_reports_dirty_rect() {
+    let mut manager: TextManager<()> = TextManager::new();
+    let id = Id::new("damage");
+    manager.create_state(id, "abc", ());
+    let state = manager.text_states.get_mut(&id).unwrap();
+    state.set_style(&mono_style_test());
+    state.set_outer_size(&Point::from((100.0, 50.0)));
+
+    let changes = manager.rasterize_all_textures(AlphaMode::Unmultiplied);
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].resized);
+    assert_eq!(changes[0].dirty_rect, DirtyRect::full(100, 50));
+
+    manager.text_states.get_mut(&id).unwrap().set_text("abd");
+    let changes = manager.rasterize_all_textures(AlphaMode::Unmultiplied);
+    assert_eq!(changes.len(), 1);
+    assert!(!changes[0].resized);
+    assert!(changes[0].dirty_rect.width < 100);
+}
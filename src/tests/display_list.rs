@@ -0,0 +1,129 @@
+use crate::style::TextDecoration;
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, DrawCommand, Highlight, Point, Rect, TextContext};
+use cosmic_text::Color;
+
+fn glyph_positions(commands: &[DrawCommand]) -> Vec<Point> {
+    commands
+        .iter()
+        .filter_map(|command| match command {
+            DrawCommand::GlyphRun { glyphs } => Some(glyphs),
+            _ => None,
+        })
+        .flatten()
+        .map(|glyph| glyph.position)
+        .collect()
+}
+
+fn rects(commands: &[DrawCommand], predicate: impl Fn(&DrawCommand) -> Option<Rect>) -> Vec<Rect> {
+    commands.iter().filter_map(predicate).collect()
+}
+
+#[test]
+pub fn test_display_list_glyphs_selection_and_caret() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "ab\ncd", (100.0, 100.0), &mono_style_test());
+    let mono_width = text_state.first_glyph().unwrap().w;
+    let line_height = mono_style_test().line_height_pt();
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let commands = text_state.display_list(&mut ctx);
+
+    // One glyph run per line, with the glyph origins on the baselines
+    let runs = commands
+        .iter()
+        .filter(|command| matches!(command, DrawCommand::GlyphRun { .. }))
+        .count();
+    assert_eq!(runs, 2);
+    let positions = glyph_positions(&commands);
+    assert_eq!(positions.len(), 4);
+    assert!(positions[0].x.abs() < 1.0);
+    assert!((positions[1].x - mono_width).abs() < 1.0);
+    assert!(positions[0].y > 0.0 && positions[0].y < line_height);
+    assert!((positions[2].y - positions[0].y - line_height).abs() < 1.0);
+
+    let selection = rects(&commands, |command| match command {
+        DrawCommand::Selection { rect } => Some(*rect),
+        _ => None,
+    });
+    let expected: Vec<Rect> = text_state
+        .selection()
+        .lines()
+        .iter()
+        .filter_map(|line| line.rect())
+        .collect();
+    assert_eq!(selection.len(), 2);
+    assert_eq!(selection, expected);
+
+    // The caret is painted last
+    let Some(DrawCommand::Caret { rect }) = commands.last() else {
+        panic!("expected a caret");
+    };
+    assert_eq!(rect.min, text_state.caret_position_relative().unwrap());
+    assert!((rect.width() - text_state.caret_width()).abs() < 0.01);
+    assert!((rect.height() - line_height).abs() < 0.01);
+
+    text_state.is_editable = false;
+    let commands = text_state.display_list(&mut ctx);
+    assert!(!commands
+        .iter()
+        .any(|command| matches!(command, DrawCommand::Caret { .. })));
+}
+
+#[test]
+pub fn test_display_list_highlights_and_decorations() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "abc", (100.0, 100.0), &mono_style_test());
+    let mono_width = text_state.first_glyph().unwrap().w;
+    let line_height = mono_style_test().line_height_pt();
+    let color = Color::rgb(255, 255, 0);
+
+    text_state.set_highlights(vec![Highlight::new(1..2, color)]);
+    text_state.set_style(
+        &mono_style_test().with_decoration(TextDecoration::UNDERLINE.with_line_through(true)),
+    );
+    text_state.recalculate(&mut ctx);
+    let commands = text_state.display_list(&mut ctx);
+    let DrawCommand::Highlight {
+        rect,
+        color: highlight_color,
+    } = commands[0]
+    else {
+        panic!("expected a highlight first");
+    };
+    assert_eq!(highlight_color, color);
+    assert!((rect.min.x - mono_width).abs() < 1.0);
+    assert!((rect.max.x - mono_width * 2.0).abs() < 1.0);
+    assert!((rect.height() - line_height).abs() < 0.01);
+
+    let baseline = glyph_positions(&commands)[0].y;
+    let decorations = rects(&commands, |command| match command {
+        DrawCommand::Decoration { rect, .. } => Some(*rect),
+        _ => None,
+    });
+    assert_eq!(decorations.len(), 2);
+    let (underline, line_through) = (decorations[0], decorations[1]);
+    assert!(underline.min.y >= baseline && underline.max.y <= line_height);
+    assert!(line_through.max.y < baseline && line_through.min.y > 0.0);
+    assert!(underline.min.x.abs() < 1.0);
+    assert!((underline.max.x - mono_width * 3.0).abs() < 1.0);
+}
+
+#[test]
+pub fn test_display_list_applies_horizontal_scroll() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        &"a".repeat(40),
+        (100.0, 100.0),
+        &mono_style_test(),
+    );
+    let before = glyph_positions(&text_state.display_list(&mut ctx));
+
+    text_state.set_absolute_scroll(Point::new(20.0, 0.0));
+    text_state.recalculate(&mut ctx);
+    let after = glyph_positions(&text_state.display_list(&mut ctx));
+    assert_eq!(before.len(), after.len());
+    assert!((before[5].x - after[5].x - 20.0).abs() < 1.0);
+    assert_eq!(before[5].y, after[5].y);
+}
@@ -0,0 +1,142 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::tests::mono_style_test;
+use crate::{
+    CpuGlyphAtlas, DistanceField, DistanceFieldKind, GlyphAtlas, Point, TextContext, TextState,
+};
+use cosmic_text::Color;
+
+fn text_state(ctx: &mut TextContext) -> TextState<()> {
+    let mut text_state = TextState::new_with_text("Hello MW", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((100.0, 20.0)));
+    text_state.recalculate(ctx);
+    text_state
+}
+
+fn rasterize(ctx: &mut TextContext, distance_field: Option<DistanceField>) -> Vec<u8> {
+    let mut text_state = text_state(ctx);
+    text_state.distance_field = distance_field;
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    text_state.rasterized_texture().pixels.clone()
+}
+
+/// Value of the field that is thresholded at 0.5 to find the glyph edge.
+fn field_value(field: DistanceField, pixel: &[u8]) -> u8 {
+    match field.kind {
+        DistanceFieldKind::Sdf => pixel[3],
+        DistanceFieldKind::Msdf => {
+            let (r, g, b) = (pixel[0], pixel[1], pixel[2]);
+            r.min(g).max(r.max(g).min(b))
+        }
+    }
+}
+
+#[test]
+pub fn test_distance_fields_agree_with_coverage() {
+    let mut ctx = TextContext::default();
+    let coverage = rasterize(&mut ctx, None);
+    for field in [DistanceField::sdf(4), DistanceField::msdf(4)] {
+        let pixels = rasterize(&mut ctx, Some(field));
+        assert_eq!(pixels.len(), coverage.len());
+        // Pixels fully inside of a glyph are inside of the field, and pixels fully outside of
+        // every glyph are outside of it
+        let mut inside = 0;
+        for (pixel, coverage) in pixels.chunks(4).zip(coverage.chunks(4)) {
+            let value = field_value(field, pixel);
+            if coverage[3] == 255 {
+                assert!(value > 128, "{field:?}");
+                inside += 1;
+            } else if coverage[3] == 0 {
+                assert!(value < 128, "{field:?}");
+            }
+        }
+        assert!(inside > 0);
+        // The field reaches past the glyph edges
+        assert!(pixels
+            .chunks(4)
+            .zip(coverage.chunks(4))
+            .any(|(pixel, coverage)| coverage[3] == 0 && pixel[3] > 0));
+    }
+}
+
+#[test]
+pub fn test_distance_field_quads_draw_like_coverage_quads() {
+    let mut ctx = TextContext::default();
+    let text_state = text_state(&mut ctx);
+    let (width, height) = (100, 20);
+
+    let mut atlas = CpuGlyphAtlas::new(512, 512);
+    let quads = text_state.glyph_quads(&mut ctx, &mut atlas);
+    let mut coverage = vec![0; width * height * 4];
+    atlas.draw_quads(&quads, &mut coverage, width as u32, height as u32);
+
+    for field in [DistanceField::sdf(3), DistanceField::msdf(3)] {
+        let mut atlas = CpuGlyphAtlas::new(512, 512);
+        let quads = text_state.distance_field_quads(&mut ctx, &mut atlas, field);
+        assert_eq!(quads.len(), 7);
+        let glyph = atlas.get(&quads[0].cache_key).unwrap();
+        // Bitmaps are padded by the spread on every side
+        let image = ctx
+            .swash_cache
+            .get_image(&mut ctx.font_system, quads[0].cache_key)
+            .clone()
+            .unwrap();
+        assert!(glyph.width >= image.placement.width + 2 * field.spread);
+        assert!(glyph.height >= image.placement.height + 2 * field.spread);
+
+        let mut pixels = vec![0; width * height * 4];
+        atlas.draw_quads(&quads, &mut pixels, width as u32, height as u32);
+        for (pixel, coverage) in pixels.chunks(4).zip(coverage.chunks(4)) {
+            if coverage[3] == 255 {
+                assert!(pixel[3] > 128, "{field:?}");
+            } else if coverage[3] == 0 {
+                assert!(pixel[3] < 128, "{field:?}");
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_changing_distance_field_redraws_texture() {
+    let mut ctx = TextContext::default();
+    let mut text_state = text_state(&mut ctx);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    text_state.distance_field = Some(DistanceField::sdf(4));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert_eq!(
+        text_state.rasterized_texture().dirty_rect,
+        crate::DirtyRect::full(100, 20)
+    );
+    assert!(!text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+}
+
+#[test]
+pub fn test_background_isnt_filled_under_distance_fields() {
+    let mut ctx = TextContext::default();
+    let field = DistanceField::sdf(4);
+    let expected = rasterize(&mut ctx, Some(field));
+
+    let mut text_state = text_state(&mut ctx);
+    text_state.distance_field = Some(field);
+    text_state.background = Some(Color::rgb(255, 255, 255));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let pixels = &text_state.rasterized_texture().pixels;
+    assert_eq!(pixels, &expected);
+    assert!(pixels.chunks_exact(4).any(|pixel| pixel[3] == 0));
+}
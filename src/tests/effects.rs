@@ -0,0 +1,89 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::{TextEffects, TextOutline, TextShadow};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, Point, TextContext, TextState};
+use cosmic_text::Color;
+
+fn effects_state(ctx: &mut TextContext, text: &str, effects: TextEffects) -> TextState<()> {
+    let style = mono_style_test().with_effects(effects);
+    let mut text_state = editable_state(ctx, text, (80.0, 50.0), &style);
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    text_state
+}
+
+fn alpha(text_state: &TextState<()>, x: i32, y: i32) -> u8 {
+    let texture = text_state.rasterized_texture();
+    let (x, y) = (x - texture.offset_x, y - texture.offset_y);
+    if x < 0 || y < 0 || x >= texture.width as i32 || y >= texture.height as i32 {
+        return 0;
+    }
+    texture.pixels[(y as usize * texture.width as usize + x as usize) * 4 + 3]
+}
+
+#[test]
+pub fn test_outline_grows_texture() {
+    let mut ctx = TextContext::default();
+    let plain = effects_state(&mut ctx, "Hi", TextEffects::NONE);
+    let texture = plain.rasterized_texture();
+    assert_eq!((texture.width, texture.height), (80, 50));
+    assert_eq!((texture.offset_x, texture.offset_y), (0, 0));
+
+    let outline = TextOutline::new(2.0, Color::rgb(255, 0, 0));
+    let outlined = effects_state(&mut ctx, "Hi", TextEffects::NONE.with_outline(outline));
+    let texture = outlined.rasterized_texture();
+    assert_eq!((texture.width, texture.height), (86, 56));
+    assert_eq!((texture.offset_x, texture.offset_y), (-3, -3));
+
+    // The outline surrounds the glyphs in its own color
+    let red = texture
+        .pixels
+        .chunks(4)
+        .filter(|pixel| *pixel == [255, 0, 0, 255])
+        .count();
+    assert!(red > 0);
+    for y in 0..50 {
+        for x in 0..80 {
+            assert!(alpha(&outlined, x, y) >= alpha(&plain, x, y));
+        }
+    }
+}
+
+#[test]
+pub fn test_shadow_is_offset() {
+    let mut ctx = TextContext::default();
+    let plain = effects_state(&mut ctx, "Hi", TextEffects::NONE);
+    let shadow = TextShadow::new(4.0, 3.0, 0.0, Color::rgb(0, 0, 255));
+    let shadowed = effects_state(&mut ctx, "Hi", TextEffects::NONE.with_shadow(shadow));
+    let texture = shadowed.rasterized_texture();
+    // The shadow only reaches out of the right and bottom edges
+    assert_eq!((texture.width, texture.height), (84, 53));
+    assert_eq!((texture.offset_x, texture.offset_y), (0, 0));
+    for y in 0..50 {
+        for x in 0..80 {
+            assert!(alpha(&shadowed, x + 4, y + 3) >= alpha(&plain, x, y));
+        }
+    }
+}
+
+#[test]
+pub fn test_effects_are_redrawn_around_damaged_lines() {
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let effects = TextEffects::NONE
+        .with_outline(TextOutline::new(1.0, Color::rgb(255, 0, 0)))
+        .with_shadow(TextShadow::glow(3.0, Color::rgba(0, 0, 255, 200)));
+    let mut text_state = effects_state(&mut ctx, "abc\ndef\nghi", effects);
+    text_state.handle_press(&mut ctx, Point::new(80.0, line_height * 1.5));
+    text_state.apply_action(&mut ctx, &Action::InsertChar("x".into()));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let texture = text_state.rasterized_texture();
+    assert!(texture.dirty_rect.width < texture.width || texture.dirty_rect.height < texture.height);
+
+    // Drawing only the damaged area gives the same pixels as drawing everything
+    let full = effects_state(&mut ctx, "abc\ndefx\nghi", effects);
+    assert_eq!(texture.pixels, full.rasterized_texture().pixels);
+}
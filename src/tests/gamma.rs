@@ -0,0 +1,77 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::FontColor;
+use crate::tests::mono_style_test;
+use crate::{GammaCorrection, Point, TextContext, TextState};
+use cosmic_text::Color;
+
+fn rasterized_alpha(ctx: &mut TextContext, color: Color) -> Vec<u8> {
+    let mut text_state = TextState::new_with_text("Thin text", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test().with_font_color(FontColor(color)));
+    text_state.set_outer_size(&Point::from((100.0, 20.0)));
+    text_state.recalculate(ctx);
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    text_state
+        .rasterized_texture()
+        .pixels
+        .chunks_exact(4)
+        .map(|pixel| pixel[3])
+        .collect()
+}
+
+#[test]
+pub fn test_gamma_makes_light_text_bolder_and_dark_text_lighter() {
+    let mut ctx = TextContext::default();
+    let white = Color::rgb(255, 255, 255);
+    let black = Color::rgb(0, 0, 0);
+    let white_before = rasterized_alpha(&mut ctx, white);
+    let black_before = rasterized_alpha(&mut ctx, black);
+    assert_eq!(white_before, black_before);
+
+    ctx.gamma_correction = GammaCorrection::NONE.with_gamma(2.0);
+    let white_after = rasterized_alpha(&mut ctx, white);
+    let black_after = rasterized_alpha(&mut ctx, black);
+    for ((before, white), black) in white_before.iter().zip(&white_after).zip(&black_after) {
+        assert!(white >= before);
+        assert!(black <= before);
+        // Empty and fully covered pixels don't change
+        if *before == 0 || *before == 255 {
+            assert_eq!((white, black), (before, before));
+        }
+    }
+    assert_ne!(white_after, white_before);
+    assert_ne!(black_after, black_before);
+}
+
+#[test]
+pub fn test_contrast_darkens_partial_coverage_and_redraws_texture() {
+    let mut ctx = TextContext::default();
+    let black = Color::rgb(0, 0, 0);
+    let mut text_state = TextState::new_with_text("Contrast", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test().with_font_color(FontColor(black)));
+    text_state.set_outer_size(&Point::from((100.0, 20.0)));
+    text_state.recalculate(&mut ctx);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let total = |text_state: &TextState<()>| -> u32 {
+        let pixels = &text_state.rasterized_texture().pixels;
+        pixels
+            .chunks_exact(4)
+            .map(|pixel| u32::from(pixel[3]))
+            .sum()
+    };
+    let before = total(&text_state);
+
+    // Changing the correction redraws the whole texture even though the layout didn't change
+    ctx.gamma_correction = GammaCorrection::new(1.0, 1.0);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let texture = text_state.rasterized_texture();
+    assert_eq!(texture.dirty_rect.width, texture.width);
+    assert!(total(&text_state) > before);
+}
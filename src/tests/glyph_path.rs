@@ -0,0 +1,110 @@
+use crate::style::{LetterSpacing, TextStyle};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{DrawCommand, GlyphPath, PathCommand, Point, Rect, TextContext};
+
+fn bounds(paths: &[GlyphPath]) -> Vec<Rect> {
+    paths.iter().map(|path| path.bounds().unwrap()).collect()
+}
+
+#[test]
+pub fn test_glyph_paths_cover_glyph_bitmaps() {
+    let mut ctx = TextContext::default();
+    let text_state = editable_state(&mut ctx, "Hi gW", (100.0, 40.0), &mono_style_test());
+    let paths = text_state.glyph_paths(&mut ctx);
+    assert_eq!(paths.len(), 4);
+
+    let glyphs: Vec<_> = text_state
+        .display_list(&mut ctx)
+        .into_iter()
+        .filter_map(|command| match command {
+            DrawCommand::GlyphRun { glyphs } => Some(glyphs),
+            _ => None,
+        })
+        .flatten()
+        .filter(|glyph| {
+            ctx.swash_cache
+                .get_image(&mut ctx.font_system, glyph.cache_key)
+                .as_ref()
+                .is_some_and(|image| image.placement.height > 0)
+        })
+        .collect();
+    assert_eq!(glyphs.len(), paths.len());
+    for (path, glyph) in paths.iter().zip(&glyphs) {
+        assert_eq!(path.cache_key, glyph.cache_key);
+        assert!(matches!(path.commands[0], PathCommand::MoveTo(_)));
+        // The outline is within a pixel of the bitmap rasterized from it
+        let placement = ctx
+            .swash_cache
+            .get_image(&mut ctx.font_system, glyph.cache_key)
+            .as_ref()
+            .unwrap()
+            .placement;
+        let left = glyph.position.x + placement.left as f32;
+        let top = glyph.position.y - placement.top as f32;
+        let bounds = path.bounds().unwrap();
+        assert!(bounds.min.x >= left - 1.0 && bounds.min.x <= left + 1.0);
+        assert!(bounds.min.y >= top - 1.0 && bounds.min.y <= top + 1.0);
+        let right = left + placement.width as f32;
+        let bottom = top + placement.height as f32;
+        assert!(bounds.max.x >= right - 1.0 && bounds.max.x <= right + 1.0);
+        assert!(bounds.max.y >= bottom - 1.0 && bounds.max.y <= bottom + 1.0);
+    }
+}
+
+#[test]
+pub fn test_glyph_paths_follow_scroll_and_letter_spacing() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "abc", (100.0, 40.0), &mono_style_test());
+    let original = bounds(&text_state.glyph_paths(&mut ctx));
+
+    text_state.set_style(&TextStyle {
+        // In ems, 3.5 pixels at 14 pixels
+        letter_spacing: Some(LetterSpacing(0.25)),
+        ..mono_style_test()
+    });
+    text_state.recalculate(&mut ctx);
+    let spaced = bounds(&text_state.glyph_paths(&mut ctx));
+    for (index, (spaced, original)) in spaced.iter().zip(&original).enumerate() {
+        let expected = original.min.x + 3.5 * index as f32;
+        assert!(
+            (spaced.min.x - expected).abs() < 1.0,
+            "{spaced:?} {original:?}"
+        );
+    }
+
+    let mut text_state = editable_state(
+        &mut ctx,
+        &"abc ".repeat(20),
+        (100.0, 40.0),
+        &mono_style_test(),
+    );
+    let unscrolled = bounds(&text_state.glyph_paths(&mut ctx));
+    text_state.set_absolute_scroll(Point::new(10.0, 0.0));
+    text_state.recalculate(&mut ctx);
+    let scrolled = bounds(&text_state.glyph_paths(&mut ctx));
+    let shift = unscrolled[0].min.x - scrolled[0].min.x;
+    assert!((shift - 10.0).abs() < 1.0, "{shift}");
+}
+
+#[test]
+pub fn test_glyph_paths_are_in_logical_pixels() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "Hello", (100.0, 40.0), &mono_style_test());
+    let logical = bounds(&text_state.glyph_paths(&mut ctx));
+
+    ctx.scale_factor = 2.0;
+    text_state.set_scale_factor(2.0);
+    text_state.recalculate(&mut ctx);
+    let scaled = bounds(&text_state.glyph_paths(&mut ctx));
+    assert_eq!(scaled.len(), logical.len());
+    for (scaled, logical) in scaled.iter().zip(&logical) {
+        assert!(
+            (scaled.min.x - logical.min.x).abs() < 1.0,
+            "{scaled:?} {logical:?}"
+        );
+        assert!(
+            (scaled.max.y - logical.max.y).abs() < 1.0,
+            "{scaled:?} {logical:?}"
+        );
+    }
+}
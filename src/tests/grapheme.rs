@@ -0,0 +1,98 @@
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, Backspace, CursorMovement, Point, TextContext, TextState};
+use unicode_segmentation::UnicodeSegmentation;
+
+const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+const FLAG: &str = "\u{1F1EF}\u{1F1F5}";
+const COMBINING: &str = "e\u{301}\u{323}";
+
+fn move_to_end(text_state: &mut TextState<()>, ctx: &mut TextContext) {
+    for _ in 0..text_state.text_char_len() {
+        text_state.apply_action(ctx, &Action::MoveCursorRight);
+    }
+}
+
+#[test]
+pub fn test_backspace_removes_whole_grapheme_clusters() {
+    let mut ctx = TextContext::default();
+    let text = format!("a{FAMILY}{FLAG}{COMBINING}");
+    let mut text_state = editable_state(&mut ctx, &text, (400.0, 30.0), &mono_style_test());
+    assert_eq!(text_state.text_char_len(), 4);
+    move_to_end(&mut text_state, &mut ctx);
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+
+    let expected = [format!("a{FAMILY}{FLAG}"), format!("a{FAMILY}"), "a".into()];
+    for (remaining, expected) in (1..=3).rev().zip(expected) {
+        text_state.apply_action(&mut ctx, &Action::DeleteBackward);
+        assert_eq!(text_state.text(), expected);
+        assert_eq!(text_state.cursor_char_index(), Some(remaining));
+    }
+}
+
+#[test]
+pub fn test_backspace_by_code_point_corrects_indic_syllables() {
+    let mut ctx = TextContext::default();
+    // Devanagari "ki": the consonant KA with the vowel sign I
+    let mut text_state = editable_state(
+        &mut ctx,
+        "\u{915}\u{93F}x",
+        (400.0, 30.0),
+        &mono_style_test(),
+    );
+    text_state.backspace = Backspace::CodePoint;
+    assert_eq!(text_state.text_char_len(), 2);
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+
+    text_state.apply_action(&mut ctx, &Action::DeleteBackward);
+    assert_eq!(text_state.text(), "\u{915}x");
+    text_state.apply_action(&mut ctx, &Action::InsertChar("\u{940}".into()));
+    assert_eq!(text_state.text(), "\u{915}\u{940}x");
+
+    // The whole syllable is removed by default
+    text_state.backspace = Backspace::GraphemeCluster;
+    text_state.apply_action(&mut ctx, &Action::DeleteBackward);
+    assert_eq!(text_state.text(), "x");
+}
+
+#[test]
+pub fn test_cursor_moves_over_whole_grapheme_clusters() {
+    let mut ctx = TextContext::default();
+    let text = format!("{COMBINING}{FLAG}{FAMILY}");
+    for cursor_movement in [CursorMovement::Logical, CursorMovement::Visual] {
+        let mut text_state = editable_state(&mut ctx, &text, (400.0, 30.0), &mono_style_test());
+        text_state.cursor_movement = cursor_movement;
+        for index in 1..=3 {
+            text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+            assert_eq!(text_state.cursor_char_index(), Some(index));
+        }
+        for index in (0..3).rev() {
+            text_state.apply_action(&mut ctx, &Action::MoveCursorLeft);
+            assert_eq!(text_state.cursor_char_index(), Some(index));
+        }
+    }
+}
+
+#[test]
+pub fn test_selection_ends_at_grapheme_boundaries() {
+    let mut ctx = TextContext::default();
+    let text = format!("a{COMBINING}{FLAG}{FAMILY}b");
+    let boundaries: Vec<usize> = text
+        .grapheme_indices(true)
+        .map(|(offset, _)| offset)
+        .chain([text.len()])
+        .collect();
+    let mut text_state = editable_state(&mut ctx, &text, (400.0, 30.0), &mono_style_test());
+
+    // Without a color emoji font the flag and the family are drawn with several glyphs each
+    let mut selected_lengths = Vec::new();
+    for x in 1..120 {
+        text_state.handle_press(&mut ctx, Point::new(0.0, 10.0));
+        text_state.handle_drag(&mut ctx, true, Point::new(x as f32, 10.0));
+        let selected = text_state.selected_text().unwrap_or("").to_string();
+        assert!(boundaries.contains(&selected.len()), "{selected:?}");
+        selected_lengths.push(selected.len());
+    }
+    selected_lengths.sort_unstable();
+    selected_lengths.dedup();
+    assert_eq!(selected_lengths, boundaries);
+}
@@ -0,0 +1,200 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::{Hyphens, Language, TextStyle, TextWrap};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{TextContext, TextState};
+
+fn mono_width(ctx: &mut TextContext) -> f32 {
+    let mut text_state =
+        editable_state(ctx, "A", (400.0, 200.0), &hyphenated_style(Hyphens::Manual));
+    text_state.first_glyph().unwrap().w
+}
+
+/// Returns `true` if any pixel in the given rectangle of the texture is not transparent.
+fn has_ink(text_state: &TextState<()>, x: std::ops::Range<u32>, y: std::ops::Range<u32>) -> bool {
+    let texture = text_state.rasterized_texture();
+    y.flat_map(|y| x.clone().map(move |x| (x, y)))
+        .any(|(x, y)| texture.pixels[(y * texture.width + x) as usize * 4 + 3] > 0)
+}
+
+fn hyphenated_style(hyphens: Hyphens) -> TextStyle {
+    mono_style_test()
+        .with_wrap(TextWrap::Wrap)
+        .with_language(Language::ENGLISH)
+        .with_hyphens(hyphens)
+}
+
+#[cfg(feature = "hyphenation")]
+#[test]
+pub fn test_hyphenation_points_in_display_text() {
+    use crate::display_text::DisplayText;
+    use crate::hyphenation::hyphenation_points;
+    use crate::style::{TextDirection, WhiteSpace};
+
+    assert_eq!(
+        hyphenation_points("extensive, 42", Language::ENGLISH),
+        vec![2, 5]
+    );
+    assert!(hyphenation_points("extensive", Language::from_tag("xx").unwrap()).is_empty());
+    assert_eq!(
+        DisplayText::new("a b", WhiteSpace::PreWrap, TextDirection::Auto, None),
+        None
+    );
+
+    let display_text = DisplayText::new(
+        "extensive",
+        WhiteSpace::PreWrap,
+        TextDirection::Auto,
+        Some(Language::ENGLISH),
+    )
+    .unwrap();
+    assert_eq!(display_text.text(), "ex\u{ad}ten\u{ad}sive");
+    // Both sides of a soft hyphen map to the char after it
+    assert_eq!(display_text.to_original(2), 2);
+    assert_eq!(display_text.to_original(4), 2);
+    assert_eq!(display_text.to_display(2), 4);
+    assert_eq!(display_text.to_original(display_text.text().len()), 9);
+}
+
+#[cfg(feature = "hyphenation")]
+#[test]
+pub fn test_auto_hyphenation_breaks_words() {
+    use crate::{Action, Point};
+
+    let mut ctx = TextContext::default();
+    let mono_width = mono_width(&mut ctx);
+    let line_height = mono_style_test().line_height_pt();
+    let text = "extensive extensive";
+    let width = mono_width * 6.5;
+
+    let mut text_state = editable_state(
+        &mut ctx,
+        text,
+        (width, 200.0),
+        &hyphenated_style(Hyphens::Auto),
+    );
+    assert_eq!(text_state.text(), text);
+    // "exten-", "sive", "exten-", "sive"
+    assert!((text_state.inner_size().y - line_height * 4.0).abs() < 0.5);
+
+    // The second line starts after the hyphenation point
+    text_state.handle_press(&mut ctx, Point::new(1.0, line_height * 1.5));
+    assert_eq!(text_state.cursor_char_index(), Some(5));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!(caret.x.abs() < 1.0);
+    assert!((caret.y - line_height).abs() < 1.0);
+
+    // The hyphen is drawn after "exten" at the end of the first line
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let hyphen_x = (mono_width * 5.0).ceil() as u32..(mono_width * 6.0).floor() as u32;
+    assert!(has_ink(
+        &text_state,
+        hyphen_x.clone(),
+        0..line_height as u32
+    ));
+
+    // Editing keeps working on the original text
+    text_state.apply_action(&mut ctx, &Action::InsertChar("X".into()));
+    assert_eq!(text_state.text(), "extenXsive extensive");
+    assert_eq!(text_state.cursor_char_index(), Some(6));
+}
+
+#[test]
+pub fn test_manual_soft_hyphen_draws_hyphen() {
+    let mut ctx = TextContext::default();
+    let mono_width = mono_width(&mut ctx);
+    let line_height = mono_style_test().line_height_pt();
+    let text = "exten\u{ad}sive";
+
+    let mut text_state = editable_state(
+        &mut ctx,
+        text,
+        (mono_width * 6.5, 200.0),
+        &hyphenated_style(Hyphens::Manual),
+    );
+    assert_eq!(text_state.text(), text);
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let hyphen_x = (mono_width * 5.0).ceil() as u32..(mono_width * 6.0).floor() as u32;
+    assert!(has_ink(
+        &text_state,
+        hyphen_x.clone(),
+        0..line_height as u32
+    ));
+
+    // Without a break, the soft hyphen stays invisible
+    let mut text_state = editable_state(
+        &mut ctx,
+        text,
+        (400.0, 200.0),
+        &hyphenated_style(Hyphens::Manual),
+    );
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let after_text = (mono_width * 9.0).ceil() as u32..(mono_width * 11.0) as u32;
+    assert!(!has_ink(&text_state, after_text, 0..line_height as u32));
+}
+
+#[test]
+pub fn test_lines_break_earlier_when_the_hyphen_doesnt_fit() {
+    use crate::hyphenation::hyphen_glyph;
+
+    let mut ctx = TextContext::default();
+    let mono_width = mono_width(&mut ctx);
+    let line_height = mono_style_test().line_height_pt();
+    let text = "ab\u{ad}cd\u{ad}ef";
+    let width = mono_width * 4.5;
+
+    // "abcd" fits into the line, but "abcd-" doesn't, so the word breaks after "ab"
+    let mut text_state = editable_state(
+        &mut ctx,
+        text,
+        (width, 200.0),
+        &hyphenated_style(Hyphens::Manual),
+    );
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+    let runs: Vec<_> = text_state.buffer().layout_runs().collect();
+    let hyphen = hyphen_glyph(&mut ctx.font_system, &runs[0]).unwrap();
+    assert!((hyphen.x - mono_width * 2.0).abs() < 1.0);
+    assert!(hyphen.x + hyphen.w <= width);
+    assert!(hyphen_glyph(&mut ctx.font_system, &runs[1]).is_none());
+
+    assert_eq!(text_state.text(), text);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let after_hyphen = (mono_width * 3.0).ceil() as u32..width as u32;
+    assert!(!has_ink(&text_state, after_hyphen, 0..line_height as u32));
+}
+
+#[test]
+pub fn test_svg_text_keeps_suppressed_soft_hyphens() {
+    use crate::{SvgGlyphs, SvgOptions};
+
+    let mut ctx = TextContext::default();
+    let mono_width = mono_width(&mut ctx);
+    let text_state = editable_state(
+        &mut ctx,
+        "ab\u{ad}cd\u{ad}ef",
+        (mono_width * 4.5, 200.0),
+        &hyphenated_style(Hyphens::Manual),
+    );
+    let options = SvgOptions::DEFAULT.with_glyphs(SvgGlyphs::Text);
+    let svg = text_state.to_svg(&mut ctx, &options);
+    // The soft hyphen after "cd" doesn't fit, and the one after "ab" breaks the line
+    assert!(!svg.contains('\u{34f}'));
+    assert_eq!(svg.matches('\u{ad}').count(), 2);
+    assert_eq!(svg.matches(">-</text>").count(), 1);
+}
@@ -1,15 +1,44 @@
 use crate::style::{
-    FontColor, FontFamily, FontSize, HorizontalTextAlignment, LineHeight, TextStyle, TextWrap,
-    VerticalTextAlignment, Weight,
+    FontColor, FontFamily, FontSize, HorizontalTextAlignment, Hyphens, LineHeight, ParagraphStyle,
+    TextDecoration, TextDirection, TextEffects, TextStyle, TextWrap, VerticalTextAlignment, Weight,
+    WhiteSpace, WritingMode,
 };
+use crate::{Point, TextContext, TextState};
 use cosmic_text::Color;
 
+mod atlas;
+mod bidi;
 mod byte_offset;
+mod caret;
 mod caret_positioning;
+mod color_glyph;
 mod copy_selected_text;
+mod damage;
+mod display_list;
+mod distance_field;
+mod effects;
+mod gamma;
+mod glyph_path;
+mod grapheme;
+mod hyphenation;
+mod overwrite;
+mod padding;
+mod paragraph;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod pixel_format;
+mod raster_overlay;
 mod resolved_font_family;
+mod scroll_rendering;
 mod serialization;
+#[cfg(feature = "png")]
+mod snapshot;
+mod subpixel;
+mod svg;
+mod tabs;
 mod text_state;
+mod vertical;
+mod white_space;
 
 fn mono_style_test() -> TextStyle {
     TextStyle {
@@ -19,8 +48,37 @@ fn mono_style_test() -> TextStyle {
         horizontal_alignment: HorizontalTextAlignment::Start,
         vertical_alignment: VerticalTextAlignment::Start,
         wrap: Some(TextWrap::NoWrap), // No wrapping to ensure a single line
+        white_space: WhiteSpace::PreWrap,
+        direction: TextDirection::Auto,
+        writing_mode: WritingMode::HorizontalTb,
+        language: None,
+        hyphens: Hyphens::Manual,
+        decoration: TextDecoration::NONE,
+        effects: TextEffects::NONE,
         font_family: FontFamily::Monospace,
         weight: Weight::NORMAL,
         letter_spacing: None,
+        tab_width: TextStyle::DEFAULT.tab_width,
+        tab_stops: None,
+        paragraph: ParagraphStyle::NONE,
+        paragraph_overrides: Default::default(),
     }
 }
+
+/// Creates an editable and selectable text state with the given style and outer size in
+/// logical pixels, and lays it out.
+fn editable_state(
+    ctx: &mut TextContext,
+    text: &str,
+    size: (f32, f32),
+    style: &TextStyle,
+) -> TextState<()> {
+    let mut text_state = TextState::new_with_text(text, &mut ctx.font_system, ());
+    text_state.set_style(style);
+    text_state.set_outer_size(&Point::from(size));
+    text_state.is_editable = true;
+    text_state.is_selectable = true;
+    text_state.are_actions_enabled = true;
+    text_state.recalculate(ctx);
+    text_state
+}
@@ -0,0 +1,64 @@
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, ActionResult, TextContext, TextState};
+
+fn overwrite_state(ctx: &mut TextContext, text: &str) -> TextState<()> {
+    let mut text_state = editable_state(ctx, text, (200.0, 50.0), &mono_style_test());
+    assert_eq!(
+        text_state.apply_action(ctx, &Action::ToggleOverwrite),
+        ActionResult::CursorUpdated
+    );
+    assert!(text_state.overwrite_mode());
+    text_state
+}
+
+fn type_text(text_state: &mut TextState<()>, ctx: &mut TextContext, text: &str) {
+    text_state.apply_action(ctx, &Action::InsertChar(text.into()));
+}
+
+#[test]
+pub fn test_overwrite_replaces_graphemes_and_appends_at_line_end() {
+    let mut ctx = TextContext::default();
+    let mut text_state = overwrite_state(&mut ctx, "abc\ndef");
+
+    type_text(&mut text_state, &mut ctx, "xy");
+    assert_eq!(text_state.text(), "xyc\ndef");
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+
+    // The line break isn't overwritten
+    type_text(&mut text_state, &mut ctx, "zw");
+    assert_eq!(text_state.text(), "xyzw\ndef");
+
+    // Typing a line break inserts it
+    type_text(&mut text_state, &mut ctx, "\n");
+    assert_eq!(text_state.text(), "xyzw\n\ndef");
+
+    text_state.apply_action(&mut ctx, &Action::ToggleOverwrite);
+    assert!(!text_state.overwrite_mode());
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    type_text(&mut text_state, &mut ctx, "v");
+    assert_eq!(text_state.text(), "xyzw\n\nvdef");
+}
+
+#[test]
+pub fn test_overwrite_replaces_whole_grapheme_clusters() {
+    let mut ctx = TextContext::default();
+    let mut text_state = overwrite_state(&mut ctx, "e\u{301}\u{1F468}\u{200D}\u{1F469}!");
+
+    type_text(&mut text_state, &mut ctx, "a");
+    assert_eq!(text_state.text(), "a\u{1F468}\u{200D}\u{1F469}!");
+
+    // A typed cluster replaces a single grapheme
+    type_text(&mut text_state, &mut ctx, "o\u{308}");
+    assert_eq!(text_state.text(), "ao\u{308}!");
+}
+
+#[test]
+pub fn test_overwrite_replaces_only_the_selection() {
+    let mut ctx = TextContext::default();
+    let mut text_state = overwrite_state(&mut ctx, "abc");
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+
+    type_text(&mut text_state, &mut ctx, "x");
+    assert_eq!(text_state.text(), "x");
+}
@@ -0,0 +1,116 @@
+use crate::math::Padding;
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::TextWrap;
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, DrawCommand, Point, TextContext, TextState};
+use cosmic_text::Color;
+
+fn padded_state(ctx: &mut TextContext, text: &str, padding: Padding) -> TextState<()> {
+    let mut text_state = editable_state(ctx, text, (100.0, 40.0), &mono_style_test());
+    text_state.set_padding(padding);
+    text_state.recalculate(ctx);
+    text_state
+}
+
+#[test]
+pub fn test_padding_offsets_hit_testing_and_geometry() {
+    let mut ctx = TextContext::default();
+    let padding = Padding::new(6.0, 8.0, 6.0, 12.0);
+    let mut text_state = padded_state(&mut ctx, "abc", padding);
+    let mono_width = text_state.first_glyph().unwrap().w;
+
+    // The outer size is kept, the text is laid out inside of the padding
+    assert_eq!(text_state.outer_size(), Point::new(100.0, 40.0));
+    assert_eq!(
+        text_state.caret_position_relative(),
+        Some(Point::new(12.0, 6.0))
+    );
+
+    // Presses are relative to the whole text area
+    text_state.handle_press(&mut ctx, Point::new(12.0 + mono_width * 2.0 + 1.0, 10.0));
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - (12.0 + mono_width * 2.0)).abs() < 1.0);
+    assert_eq!(caret.y, 6.0);
+
+    text_state.handle_drag(&mut ctx, true, Point::new(12.0, 10.0));
+    let line = text_state.selection().lines()[0];
+    assert!((line.start_x_pt.unwrap() - 12.0).abs() < 0.5);
+    assert_eq!(line.start_y_pt, Some(6.0));
+
+    // Glyphs in the display list are moved by the padding as well
+    let glyph_x = text_state
+        .display_list(&mut ctx)
+        .into_iter()
+        .find_map(|command| match command {
+            DrawCommand::GlyphRun { glyphs } => Some(glyphs[0].position.x),
+            _ => None,
+        })
+        .unwrap();
+    assert!((glyph_x - 12.0).abs() < 1.0);
+}
+
+#[test]
+pub fn test_padding_shrinks_layout_and_scroll_follow() {
+    let mut ctx = TextContext::default();
+    let padding = Padding::symmetric(20.0, 0.0);
+    let text = "aaaa aaaa aaaa aaaa";
+
+    // Wrapping happens at the width inside of the padding
+    let mut text_state = padded_state(&mut ctx, text, padding);
+    let mono_width = text_state.first_glyph().unwrap().w;
+    text_state.set_style(&mono_style_test().with_wrap(TextWrap::Wrap));
+    text_state.recalculate(&mut ctx);
+    assert!(text_state.inner_size().x <= 60.0);
+    assert!(text_state.buffer().layout_runs().count() > 1);
+
+    // The caret is scrolled into the area inside of the padding
+    let mut text_state = padded_state(&mut ctx, text, padding);
+    for _ in 0..text.len() {
+        text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    }
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!(text_state.absolute_scroll().x > 0.0);
+    assert!(caret.x >= 20.0);
+    assert!(caret.x + text_state.caret_width() <= 80.0 + mono_width / 2.0);
+}
+
+#[test]
+pub fn test_background_fills_padding_and_glyphs_are_clipped() {
+    let mut ctx = TextContext::default();
+    let mut text_state = padded_state(&mut ctx, "WWWWWWWWWWWW", Padding::uniform(10.0));
+    text_state.background = Some(Color::rgb(255, 255, 0));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    let texture = text_state.rasterized_texture();
+    assert_eq!((texture.width, texture.height), (100, 40));
+    let pixel = |x: u32, y: u32| {
+        let index = (y * texture.width + x) as usize * 4;
+        <[u8; 4]>::try_from(&texture.pixels[index..index + 4]).unwrap()
+    };
+    assert_eq!(pixel(0, 0), [255, 255, 0, 255]);
+    assert_eq!(pixel(99, 39), [255, 255, 0, 255]);
+
+    // The text overflows the area inside of the padding, but isn't drawn into the padding
+    let is_ink = |pixel: [u8; 4]| pixel[2] == 0 && pixel[0] < 128;
+    let inked_columns: Vec<u32> = (0..texture.width)
+        .filter(|x| (0..texture.height).any(|y| is_ink(pixel(*x, y))))
+        .collect();
+    assert!(!inked_columns.is_empty());
+    assert!(inked_columns.iter().all(|x| (10..90).contains(x)));
+
+    // Changing the background redraws the whole texture
+    text_state.background = None;
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let texture = text_state.rasterized_texture();
+    assert_eq!(texture.dirty_rect.width, 100);
+    assert_eq!(texture.pixels[3], 0);
+}
@@ -0,0 +1,210 @@
+use crate::style::{ParagraphStyle, TabStops, TextDirection, TextWrap};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, Point, TextContext, TextState};
+
+fn mono_width_and_line_height(ctx: &mut TextContext) -> (f32, f32) {
+    let mut text_state = TextState::new_with_text("A", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((400.0, 100.0)));
+    text_state.recalculate(ctx);
+    (
+        text_state.first_glyph().unwrap().w,
+        text_state.inner_size().y,
+    )
+}
+
+#[test]
+pub fn test_first_line_indent_and_insets() {
+    let mut ctx = TextContext::default();
+    let (mono_width, _) = mono_width_and_line_height(&mut ctx);
+    let paragraph = ParagraphStyle::NONE
+        .with_first_line_indent(20.0)
+        .with_insets(10.0, 5.0);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "ABCD",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(paragraph),
+    );
+
+    assert!((text_state.inner_size().x - (35.0 + mono_width * 4.0)).abs() < 0.5);
+
+    // Clicking on the right half of the second glyph puts the caret after it
+    text_state.handle_press(&mut ctx, Point::new(30.0 + mono_width * 1.75, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - (30.0 + mono_width * 2.0)).abs() < 1.0);
+
+    // Clicking in the indent puts the caret at the start of the paragraph
+    text_state.handle_press(&mut ctx, Point::new(15.0, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - 30.0).abs() < 1.0);
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let selection_line = &text_state.selection().lines()[0];
+    assert!((selection_line.start_x_pt.unwrap() - 30.0).abs() < 1.0);
+}
+
+#[test]
+pub fn test_first_line_indent_is_at_the_start_edge_of_rtl_paragraphs() {
+    let mut ctx = TextContext::default();
+    let (mono_width, line_height) = mono_width_and_line_height(&mut ctx);
+    let paragraph = ParagraphStyle::NONE
+        .with_first_line_indent(mono_width * 2.0)
+        .with_insets(400.0 - mono_width * 7.0, 0.0);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "aaaa bbbb",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(paragraph),
+    );
+    text_state.set_style(
+        &mono_style_test()
+            .with_direction(TextDirection::Rtl)
+            .with_wrap(TextWrap::Wrap)
+            .with_paragraph(paragraph),
+    );
+    text_state.recalculate(&mut ctx);
+
+    // Only "aaaa " fits next to the indent, and the first line ends before it on the right
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - (400.0 - mono_width * 2.0)).abs() < 1.0);
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let lines = text_state.selection().lines();
+    assert!((lines[0].end_x_pt.unwrap() - (400.0 - mono_width * 2.0)).abs() < 1.0);
+    assert!((lines[1].end_x_pt.unwrap() - 400.0).abs() < 1.0);
+
+    // Clicking in the indent puts the caret at the start of the paragraph
+    text_state.handle_press(&mut ctx, Point::new(400.0 - mono_width, line_height / 2.0));
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+}
+
+#[test]
+pub fn test_tab_stops_move_carets_and_hits() {
+    let mut ctx = TextContext::default();
+    let (mono_width, _) = mono_width_and_line_height(&mut ctx);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "a\tb",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(ParagraphStyle::NONE),
+    );
+    text_state.set_style(&mono_style_test().with_tab_stops(TabStops::new([100.0])));
+    text_state.recalculate(&mut ctx);
+    assert!((text_state.inner_size().x - (100.0 + mono_width)).abs() < 0.5);
+
+    // The caret after the tab is at the stop, and before it right after "a"
+    text_state.handle_press(&mut ctx, Point::new(100.0 + mono_width * 0.25, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+    assert!((text_state.caret_position_relative().unwrap().x - 100.0).abs() < 1.0);
+    text_state.handle_press(&mut ctx, Point::new(mono_width * 1.25, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(1));
+    assert!((text_state.caret_position_relative().unwrap().x - mono_width).abs() < 1.0);
+
+    // Clicks on the tab land on its nearest edge
+    text_state.handle_press(&mut ctx, Point::new(90.0, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let line = text_state.selection().lines()[0];
+    assert!((line.end_x_pt.unwrap() - (100.0 + mono_width)).abs() < 1.0);
+}
+
+#[test]
+pub fn test_hanging_indent_wraps_within_insets() {
+    let mut ctx = TextContext::default();
+    let (mono_width, line_height) = mono_width_and_line_height(&mut ctx);
+    let paragraph = ParagraphStyle::NONE
+        .with_hanging_indent(mono_width * 2.0)
+        .with_insets(0.0, 400.0 - mono_width * 10.5);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "aaaa bbbb cccc",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(paragraph),
+    );
+    text_state.set_style(
+        &mono_style_test()
+            .with_wrap(TextWrap::Wrap)
+            .with_paragraph(paragraph),
+    );
+    text_state.recalculate(&mut ctx);
+
+    // "aaaa bbbb " fits on the first line, "cccc" wraps and is indented
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+    text_state.handle_press(
+        &mut ctx,
+        Point::new(mono_width * 2.0 + 1.0, line_height * 1.5),
+    );
+    assert_eq!(text_state.cursor_char_index(), Some(10));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - mono_width * 2.0).abs() < 1.0);
+    assert!((caret.y - line_height).abs() < 1.0);
+}
+
+#[test]
+pub fn test_first_line_indent_wraps_overflowing_paragraphs() {
+    let mut ctx = TextContext::default();
+    let (mono_width, line_height) = mono_width_and_line_height(&mut ctx);
+    let paragraph = ParagraphStyle::NONE
+        .with_first_line_indent(mono_width * 4.0)
+        .with_insets(0.0, 400.0 - mono_width * 10.5);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "aaaaaaaaaaaaaaaa bb cc",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(paragraph),
+    );
+    text_state.set_style(
+        &mono_style_test()
+            .with_wrap(TextWrap::Wrap)
+            .with_paragraph(paragraph),
+    );
+    text_state.recalculate(&mut ctx);
+
+    // The long word overflows the text area, and the lines are laid out again at its width.
+    // "bb" still doesn't fit after it on the indented first line.
+    assert!(text_state.inner_size().x > 400.0);
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+    text_state.handle_press(&mut ctx, Point::new(1.0, line_height * 1.5));
+    assert_eq!(text_state.cursor_char_index(), Some(17));
+}
+
+#[test]
+pub fn test_paragraph_spacing_and_overrides() {
+    let mut ctx = TextContext::default();
+    let (_, line_height) = mono_width_and_line_height(&mut ctx);
+    let mut text_state = editable_state(
+        &mut ctx,
+        "one\ntwo",
+        (400.0, 100.0),
+        &mono_style_test().with_paragraph(ParagraphStyle::NONE.with_spacing(5.0, 10.0)),
+    );
+    assert!((text_state.inner_size().y - (line_height * 2.0 + 30.0)).abs() < 0.5);
+
+    // The second paragraph starts after the first one and both spacings
+    let second_top = 5.0 + line_height + 10.0 + 5.0;
+    text_state.handle_press(&mut ctx, Point::new(0.0, second_top + 1.0));
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.y - second_top).abs() < 1.0);
+
+    // Clicks in the space above a paragraph land on its first line
+    text_state.handle_press(&mut ctx, Point::new(0.0, second_top - 2.0));
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+
+    let style = mono_style_test()
+        .with_paragraph(ParagraphStyle::NONE.with_spacing(5.0, 10.0))
+        .with_paragraph_override(1, ParagraphStyle::NONE.with_insets(50.0, 0.0));
+    text_state.set_style(&style);
+    text_state.recalculate(&mut ctx);
+    assert!((text_state.inner_size().y - (line_height * 2.0 + 15.0)).abs() < 0.5);
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let lines = text_state.selection().lines();
+    assert!((lines[0].start_x_pt.unwrap() - 0.0).abs() < 1.0);
+    assert!((lines[1].start_x_pt.unwrap() - 50.0).abs() < 1.0);
+    assert!((lines[1].start_y_pt.unwrap() - (5.0 + line_height + 10.0)).abs() < 1.0);
+}
@@ -0,0 +1,95 @@
+use crate::{
+    state::{AlphaMode, PixelFormat},
+    style::FontFamily,
+    tests::mono_style_test,
+    Id, Point, TextManager,
+};
+use std::collections::HashSet;
+
+fn labels() -> TextManager<()> {
+    let mut manager = TextManager::new();
+    for index in 0..64 {
+        let id = Id::new(index);
+        manager.create_state(id, format!("Label number {index}"), ());
+        let state = manager.text_states.get_mut(&id).unwrap();
+        state.set_style(&mono_style_test().with_font_size(10.0 + index as f32 % 7.0));
+        state.set_outer_size(&Point::from((120.0, 24.0)));
+    }
+    manager
+}
+
+#[test]
+fn test_parallel_rasterization_matches_single_threaded() {
+    let mut serial = labels();
+    let mut parallel = labels();
+    for _ in 0..2 {
+        let serial_changes =
+            serial.rasterize_all_textures_with_format(AlphaMode::Premultiplied, PixelFormat::Rgba8);
+        let parallel_changes = parallel.rasterize_all_textures_on_threads(
+            AlphaMode::Premultiplied,
+            PixelFormat::Rgba8,
+            4,
+        );
+        // The managers iterate their states in different orders
+        let ids = |changes: &[crate::text_manager::RasterizedTextureInfo]| {
+            changes.iter().map(|info| info.id).collect::<HashSet<_>>()
+        };
+        assert_eq!(serial_changes.len(), parallel_changes.len());
+        assert_eq!(ids(&serial_changes), ids(&parallel_changes));
+        for (id, state) in &serial.text_states {
+            let texture = state.rasterized_texture();
+            let parallel_texture = parallel.text_states[id].rasterized_texture();
+            assert_eq!(texture.width, parallel_texture.width);
+            assert_eq!(texture.pixels, parallel_texture.pixels);
+        }
+
+        // Only the edited label is rasterized again
+        for manager in [&mut serial, &mut parallel] {
+            manager
+                .text_states
+                .get_mut(&Id::new(3))
+                .unwrap()
+                .set_text("Edited");
+        }
+    }
+    let changes =
+        parallel.rasterize_all_textures_on_threads(AlphaMode::Premultiplied, PixelFormat::Rgba8, 4);
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn test_parallel_rasterization_picks_up_replaced_fonts() {
+    const EMOJI_FONT: &[u8] = include_bytes!("fonts/NotoColorEmoji-Partial.ttf");
+    const COLR_FONT: &[u8] = include_bytes!("fonts/colr_1.ttf");
+
+    let mut serial = labels();
+    let mut parallel = labels();
+    for manager in [&mut serial, &mut parallel] {
+        manager.load_fonts_from_bytes(std::iter::once(COLR_FONT));
+    }
+    parallel.rasterize_all_textures_on_threads(AlphaMode::Premultiplied, PixelFormat::Rgba8, 4);
+
+    // Replacing a font keeps the number of fonts the same
+    for manager in [&mut serial, &mut parallel] {
+        let db = manager.text_context.font_system.db_mut();
+        let colr_face = db.faces().last().unwrap().id;
+        db.remove_face(colr_face);
+        manager.load_fonts_from_bytes(std::iter::once(EMOJI_FONT));
+        for index in 0..64 {
+            let state = manager.text_states.get_mut(&Id::new(index)).unwrap();
+            let mut style = mono_style_test().with_font_size(24.0);
+            style.font_family = FontFamily::new("Noto Color Emoji");
+            state.set_style(&style);
+            state.set_text("#0");
+        }
+    }
+    serial.rasterize_all_textures_with_format(AlphaMode::Premultiplied, PixelFormat::Rgba8);
+    parallel.rasterize_all_textures_on_threads(AlphaMode::Premultiplied, PixelFormat::Rgba8, 4);
+    for (id, state) in &serial.text_states {
+        let texture = state.rasterized_texture();
+        assert_eq!(
+            texture.pixels,
+            parallel.text_states[id].rasterized_texture().pixels
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::FontColor;
+use crate::tests::mono_style_test;
+use crate::utils::{f32_to_f16_bits, srgb_to_linear_u8};
+use crate::{Point, TextContext, TextState};
+use cosmic_text::Color;
+
+fn rasterize(ctx: &mut TextContext, format: PixelFormat) -> TextState<()> {
+    let mut text_state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((60.0, 20.0)));
+    text_state.recalculate(ctx);
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, format));
+    text_state
+}
+
+#[test]
+pub fn test_pixel_formats_match_rgba8() {
+    let mut ctx = TextContext::default();
+    let rgba = rasterize(&mut ctx, PixelFormat::Rgba8);
+    let rgba = &rgba.rasterized_texture().pixels;
+    assert!(rgba.chunks(4).any(|pixel| pixel[3] > 0));
+
+    let bgra = rasterize(&mut ctx, PixelFormat::Bgra8);
+    assert_eq!(bgra.rasterized_texture().format, PixelFormat::Bgra8);
+    for (bgra, rgba) in bgra
+        .rasterized_texture()
+        .pixels
+        .chunks(4)
+        .zip(rgba.chunks(4))
+    {
+        assert_eq!(bgra, [rgba[2], rgba[1], rgba[0], rgba[3]]);
+    }
+
+    let a8 = rasterize(&mut ctx, PixelFormat::A8);
+    let alpha: Vec<u8> = rgba.chunks(4).map(|pixel| pixel[3]).collect();
+    assert_eq!(a8.rasterized_texture().pixels, alpha);
+
+    let rgba16f = rasterize(&mut ctx, PixelFormat::Rgba16F);
+    let texture = rgba16f.rasterized_texture();
+    assert_eq!(texture.pixels.len(), 60 * 20 * 8);
+    for (half, rgba) in texture.pixels.chunks(8).zip(rgba.chunks(4)) {
+        let alpha = u16::from_le_bytes([half[6], half[7]]);
+        assert_eq!(alpha, f32_to_f16_bits(f32::from(rgba[3]) / 255.0));
+    }
+}
+
+#[test]
+pub fn test_rgba16f_is_premultiplied_in_linear_space() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    let style = mono_style_test().with_font_color(FontColor(Color::rgb(200, 100, 50)));
+    text_state.set_style(&style);
+    text_state.set_outer_size(&Point::from((60.0, 20.0)));
+    text_state.recalculate(&mut ctx);
+    text_state.rasterize_into_texture(&mut ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8);
+    let straight = text_state.rasterized_texture().pixels.clone();
+    text_state.rasterize_into_texture(&mut ctx, AlphaMode::Premultiplied, PixelFormat::Rgba16F);
+    let premultiplied = &text_state.rasterized_texture().pixels;
+
+    let mut partially_covered = 0;
+    for (half, straight) in premultiplied.chunks(8).zip(straight.chunks(4)) {
+        let alpha = f32::from(straight[3]) / 255.0;
+        if straight[3] > 0 && straight[3] < 255 {
+            partially_covered += 1;
+        }
+        for channel in 0..3 {
+            // Premultiplying the sRGB bytes first would darken the edges of the glyphs
+            let expected = f32_to_f16_bits(srgb_to_linear_u8(straight[channel]) * alpha);
+            let bits = u16::from_le_bytes([half[channel * 2], half[channel * 2 + 1]]);
+            assert_eq!(bits, expected);
+        }
+    }
+    assert!(partially_covered > 0);
+}
+
+#[test]
+pub fn test_changing_format_redraws_texture() {
+    let mut ctx = TextContext::default();
+    let mut text_state = rasterize(&mut ctx, PixelFormat::Rgba8);
+    assert!(text_state.rasterize_into_texture(&mut ctx, AlphaMode::Unmultiplied, PixelFormat::A8));
+    let texture = text_state.rasterized_texture();
+    assert_eq!(texture.pixels.len(), 60 * 20);
+    assert_eq!(texture.dirty_rect, crate::DirtyRect::full(60, 20));
+    assert!(!text_state.rasterize_into_texture(&mut ctx, AlphaMode::Unmultiplied, PixelFormat::A8));
+}
+
+#[test]
+pub fn test_f32_to_f16_bits() {
+    assert_eq!(f32_to_f16_bits(0.0), 0x0000);
+    assert_eq!(f32_to_f16_bits(-0.0), 0x8000);
+    assert_eq!(f32_to_f16_bits(1.0), 0x3C00);
+    assert_eq!(f32_to_f16_bits(0.5), 0x3800);
+    assert_eq!(f32_to_f16_bits(-2.0), 0xC000);
+    assert_eq!(f32_to_f16_bits(65504.0), 0x7BFF);
+    assert_eq!(f32_to_f16_bits(1.0e6), 0x7C00);
+    // Smallest subnormal
+    assert_eq!(f32_to_f16_bits(5.960_464_5e-8), 0x0001);
+    // Ties round to even
+    assert_eq!(f32_to_f16_bits(1.0 + 1.0 / 2048.0), 0x3C00);
+    assert_eq!(f32_to_f16_bits(1.0 + 3.0 / 2048.0), 0x3C02);
+}
@@ -0,0 +1,162 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, CaretShape, DrawCommand, Point, SelectionColors, TextContext, TextState};
+use cosmic_text::Color;
+
+fn pixel(text_state: &TextState<()>, x: u32, y: u32) -> [u8; 4] {
+    let texture = text_state.rasterized_texture();
+    let index = (y * texture.width + x) as usize * 4;
+    texture.pixels[index..index + 4].try_into().unwrap()
+}
+
+#[test]
+pub fn test_selection_is_painted_behind_glyphs() {
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let mut text_state = editable_state(&mut ctx, "ab", (100.0, 40.0), &mono_style_test());
+    let mono_width = text_state.first_glyph().unwrap().w;
+    text_state.selection_colors =
+        Some(SelectionColors::new(Color::rgb(255, 0, 0)).with_text(Color::rgb(255, 255, 255)));
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    // The background fills the selection, outside of it the texture stays transparent
+    assert_eq!(pixel(&text_state, 1, 1), [255, 0, 0, 255]);
+    let after_selection = (mono_width * 2.0).ceil() as u32 + 2;
+    assert_eq!(pixel(&text_state, after_selection, 1)[3], 0);
+    assert_eq!(pixel(&text_state, 1, line_height as u32 + 2)[3], 0);
+
+    // Selected glyphs use the selected text color, so there's no dark ink
+    let texture = text_state.rasterized_texture();
+    let has_light_ink = texture
+        .pixels
+        .chunks(4)
+        .any(|pixel| pixel[3] == 255 && pixel[1] > 128);
+    let has_dark_ink = texture
+        .pixels
+        .chunks(4)
+        .any(|pixel| pixel[3] > 0 && pixel[0] < 128);
+    assert!(has_light_ink);
+    assert!(!has_dark_ink);
+}
+
+#[test]
+pub fn test_caret_is_painted_and_blinks() {
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let mut text_state = editable_state(&mut ctx, "ab", (100.0, 40.0), &mono_style_test());
+    text_state.set_caret_width(2.0);
+    text_state.caret.color = Some(Color::rgb(0, 0, 255));
+    text_state.handle_press(&mut ctx, Point::new(100.0, 1.0));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    let caret = text_state.caret_position_relative().unwrap();
+    let (caret_x, caret_y) = (caret.x.round() as u32, (line_height / 2.0) as u32);
+    assert_eq!(pixel(&text_state, caret_x, caret_y), [0, 0, 255, 255]);
+
+    // Hiding the caret only damages the caret
+    text_state.caret_blink_visible = false;
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let dirty_rect = text_state.rasterized_texture().dirty_rect;
+    assert_eq!(dirty_rect.x, caret_x);
+    assert_eq!(dirty_rect.width, 2);
+    assert_ne!(pixel(&text_state, caret_x, caret_y), [0, 0, 255, 255]);
+
+    // Without a color the caret is left to the renderer
+    text_state.caret_blink_visible = true;
+    text_state.caret.color = None;
+    assert!(!text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert_ne!(pixel(&text_state, caret_x, caret_y), [0, 0, 255, 255]);
+}
+
+#[test]
+pub fn test_block_caret_is_painted_behind_the_grapheme_it_covers() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(&mut ctx, "W", (100.0, 40.0), &mono_style_test());
+    text_state.caret.shape = CaretShape::Block;
+    text_state.caret.color = Some(Color::rgb(0, 0, 255));
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    // The black glyph is drawn over the opaque blue block
+    let block = text_state.caret_rects()[0];
+    let pixels: Vec<[u8; 4]> = (block.min.y as u32..block.max.y as u32)
+        .flat_map(|y| (block.min.x as u32..block.max.x as u32).map(move |x| (x, y)))
+        .map(|(x, y)| pixel(&text_state, x, y))
+        .collect();
+    assert!(pixels.iter().all(|pixel| pixel[3] == 255));
+    assert!(pixels.contains(&[0, 0, 255, 255]));
+    assert!(pixels.contains(&[0, 0, 0, 255]));
+
+    // The display list draws the block before the glyphs too
+    let commands = text_state.display_list(&mut ctx);
+    let caret = commands
+        .iter()
+        .position(|command| matches!(command, DrawCommand::Caret { .. }));
+    let glyphs = commands
+        .iter()
+        .position(|command| matches!(command, DrawCommand::GlyphRun { .. }));
+    assert!(caret < glyphs);
+}
+
+#[test]
+pub fn test_overlapping_glyphs_are_blended_instead_of_overwritten() {
+    use crate::style::LetterSpacing;
+
+    let mut ctx = TextContext::default();
+    let rasterize = |ctx: &mut TextContext, text: &str, letter_spacing: Option<LetterSpacing>| {
+        let mut text_state = TextState::new_with_text(text, &mut ctx.font_system, ());
+        let mut style = mono_style_test();
+        style.letter_spacing = letter_spacing;
+        text_state.set_style(&style);
+        text_state.set_outer_size(&Point::from((100.0, 40.0)));
+        text_state.recalculate(ctx);
+        assert!(text_state.rasterize_into_texture(
+            ctx,
+            AlphaMode::Unmultiplied,
+            PixelFormat::Rgba8
+        ));
+        text_state
+    };
+    let mut single = rasterize(&mut ctx, "l", None);
+    let advance = single.first_glyph().unwrap().w;
+    let font_size = mono_style_test().font_size.value();
+    // The second "l" starts two pixels after the first one and covers its right half
+    let overlapping = rasterize(
+        &mut ctx,
+        "ll",
+        Some(LetterSpacing((2.0 - advance) / font_size)),
+    );
+    let second = overlapping.buffer().layout_runs().next().unwrap().glyphs[1].x;
+    assert!((second - 2.0).abs() < 0.01);
+
+    // The faint edge of the second glyph used to overwrite the stem of the first one
+    let mut covered = 0;
+    for y in 0..40 {
+        for x in 0..100 {
+            let alone = pixel(&single, x, y)[3];
+            covered += usize::from(alone == 255);
+            assert!(pixel(&overlapping, x, y)[3] >= alone, "({x}, {y})");
+        }
+    }
+    assert!(covered > 0);
+}
@@ -1,6 +1,7 @@
 use crate::style::{
-    FontColor, FontFamily, FontSize, HorizontalTextAlignment, LineHeight, TextStyle, TextWrap,
-    VerticalTextAlignment, Weight,
+    FontColor, FontFamily, FontSize, HorizontalTextAlignment, Hyphens, LineHeight, ParagraphStyle,
+    TextDecoration, TextDirection, TextEffects, TextStyle, TextWrap, VerticalTextAlignment, Weight,
+    WhiteSpace, WritingMode,
 };
 use crate::tests::mono_style_test;
 use crate::{Point, TextContext, TextState};
@@ -48,9 +49,20 @@ fn test_resolved_font_family_changes_to_serif() {
         horizontal_alignment: HorizontalTextAlignment::Start,
         vertical_alignment: VerticalTextAlignment::Start,
         wrap: Some(TextWrap::NoWrap),
+        white_space: WhiteSpace::PreWrap,
+        direction: TextDirection::Auto,
+        writing_mode: WritingMode::HorizontalTb,
+        language: None,
+        hyphens: Hyphens::Manual,
+        decoration: TextDecoration::NONE,
+        effects: TextEffects::NONE,
         font_family: FontFamily::Serif,
         weight: Weight::NORMAL,
         letter_spacing: None,
+        tab_width: TextStyle::DEFAULT.tab_width,
+        tab_stops: None,
+        paragraph: ParagraphStyle::NONE,
+        paragraph_overrides: Default::default(),
     };
 
     text_state.set_style(&serif_style);
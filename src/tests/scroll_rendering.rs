@@ -0,0 +1,132 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::{VerticalTextAlignment, WritingMode};
+use crate::tests::mono_style_test;
+use crate::{Point, RasterizedTexture, ScrollRendering, TextContext, TextState};
+
+const TEXT: &str = "Line 1 with some text\nLine 2 with some text\nLine 3\nLine 4\nLine 5\nLine 6";
+
+fn scrolled_state(ctx: &mut TextContext, scroll_rendering: ScrollRendering) -> TextState<()> {
+    let mut text_state = TextState::new_with_text(TEXT, &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test().with_vertical_alignment(VerticalTextAlignment::None));
+    text_state.set_outer_size(&Point::new(80.0, 40.0));
+    text_state.scroll_rendering = scroll_rendering;
+    text_state.recalculate(ctx);
+    text_state
+}
+
+fn rasterize_at(
+    text_state: &mut TextState<()>,
+    ctx: &mut TextContext,
+    scroll: Point,
+) -> RasterizedTexture {
+    text_state.set_absolute_scroll(scroll);
+    text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8);
+    text_state.rasterized_texture().clone()
+}
+
+/// Rows of the pixels in the top-left corner of the texture.
+fn corner(texture: &RasterizedTexture, width: u32, height: u32) -> Vec<&[u8]> {
+    (0..height)
+        .map(|y| {
+            let start = (y * texture.width) as usize * 4;
+            &texture.pixels[start..start + width as usize * 4]
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_fractional_scroll_moves_texture_instead_of_glyphs() {
+    let mut ctx = TextContext::default();
+    let mut exact = scrolled_state(&mut ctx, ScrollRendering::Exact);
+    let mut fractional = scrolled_state(&mut ctx, ScrollRendering::Fractional);
+
+    let expected = rasterize_at(&mut exact, &mut ctx, Point::new(5.0, 10.0));
+    let texture = rasterize_at(&mut fractional, &mut ctx, Point::new(5.0, 10.0));
+    assert_eq!((texture.width, texture.height), (81, 41));
+    assert_eq!(
+        (texture.subpixel_offset_x, texture.subpixel_offset_y),
+        (0.0, 0.0)
+    );
+
+    // Scrolling within a pixel only changes the offset the compositor moves the texture by
+    fractional.set_absolute_scroll(Point::new(5.6, 10.25));
+    assert!(fractional.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let moved = fractional.rasterized_texture();
+    assert!(moved.dirty_rect.is_empty());
+    assert!((moved.subpixel_offset_x + 0.6).abs() < 0.001);
+    assert!((moved.subpixel_offset_y + 0.25).abs() < 0.001);
+    assert_eq!(moved.pixels, texture.pixels);
+    assert_eq!(corner(moved, 80, 40), corner(&expected, 80, 40));
+
+    // Exact scrolling rasterizes the glyphs at the new subpixel positions
+    let shimmering = rasterize_at(&mut exact, &mut ctx, Point::new(5.6, 10.25));
+    assert_ne!(shimmering.pixels, expected.pixels);
+    assert_eq!(
+        (shimmering.subpixel_offset_x, shimmering.subpixel_offset_y),
+        (0.0, 0.0)
+    );
+
+    // Whole pixels are rasterized as usual
+    let next = rasterize_at(&mut fractional, &mut ctx, Point::new(6.6, 10.25));
+    let expected = rasterize_at(&mut exact, &mut ctx, Point::new(6.0, 10.0));
+    assert!(!next.dirty_rect.is_empty());
+    assert_eq!(corner(&next, 80, 40), corner(&expected, 80, 40));
+}
+
+#[test]
+pub fn test_glyphs_follow_horizontal_scroll() {
+    let mut ctx = TextContext::default();
+    let mut text_state = scrolled_state(&mut ctx, ScrollRendering::Exact);
+    let unscrolled = rasterize_at(&mut text_state, &mut ctx, Point::new(0.0, 0.0));
+    // The first line is wider than the text area
+    let scrolled = rasterize_at(&mut text_state, &mut ctx, Point::new(20.0, 0.0));
+
+    // The glyphs move left with the caret and the selection, instead of staying in place
+    assert_ne!(corner(&scrolled, 60, 14), corner(&unscrolled, 60, 14));
+    for (scrolled, unscrolled) in
+        corner(&scrolled, 60, 14)
+            .into_iter()
+            .zip(corner(&unscrolled, 80, 14))
+    {
+        assert_eq!(scrolled, &unscrolled[20 * 4..]);
+    }
+}
+
+#[test]
+pub fn test_snapped_scroll_rounds_to_device_pixels() {
+    let mut ctx = TextContext::default();
+    let mut exact = scrolled_state(&mut ctx, ScrollRendering::Exact);
+    let mut snapped = scrolled_state(&mut ctx, ScrollRendering::Snapped);
+
+    for (scroll, rounded) in [(3.4, 3.0), (3.6, 4.0)] {
+        let texture = rasterize_at(&mut snapped, &mut ctx, Point::new(scroll, scroll));
+        let expected = rasterize_at(&mut exact, &mut ctx, Point::new(rounded, rounded));
+        assert_eq!((texture.width, texture.height), (80, 40));
+        assert_eq!(texture.pixels, expected.pixels);
+    }
+    // Hit testing keeps the exact offset
+    assert!((snapped.absolute_scroll().x - 3.6).abs() < 0.001);
+}
+
+#[test]
+pub fn test_fractional_scroll_of_vertical_text_moves_columns_left() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text(TEXT, &mut ctx.font_system, ());
+    text_state.set_style(
+        &mono_style_test()
+            .with_vertical_alignment(VerticalTextAlignment::None)
+            .with_writing_mode(WritingMode::VerticalRl),
+    );
+    text_state.set_outer_size(&Point::new(40.0, 80.0));
+    text_state.scroll_rendering = ScrollRendering::Fractional;
+    text_state.recalculate(&mut ctx);
+
+    // The content is rasterized moved right, so the texture is moved left
+    let texture = rasterize_at(&mut text_state, &mut ctx, Point::new(10.25, 0.0));
+    assert!(texture.subpixel_offset_x > -1.0 && texture.subpixel_offset_x <= 0.0);
+    assert!((texture.subpixel_offset_x + 0.75).abs() < 0.001);
+}
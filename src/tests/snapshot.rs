@@ -0,0 +1,81 @@
+use crate::{
+    state::{AlphaMode, PixelFormat},
+    tests::mono_style_test,
+    Point, RasterizedTexture, TextContext, TextState,
+};
+
+fn rasterize(ctx: &mut TextContext, text: &str, format: PixelFormat) -> RasterizedTexture {
+    let mut text_state = TextState::new_with_text(text, &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((60.0, 20.0)));
+    text_state.recalculate(ctx);
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, format));
+    text_state.rasterized_texture().clone()
+}
+
+#[test]
+pub fn test_png_round_trip_keeps_pixels() {
+    let mut ctx = TextContext::default();
+    // (rasterized format, decoded format, tolerance)
+    let formats = [
+        (PixelFormat::Rgba8, PixelFormat::Rgba8, 0),
+        (PixelFormat::Rgba8, PixelFormat::Rgba16F, 1),
+        (PixelFormat::Bgra8, PixelFormat::Bgra8, 0),
+        (PixelFormat::Bgra8, PixelFormat::Rgba8, 0),
+        (PixelFormat::A8, PixelFormat::A8, 0),
+        (PixelFormat::Rgba16F, PixelFormat::Rgba16F, 1),
+        (PixelFormat::Rgba16F, PixelFormat::Rgba8, 1),
+    ];
+    for (format, decoded_format, tolerance) in formats {
+        let texture = rasterize(&mut ctx, "Hello", format);
+        let png = texture.to_png().unwrap();
+        let decoded = RasterizedTexture::from_png(&png, decoded_format).unwrap();
+        assert_eq!(decoded.format, decoded_format);
+        assert_eq!((decoded.width, decoded.height), (60, 20));
+        if format == decoded_format && tolerance == 0 {
+            assert_eq!(decoded.pixels, texture.pixels);
+        }
+        let diff = decoded.diff(&texture, tolerance);
+        assert!(diff.is_match(), "{format:?}: {:?}", diff.max_difference);
+    }
+}
+
+#[test]
+pub fn test_diff_reports_changed_pixels() {
+    let mut ctx = TextContext::default();
+    let expected = rasterize(&mut ctx, "Hello", PixelFormat::Rgba8);
+    let actual = rasterize(&mut ctx, "Hellp", PixelFormat::Rgba8);
+
+    let diff = actual.diff(&expected, 0);
+    assert!(diff.size_matches);
+    assert!(!diff.is_match());
+    assert!(diff.max_difference > 128);
+    // Only the last glyph changed
+    let glyph_width = 60 / 7;
+    assert!(diff
+        .mismatches
+        .iter()
+        .all(|mismatch| mismatch.x >= glyph_width * 3));
+    for mismatch in &diff.mismatches {
+        let index = (mismatch.y * 60 + mismatch.x) as usize * 4;
+        assert_eq!(mismatch.actual, actual.pixels[index..index + 4]);
+        assert_eq!(mismatch.expected, expected.pixels[index..index + 4]);
+    }
+
+    // The diff image marks the mismatches
+    let image = RasterizedTexture::from_png(&diff.to_png().unwrap(), PixelFormat::Rgba8).unwrap();
+    let marked = image
+        .pixels
+        .chunks_exact(4)
+        .filter(|pixel| *pixel == [255, 0, 0, 255])
+        .count();
+    assert_eq!(marked, diff.mismatches.len());
+
+    // Textures of different sizes never match
+    let mut smaller = expected.clone();
+    smaller.width = 30;
+    smaller.pixels.truncate(30 * 20 * 4);
+    let diff = smaller.diff(&expected, 255);
+    assert!(!diff.size_matches);
+    assert!(!diff.is_match());
+}
@@ -0,0 +1,72 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::tests::mono_style_test;
+use crate::{Antialiasing, Point, SubpixelOrder, TextContext, TextState};
+use cosmic_text::Color;
+
+fn rasterize(ctx: &mut TextContext, antialiasing: Antialiasing) -> Vec<u8> {
+    let mut text_state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((60.0, 20.0)));
+    text_state.antialiasing = antialiasing;
+    text_state.recalculate(ctx);
+    assert!(text_state.rasterize_into_texture(ctx, AlphaMode::Unmultiplied, PixelFormat::Rgba8));
+    text_state.rasterized_texture().pixels.clone()
+}
+
+fn subpixel(order: SubpixelOrder) -> Antialiasing {
+    Antialiasing::Subpixel {
+        order,
+        background: Color::rgb(255, 255, 255),
+    }
+}
+
+#[test]
+pub fn test_subpixel_text_has_per_channel_coverage() {
+    let mut ctx = TextContext::default();
+    let grayscale = rasterize(&mut ctx, Antialiasing::Grayscale);
+    assert!(grayscale.chunks(4).all(|pixel| pixel[..3] == [0, 0, 0]));
+
+    let rgb = rasterize(&mut ctx, subpixel(SubpixelOrder::Rgb));
+    assert_eq!(rgb.len(), grayscale.len());
+    // Black text on an opaque white background, with color fringes at the glyph edges
+    assert!(rgb.chunks(4).all(|pixel| pixel[3] == 255));
+    assert!(rgb.chunks(4).any(|pixel| pixel[..3] == [0, 0, 0]));
+    assert!(rgb.chunks(4).any(|pixel| pixel[..3] == [255, 255, 255]));
+    assert!(rgb.chunks(4).any(|pixel| pixel[0] != pixel[2]));
+
+    // The channel order of the display mirrors the coverage of red and blue
+    let bgr = rasterize(&mut ctx, subpixel(SubpixelOrder::Bgr));
+    for (bgr, rgb) in bgr.chunks(4).zip(rgb.chunks(4)) {
+        assert_eq!(bgr, [rgb[2], rgb[1], rgb[0], rgb[3]]);
+    }
+}
+
+#[test]
+pub fn test_changing_antialiasing_redraws_texture() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((60.0, 20.0)));
+    text_state.recalculate(&mut ctx);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    assert!(!text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    text_state.antialiasing = subpixel(SubpixelOrder::Rgb);
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+    let texture = text_state.rasterized_texture();
+    assert_eq!(texture.dirty_rect, crate::DirtyRect::full(60, 20));
+    // The corner is outside of the text and filled with the background
+    assert_eq!(texture.pixels[..4], [255, 255, 255, 255]);
+}
@@ -0,0 +1,90 @@
+use crate::style::TextDecoration;
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, DrawCommand, Highlight, SvgGlyphs, SvgOptions, TextContext};
+use cosmic_text::Color;
+
+#[test]
+pub fn test_svg_contains_requested_layers() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "Hi there",
+        (120.0, 30.0),
+        &mono_style_test().with_decoration(TextDecoration::UNDERLINE),
+    );
+    text_state.set_highlights(vec![Highlight::new(0..2, Color::rgba(255, 255, 0, 128))]);
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    text_state.recalculate(&mut ctx);
+
+    let svg = text_state.to_svg(&mut ctx, &SvgOptions::DEFAULT);
+    assert!(svg.starts_with(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" height=\"30\" \
+         viewBox=\"0 0 120 30\">"
+    ));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<path").count(), 7);
+    // The highlight and the underline
+    assert_eq!(svg.matches("<rect").count(), 2);
+    assert!(svg.contains("fill=\"#ffff00\" fill-opacity=\"0.502\""));
+
+    let options = SvgOptions::DEFAULT
+        .with_selection(Color::rgb(51, 102, 204))
+        .with_caret(Color::rgb(255, 0, 0))
+        .with_decorations(false)
+        .with_highlights(false);
+    let svg = text_state.to_svg(&mut ctx, &options);
+    assert_eq!(svg.matches("<rect").count(), 2);
+    assert!(svg.contains("fill=\"#3366cc\""));
+    assert!(svg.contains("fill=\"#ff0000\""));
+    // The selection goes behind the glyphs and the caret in front of them
+    let selection = svg.find("#3366cc").unwrap();
+    let caret = svg.find("#ff0000").unwrap();
+    let first_glyph = svg.find("<path").unwrap();
+    assert!(selection < first_glyph && first_glyph < caret);
+}
+
+#[test]
+pub fn test_svg_text_is_positioned_and_escaped() {
+    let mut ctx = TextContext::default();
+    let text_state = editable_state(
+        &mut ctx,
+        "a<&>e\u{301}",
+        (120.0, 30.0),
+        &mono_style_test().with_decoration(TextDecoration::UNDERLINE),
+    );
+    let options = SvgOptions::DEFAULT.with_glyphs(SvgGlyphs::Text);
+    let svg = text_state.to_svg(&mut ctx, &options);
+    assert_eq!(svg.matches("<text").count(), 5);
+    assert!(svg.contains(">&lt;</text>"));
+    assert!(svg.contains(">&amp;</text>"));
+    assert!(svg.contains(">&gt;</text>"));
+    // A cluster of several characters is written once
+    assert_eq!(svg.matches(">e\u{301}</text>").count(), 1);
+    assert!(!svg.contains("<path"));
+
+    // Glyphs are where the display list puts them
+    let glyph = text_state
+        .display_list(&mut ctx)
+        .into_iter()
+        .find_map(|command| match command {
+            DrawCommand::GlyphRun { glyphs } => glyphs.get(1).copied(),
+            _ => None,
+        })
+        .unwrap();
+    let second = svg
+        .lines()
+        .filter(|line| line.contains("<text"))
+        .nth(1)
+        .unwrap();
+    let x: f32 = second
+        .split("x=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .and_then(|x| x.parse().ok())
+        .unwrap();
+    // Text keeps the subpixel position the glyph is rasterized with
+    let expected = glyph.position.x + glyph.cache_key.x_bin.as_float();
+    assert!((x - expected).abs() < 0.01, "{x} {expected}");
+    assert!(second.contains(&format!("y=\"{}\"", glyph.position.y)));
+    assert!(second.contains("font-size=\"14\""));
+}
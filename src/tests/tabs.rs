@@ -0,0 +1,116 @@
+use crate::style::{TabStops, TextStyle};
+use crate::tests::mono_style_test;
+use crate::{Action, ActionResult, DrawCommand, Point, TextContext, TextState};
+
+fn glyph_x_after_tab(text_state: &TextState<()>) -> f32 {
+    let run = text_state.buffer().layout_runs().next().unwrap();
+    run.glyphs.last().unwrap().x
+}
+
+/// Explicit tab stops move the glyphs when they're drawn, not in the layout.
+fn drawn_x_after_tab(text_state: &TextState<()>, ctx: &mut TextContext) -> f32 {
+    text_state
+        .display_list(ctx)
+        .into_iter()
+        .find_map(|command| match command {
+            DrawCommand::GlyphRun { glyphs } => Some(glyphs.last().unwrap().position.x),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+pub fn test_tab_width() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text("A", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((400.0, 25.0)));
+    text_state.recalculate(&mut ctx);
+    let mono_width = text_state.first_glyph().unwrap().w;
+
+    text_state.set_text("\tA");
+    text_state.set_style(&mono_style_test().with_tab_width(2));
+    text_state.recalculate(&mut ctx);
+    assert!((glyph_x_after_tab(&text_state) - mono_width * 2.0).abs() < 0.5);
+
+    // Tabs are two spaces wide unless the style asks for more
+    let default_width = TextStyle::default().tab_width;
+    text_state.set_style(&mono_style_test().with_tab_width(default_width));
+    text_state.recalculate(&mut ctx);
+    assert!((glyph_x_after_tab(&text_state) - mono_width * 2.0).abs() < 0.5);
+
+    text_state.set_style(&mono_style_test().with_tab_width(8));
+    text_state.recalculate(&mut ctx);
+    assert!((glyph_x_after_tab(&text_state) - mono_width * 8.0).abs() < 0.5);
+    assert!((text_state.inner_size().x - mono_width * 9.0).abs() < 0.5);
+}
+
+#[test]
+pub fn test_explicit_tab_stops() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text("AB\tC", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test().with_tab_stops(TabStops::new([100.0])));
+    text_state.set_outer_size(&Point::from((400.0, 25.0)));
+    text_state.recalculate(&mut ctx);
+    assert!((drawn_x_after_tab(&text_state, &mut ctx) - 100.0).abs() < 0.5);
+
+    // A tab past the last explicit stop falls back to regular stops
+    text_state.set_text("AB\tC\tD");
+    text_state.recalculate(&mut ctx);
+    let mono_width = text_state.first_glyph().unwrap().w;
+    let x = drawn_x_after_tab(&text_state, &mut ctx);
+    assert!(x > 100.0 + mono_width);
+    assert!(x <= 100.0 + mono_width * 5.0 + 2.0);
+}
+
+#[test]
+pub fn test_insert_tab_without_selection() {
+    let mut ctx = TextContext::default();
+    let mut text_state = TextState::new_with_text("Hello", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((400.0, 25.0)));
+    text_state.is_editable = true;
+    text_state.is_selectable = true;
+    text_state.are_actions_enabled = true;
+    text_state.recalculate(&mut ctx);
+
+    let result = text_state.apply_action(&mut ctx, &Action::InsertTab);
+    assert_eq!(result, ActionResult::TextChanged);
+    assert_eq!(text_state.text(), "\tHello");
+    assert_eq!(text_state.cursor_char_index(), Some(1));
+
+    let result = text_state.apply_action(&mut ctx, &Action::Outdent);
+    assert_eq!(result, ActionResult::TextChanged);
+    assert_eq!(text_state.text(), "Hello");
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+
+    let result = text_state.apply_action(&mut ctx, &Action::Outdent);
+    assert_eq!(result, ActionResult::None);
+}
+
+#[test]
+pub fn test_indent_and_outdent_multiline_selection() {
+    let mut ctx = TextContext::default();
+    let mut text_state =
+        TextState::new_with_text("one\n      two\nthree", &mut ctx.font_system, ());
+    text_state.set_style(&mono_style_test());
+    text_state.set_outer_size(&Point::from((400.0, 100.0)));
+    text_state.is_editable = true;
+    text_state.is_selectable = true;
+    text_state.are_actions_enabled = true;
+    text_state.recalculate(&mut ctx);
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let result = text_state.apply_action(&mut ctx, &Action::InsertTab);
+    assert_eq!(result, ActionResult::TextChanged);
+    assert_eq!(text_state.text(), "\tone\n\t      two\n\tthree");
+    assert_eq!(text_state.selected_text(), Some(text_state.text()));
+
+    text_state.apply_action(&mut ctx, &Action::Outdent);
+    assert_eq!(text_state.text(), "one\n      two\nthree");
+
+    // Spaces are removed up to the tab width
+    text_state.apply_action(&mut ctx, &Action::Outdent);
+    assert_eq!(text_state.text(), "one\n    two\nthree");
+    assert_eq!(text_state.selected_text(), Some(text_state.text()));
+}
@@ -1,7 +1,8 @@
 use crate::math::Size;
 use crate::style::{
-    FontColor, FontFamily, FontSize, HorizontalTextAlignment, LineHeight, TextStyle, TextWrap,
-    VerticalTextAlignment, Weight,
+    FontColor, FontFamily, FontSize, HorizontalTextAlignment, Hyphens, LineHeight, ParagraphStyle,
+    TextDecoration, TextDirection, TextEffects, TextStyle, TextWrap, VerticalTextAlignment, Weight,
+    WhiteSpace, WritingMode,
 };
 use crate::tests::mono_style_test;
 use crate::{Action, Point, TextContext, TextState};
@@ -19,9 +20,20 @@ fn mono_style_with_alignment(
         horizontal_alignment: h_align,
         vertical_alignment: v_align,
         wrap: Some(TextWrap::NoWrap), // No wrapping to ensure a single line
+        white_space: WhiteSpace::PreWrap,
+        direction: TextDirection::Auto,
+        writing_mode: WritingMode::HorizontalTb,
+        language: None,
+        hyphens: Hyphens::Manual,
+        decoration: TextDecoration::NONE,
+        effects: TextEffects::NONE,
         font_family: FontFamily::Monospace,
         weight: Weight::NORMAL,
         letter_spacing: None,
+        tab_width: TextStyle::DEFAULT.tab_width,
+        tab_stops: None,
+        paragraph: ParagraphStyle::NONE,
+        paragraph_overrides: Default::default(),
     }
 }
 
@@ -37,9 +49,20 @@ fn mono_style_with_wrap(
         horizontal_alignment: h_align,
         vertical_alignment: v_align,
         wrap,
+        white_space: WhiteSpace::PreWrap,
+        direction: TextDirection::Auto,
+        writing_mode: WritingMode::HorizontalTb,
+        language: None,
+        hyphens: Hyphens::Manual,
+        decoration: TextDecoration::NONE,
+        effects: TextEffects::NONE,
         font_family: FontFamily::Monospace,
         weight: Weight::NORMAL,
         letter_spacing: None,
+        tab_width: TextStyle::DEFAULT.tab_width,
+        tab_stops: None,
+        paragraph: ParagraphStyle::NONE,
+        paragraph_overrides: Default::default(),
     }
 }
 
@@ -0,0 +1,131 @@
+use crate::state::{AlphaMode, PixelFormat};
+use crate::style::{VerticalTextAlignment, WritingMode};
+use crate::tests::{editable_state, mono_style_test};
+use crate::vertical::is_upright;
+use crate::{Action, Point, TextContext, TextState};
+
+#[test]
+pub fn test_vertical_layout_size_and_caret() {
+    let mut ctx = TextContext::default();
+    let mut horizontal = TextState::new_with_text("abcd", &mut ctx.font_system, ());
+    horizontal.set_style(&mono_style_test());
+    horizontal.set_outer_size(&Point::from((400.0, 100.0)));
+    horizontal.recalculate(&mut ctx);
+    let mono_width = horizontal.first_glyph().unwrap().w;
+    let line_height = mono_style_test().line_height_pt();
+
+    let mut text_state = editable_state(
+        &mut ctx,
+        "abcd",
+        (100.0, 400.0),
+        &mono_style_test().with_writing_mode(WritingMode::VerticalRl),
+    );
+    let inner_size = text_state.inner_size();
+    assert_eq!(inner_size.x, horizontal.inner_size().y);
+    assert_eq!(inner_size.y, horizontal.inner_size().x);
+
+    // The first column is at the right edge, and the text runs down
+    text_state.handle_press(
+        &mut ctx,
+        Point::new(100.0 - line_height / 2.0, mono_width * 1.75),
+    );
+    assert_eq!(text_state.cursor_char_index(), Some(2));
+    let caret = text_state.caret_position_relative().unwrap();
+    assert!((caret.x - (100.0 - line_height)).abs() < 1.0);
+    assert!((caret.y - mono_width * 2.0).abs() < 1.0);
+}
+
+#[test]
+pub fn test_vertical_columns_selection_and_scroll() {
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "ab\ncd",
+        (100.0, 400.0),
+        &mono_style_test().with_writing_mode(WritingMode::VerticalRl),
+    );
+
+    text_state.handle_press(&mut ctx, Point::new(100.0 - line_height * 1.5, 1.0));
+    assert_eq!(text_state.cursor_char_index(), Some(3));
+
+    // Arrow keys move on screen: right goes to the previous column, down goes along the column
+    text_state.apply_action(&mut ctx, &Action::MoveCursorRight);
+    assert_eq!(text_state.cursor_char_index(), Some(0));
+    text_state.apply_action(&mut ctx, &Action::MoveCursorDown);
+    assert_eq!(text_state.cursor_char_index(), Some(1));
+    text_state.apply_action(&mut ctx, &Action::MoveCursorLeft);
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let lines = text_state.selection().lines();
+    assert_eq!(lines.len(), 2);
+    assert!((lines[0].start_x_pt.unwrap() - (100.0 - line_height)).abs() < 1.0);
+    assert!((lines[0].end_x_pt.unwrap() - 100.0).abs() < 1.0);
+    assert!(lines[0].start_y_pt.unwrap().abs() < 1.0);
+    assert!((lines[1].end_x_pt.unwrap() - (100.0 - line_height)).abs() < 1.0);
+
+    // Scrolling across the columns uses the horizontal axis
+    let text = (0..20)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut text_state = editable_state(
+        &mut ctx,
+        &text,
+        (60.0, 100.0),
+        &mono_style_test().with_writing_mode(WritingMode::VerticalRl),
+    );
+    text_state.set_style(
+        &mono_style_test()
+            .with_writing_mode(WritingMode::VerticalRl)
+            .with_vertical_alignment(VerticalTextAlignment::None),
+    );
+    text_state.recalculate(&mut ctx);
+    assert!((text_state.inner_size().x - line_height * 20.0).abs() < 0.5);
+    text_state.set_absolute_scroll(Point::new(30.0, 0.0));
+    let scroll = text_state.absolute_scroll();
+    assert!((scroll.x - 30.0).abs() < 0.5);
+    assert!(scroll.y.abs() < 0.5);
+}
+
+#[test]
+pub fn test_vertical_rasterization() {
+    assert!(is_upright('あ'));
+    assert!(is_upright('漢'));
+    assert!(is_upright('한'));
+    assert!(!is_upright('a'));
+    assert!(!is_upright('ー'));
+    // Brackets are rotated unless the font has vertical forms for them
+    assert!(is_upright('。'));
+    assert!(is_upright('〒'));
+    assert!(!is_upright('「'));
+    assert!(!is_upright('】'));
+    assert!(!is_upright('〔'));
+    assert!(!is_upright('（'));
+
+    let mut ctx = TextContext::default();
+    let line_height = mono_style_test().line_height_pt();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "ab",
+        (100.0, 100.0),
+        &mono_style_test().with_writing_mode(WritingMode::VerticalRl),
+    );
+    assert!(text_state.rasterize_into_texture(
+        &mut ctx,
+        AlphaMode::Unmultiplied,
+        PixelFormat::Rgba8
+    ));
+
+    // All ink is in the rightmost column
+    let texture = text_state.rasterized_texture();
+    let ink_columns: Vec<u32> = (0..texture.height)
+        .flat_map(|y| (0..texture.width).map(move |x| (x, y)))
+        .filter(|(x, y)| texture.pixels[(y * texture.width + x) as usize * 4 + 3] > 0)
+        .map(|(x, _)| x)
+        .collect();
+    assert!(!ink_columns.is_empty());
+    let leftmost = *ink_columns.iter().min().unwrap();
+    assert!(leftmost as f32 >= 100.0 - line_height - 1.0);
+}
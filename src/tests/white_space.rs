@@ -0,0 +1,184 @@
+use crate::display_text::DisplayText;
+use crate::style::{TextDirection, TextWrap, WhiteSpace};
+use crate::tests::{editable_state, mono_style_test};
+use crate::{Action, ActionResult, Point, TextContext};
+
+#[test]
+pub fn test_display_text_mapping() {
+    assert_eq!(
+        DisplayText::new("a  b", WhiteSpace::Pre, TextDirection::Auto, None),
+        None
+    );
+    let display_text =
+        DisplayText::new("a b", WhiteSpace::Normal, TextDirection::Auto, None).unwrap();
+    assert_eq!(display_text.text(), "a b");
+
+    let display_text =
+        DisplayText::new("a \t\n b\nc", WhiteSpace::Normal, TextDirection::Auto, None).unwrap();
+    assert_eq!(display_text.text(), "a b c");
+    assert_eq!(display_text.to_original(2), 5);
+    assert_eq!(display_text.to_original(5), 8);
+    // Offsets inside a collapsed sequence map to the space it collapsed into
+    assert_eq!(display_text.to_display(3), 1);
+    assert_eq!(display_text.to_display(5), 2);
+
+    let display_text =
+        DisplayText::new("a   b\n  c", WhiteSpace::PreLine, TextDirection::Auto, None).unwrap();
+    assert_eq!(display_text.text(), "a b\nc");
+}
+
+#[test]
+pub fn test_collapsing_removes_white_space_at_line_edges() {
+    let display_text =
+        DisplayText::new("  a  b \n ", WhiteSpace::Normal, TextDirection::Auto, None).unwrap();
+    assert_eq!(display_text.text(), "a b");
+    // Removed white space maps to where it would have been
+    assert_eq!(display_text.to_display(1), 0);
+    assert_eq!(display_text.to_original(0), 2);
+    assert_eq!(display_text.to_display(8), 3);
+    assert_eq!(display_text.to_original(3), 9);
+
+    let display_text = DisplayText::new(
+        " a  \n\t b \n",
+        WhiteSpace::PreLine,
+        TextDirection::Auto,
+        None,
+    )
+    .unwrap();
+    assert_eq!(display_text.text(), "a\nb\n");
+    assert_eq!(display_text.to_original(1), 4);
+    assert_eq!(display_text.to_display(6), 2);
+}
+
+#[test]
+pub fn test_edits_update_the_display_text_like_a_rebuild() {
+    let modes = [
+        (WhiteSpace::Normal, TextDirection::Auto),
+        (WhiteSpace::NoWrap, TextDirection::Rtl),
+        (WhiteSpace::PreLine, TextDirection::Ltr),
+        (WhiteSpace::PreWrap, TextDirection::Rtl),
+    ];
+    // (position, removed bytes, inserted text)
+    let edits = [
+        (0, 0, "  "),
+        (2, 0, "ab"),
+        (4, 0, " \n "),
+        (3, 2, ""),
+        (0, 3, ""),
+        (2, 0, "\n\n"),
+        (1, 1, "x y"),
+        (6, 0, "\t"),
+        (0, 0, "\n"),
+    ];
+    for (white_space, direction) in modes {
+        let mut text = String::from("one  two\n three ");
+        let mut display_text = DisplayText::new(&text, white_space, direction, None).unwrap();
+        for (position, removed, inserted) in edits {
+            text.replace_range(position..position + removed, inserted);
+            display_text.replace(&text, position..position + inserted.len(), removed);
+            let rebuilt = DisplayText::new(&text, white_space, direction, None);
+            assert_eq!(
+                Some(&display_text),
+                rebuilt.as_ref(),
+                "{white_space:?} {text:?}"
+            );
+            for (line_index, line) in display_text.text().split('\n').enumerate() {
+                assert_eq!(
+                    display_text.line_starts_with_mark(line_index),
+                    direction.mark().is_some_and(|mark| line.starts_with(mark)),
+                    "{white_space:?} {text:?} {line_index}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_normal_collapses_white_space_and_line_breaks() {
+    let mut ctx = TextContext::default();
+    let collapsed = editable_state(
+        &mut ctx,
+        "Hello world",
+        (400.0, 100.0),
+        &mono_style_test().with_white_space(WhiteSpace::Pre),
+    );
+    let text = "Hello   \n  world";
+    let text_state = editable_state(
+        &mut ctx,
+        text,
+        (400.0, 100.0),
+        &mono_style_test().with_white_space(WhiteSpace::Normal),
+    );
+
+    assert_eq!(text_state.text(), text);
+    assert_eq!(text_state.inner_size(), collapsed.inner_size());
+
+    let text_state = editable_state(
+        &mut ctx,
+        text,
+        (400.0, 100.0),
+        &mono_style_test().with_white_space(WhiteSpace::PreLine),
+    );
+    let line_height = collapsed.inner_size().y;
+    assert!((text_state.inner_size().y - line_height * 2.0).abs() < 0.5);
+}
+
+#[test]
+pub fn test_pre_and_no_wrap_disable_wrapping() {
+    let mut ctx = TextContext::default();
+    let text = "aaaa bbbb cccc dddd";
+    for (white_space, lines) in [
+        (WhiteSpace::PreWrap, 2.0),
+        (WhiteSpace::Pre, 1.0),
+        (WhiteSpace::NoWrap, 1.0),
+    ] {
+        let mut text_state = editable_state(
+            &mut ctx,
+            text,
+            (400.0, 100.0),
+            &mono_style_test().with_white_space(white_space),
+        );
+        text_state.set_style(
+            &mono_style_test()
+                .with_wrap(TextWrap::Wrap)
+                .with_white_space(white_space),
+        );
+        text_state.set_outer_size(&Point::from((100.0, 100.0)));
+        text_state.recalculate(&mut ctx);
+        let line_height = mono_style_test().line_height_pt();
+        assert!(text_state.inner_size().y >= line_height * lines);
+        assert!(text_state.inner_size().y < line_height * (lines + 1.0));
+    }
+}
+
+#[test]
+pub fn test_editing_maps_to_original_text() {
+    let mut ctx = TextContext::default();
+    let mut text_state = editable_state(
+        &mut ctx,
+        "a   b",
+        (400.0, 100.0),
+        &mono_style_test().with_white_space(WhiteSpace::Normal),
+    );
+    let mono_width = text_state.first_glyph().unwrap().w;
+
+    // "b" is the third glyph on screen, but the fifth character of the text
+    text_state.handle_press(&mut ctx, Point::new(mono_width * 2.25, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(4));
+
+    text_state.apply_action(&mut ctx, &Action::InsertChar("X".into()));
+    assert_eq!(text_state.text(), "a   Xb");
+    assert_eq!(text_state.cursor_char_index(), Some(5));
+
+    // Typing a space next to another one doesn't move the caret on screen
+    text_state.handle_press(&mut ctx, Point::new(mono_width * 1.25, 5.0));
+    assert_eq!(text_state.cursor_char_index(), Some(1));
+    let caret_x = text_state.caret_position_relative().unwrap().x;
+    text_state.apply_action(&mut ctx, &Action::InsertChar(" ".into()));
+    assert_eq!(text_state.text(), "a    Xb");
+    assert!((text_state.caret_position_relative().unwrap().x - caret_x).abs() < 0.5);
+
+    text_state.apply_action(&mut ctx, &Action::SelectAll);
+    let result = text_state.apply_action(&mut ctx, &Action::CopySelectedText);
+    assert_eq!(result, ActionResult::TextCopied("a    Xb".to_string()));
+}
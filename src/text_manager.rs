@@ -3,8 +3,14 @@
 //! This module provides high-level management of multiple text states, font loading,
 //! and resource tracking for text rendering systems.
 
+use crate::atlas::{GlyphAtlas, GlyphQuad};
+use crate::color_glyph::ColorGlyphCache;
+use crate::damage::DirtyRect;
+use crate::distance_field::DistanceFieldCache;
 use crate::font_family_query::FontFamilyCache;
-use crate::state::{AlphaMode, TextState};
+use crate::gamma::GammaCorrection;
+use crate::state::{AlphaMode, PixelFormat, TextState};
+use crate::subpixel::SubpixelCache;
 use crate::Id;
 use ahash::{HashMap, HashSet, HashSetExt};
 use cosmic_text::{fontdb, FontSystem, SwashCache};
@@ -25,6 +31,19 @@ pub struct TextContext {
     pub usage_tracker: TextUsageTracker,
     /// Cache for resolved font family queries.
     pub font_family_cache: FontFamilyCache,
+    /// Cache for glyphs rendered with subpixel antialiasing.
+    pub subpixel_cache: SubpixelCache,
+    /// Cache for signed distance field glyphs.
+    pub distance_field_cache: DistanceFieldCache,
+    /// Cache for color glyph images, such as emoji.
+    pub color_glyph_cache: ColorGlyphCache,
+    /// Gamma and contrast correction of glyph coverage in rasterized textures.
+    pub gamma_correction: GammaCorrection,
+    /// Incremented whenever fonts are loaded with [`TextContext::load_fonts`] or
+    /// [`TextContext::load_fonts_from_bytes`]. Increment it after changing the font database
+    /// of [`TextContext::font_system`] directly, so that the per-thread copies of it used by
+    /// parallel rasterization are refreshed.
+    pub font_generation: u64,
 }
 
 impl Default for TextContext {
@@ -36,6 +55,11 @@ impl Default for TextContext {
             scale_factor: 1.0,
             usage_tracker: TextUsageTracker::new(),
             font_family_cache: FontFamilyCache::new(),
+            subpixel_cache: SubpixelCache::new(),
+            distance_field_cache: DistanceFieldCache::new(),
+            color_glyph_cache: ColorGlyphCache::new(),
+            gamma_correction: GammaCorrection::NONE,
+            font_generation: 0,
         }
     }
 }
@@ -54,6 +78,9 @@ pub struct TextManager<TMetadata = ()> {
     pub text_states: HashMap<Id, TextState<TMetadata>>,
     /// Shared context for text rendering operations.
     pub text_context: TextContext,
+    /// Per-thread contexts for parallel rasterization.
+    #[cfg(feature = "parallel")]
+    worker_contexts: Vec<TextContext>,
 }
 
 impl<TMetadata> TextManager<TMetadata> {
@@ -69,6 +96,8 @@ impl<TMetadata> TextManager<TMetadata> {
         Self {
             text_states: HashMap::default(),
             text_context: TextContext::default(),
+            #[cfg(feature = "parallel")]
+            worker_contexts: Vec::new(),
         }
     }
 
@@ -199,33 +228,194 @@ impl<TMetadata> TextManager<TMetadata> {
 
     /// Rasterizes all text states into CPU-side RGBA textures and stores them in the states.
     ///
-    /// This will recalculate the shaping/layout if needed prior to rasterization.
-    /// Currently runs on a single thread; the API is designed to be easily parallelized later.
+    /// This will recalculate the shaping/layout if needed prior to rasterization. Only the lines
+    /// that changed are drawn again, and the returned infos tell which part of each texture
+    /// needs to be uploaded.
+    /// Runs on a single thread, see `rasterize_all_textures_parallel` with the `parallel`
+    /// feature for a multithreaded version.
     pub fn rasterize_all_textures(&mut self, alpha_mode: AlphaMode) -> Vec<RasterizedTextureInfo> {
-        // In the future this can be parallelized by splitting the states into chunks and
-        // creating per-thread SwashCache/FontSystem references as needed.
-        let mut changes = Vec::new();
+        self.rasterize_all_textures_with_format(alpha_mode, PixelFormat::Rgba8)
+    }
+
+    /// Rasterizes all text states into CPU-side textures with the given pixel format and stores
+    /// them in the states, see [`TextManager::rasterize_all_textures`].
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{AlphaMode, PixelFormat, TextManager};
+    ///
+    /// let mut manager: TextManager<()> = TextManager::new();
+    /// // Coverage masks to be tinted on the GPU
+    /// let changes =
+    ///     manager.rasterize_all_textures_with_format(AlphaMode::Premultiplied, PixelFormat::A8);
+    /// ```
+    pub fn rasterize_all_textures_with_format(
+        &mut self,
+        alpha_mode: AlphaMode,
+        format: PixelFormat,
+    ) -> Vec<RasterizedTextureInfo> {
+        self.text_states
+            .iter_mut()
+            .filter_map(|(id, state)| {
+                rasterize_state(*id, state, &mut self.text_context, alpha_mode, format)
+            })
+            .collect()
+    }
+
+    /// Describes all text states as glyph quads that sample from a shared glyph atlas, see
+    /// [`TextState::glyph_quads`]. All states can then be drawn with one atlas texture.
+    ///
+    /// This will recalculate the shaping/layout if needed first.
+    ///
+    /// # Arguments
+    /// * `atlas` - The atlas that stores the glyph bitmaps
+    ///
+    /// # Returns
+    /// The glyph quads of each text state
+    pub fn glyph_quads_all(&mut self, atlas: &mut impl GlyphAtlas) -> Vec<(Id, Vec<GlyphQuad>)> {
+        let mut quads = Vec::with_capacity(self.text_states.len());
         for (id, state) in self.text_states.iter_mut() {
-            let old_w = state.rasterized_texture().width;
-            let old_h = state.rasterized_texture().height;
-            // Ensure the buffer is up to date
             state.recalculate(&mut self.text_context);
-            // Rasterize into the state's texture storage
-            let rerasterized = state.rasterize_into_texture(&mut self.text_context, alpha_mode);
-            if rerasterized {
-                let new_w = state.rasterized_texture().width;
-                let new_h = state.rasterized_texture().height;
-                let resized = new_w != old_w || new_h != old_h;
-                changes.push(RasterizedTextureInfo {
-                    id: *id,
-                    width: new_w,
-                    height: new_h,
-                    resized,
-                });
-            }
+            quads.push((*id, state.glyph_quads(&mut self.text_context, atlas)));
         }
-        changes
+        quads
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<TMetadata: Send> TextManager<TMetadata> {
+    /// Rasterizes all text states like [`TextManager::rasterize_all_textures_with_format`],
+    /// but spreads the states over all available threads. The textures and returned infos are
+    /// identical to the single-threaded version.
+    ///
+    /// Every thread shapes and rasterizes with its own copy of the font database and its own
+    /// glyph caches, which are kept between calls. The copies are refreshed when fonts are
+    /// loaded into [`TextManager::text_context`], see [`TextContext::font_generation`]. Custom
+    /// font fallback of the shared font system isn't copied.
+    ///
+    /// # Examples
+    /// ```
+    /// use protextinator::{AlphaMode, PixelFormat, TextManager};
+    ///
+    /// let mut manager: TextManager<()> = TextManager::new();
+    /// let changes =
+    ///     manager.rasterize_all_textures_parallel(AlphaMode::Premultiplied, PixelFormat::Rgba8);
+    /// ```
+    pub fn rasterize_all_textures_parallel(
+        &mut self,
+        alpha_mode: AlphaMode,
+        format: PixelFormat,
+    ) -> Vec<RasterizedTextureInfo> {
+        let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+        self.rasterize_all_textures_on_threads(alpha_mode, format, threads)
+    }
+
+    /// Rasterizes all text states on up to the given number of threads.
+    pub(crate) fn rasterize_all_textures_on_threads(
+        &mut self,
+        alpha_mode: AlphaMode,
+        format: PixelFormat,
+        threads: usize,
+    ) -> Vec<RasterizedTextureInfo> {
+        let threads = threads.min(self.text_states.len());
+        if threads <= 1 {
+            return self.rasterize_all_textures_with_format(alpha_mode, format);
+        }
+        self.prepare_worker_contexts(threads);
+
+        // Contiguous chunks keep the infos in the same order as in the single-threaded version
+        let mut states: Vec<_> = self.text_states.iter_mut().collect();
+        let chunk_size = states.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = states
+                .chunks_mut(chunk_size)
+                .zip(self.worker_contexts.iter_mut())
+                .map(|(chunk, ctx)| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter_mut()
+                            .filter_map(|(id, state)| {
+                                rasterize_state(**id, state, ctx, alpha_mode, format)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    }
+
+    /// Makes sure there are enough per-thread contexts with the current fonts and scale factor.
+    fn prepare_worker_contexts(&mut self, count: usize) {
+        let font_system = &self.text_context.font_system;
+        let fonts_changed = self.worker_contexts.first().is_some_and(|ctx| {
+            ctx.font_generation != self.text_context.font_generation
+                || ctx.font_system.locale() != font_system.locale()
+        });
+        if fonts_changed {
+            self.worker_contexts.clear();
+        }
+        while self.worker_contexts.len() < count {
+            self.worker_contexts.push(TextContext {
+                font_system: FontSystem::new_with_locale_and_db(
+                    font_system.locale().to_string(),
+                    font_system.db().clone(),
+                ),
+                swash_cache: SwashCache::new(),
+                scale_factor: self.text_context.scale_factor,
+                usage_tracker: TextUsageTracker::new(),
+                font_family_cache: FontFamilyCache::new(),
+                subpixel_cache: SubpixelCache::new(),
+                distance_field_cache: DistanceFieldCache::new(),
+                color_glyph_cache: ColorGlyphCache::new(),
+                gamma_correction: self.text_context.gamma_correction,
+                font_generation: self.text_context.font_generation,
+            });
+        }
+        for ctx in &mut self.worker_contexts {
+            ctx.scale_factor = self.text_context.scale_factor;
+            ctx.gamma_correction = self.text_context.gamma_correction;
+        }
+    }
+}
+
+/// Brings the layout of the state up to date and rasterizes it, returning the info if the
+/// texture changed.
+fn rasterize_state<TMetadata>(
+    id: Id,
+    state: &mut TextState<TMetadata>,
+    ctx: &mut TextContext,
+    alpha_mode: AlphaMode,
+    format: PixelFormat,
+) -> Option<RasterizedTextureInfo> {
+    let old_w = state.rasterized_texture().width;
+    let old_h = state.rasterized_texture().height;
+    // Ensure the buffer is up to date
+    state.recalculate(ctx);
+    // Rasterize into the state's texture storage
+    if !state.rasterize_into_texture(ctx, alpha_mode, format) {
+        return None;
     }
+    let new_w = state.rasterized_texture().width;
+    let new_h = state.rasterized_texture().height;
+    Some(RasterizedTextureInfo {
+        id,
+        width: new_w,
+        height: new_h,
+        offset_x: state.rasterized_texture().offset_x,
+        offset_y: state.rasterized_texture().offset_y,
+        subpixel_offset_x: state.rasterized_texture().subpixel_offset_x,
+        subpixel_offset_y: state.rasterized_texture().subpixel_offset_y,
+        resized: new_w != old_w || new_h != old_h,
+        dirty_rect: state.rasterized_texture().dirty_rect,
+    })
 }
 
 /// Information about a text state's rasterized texture after `rasterize_all_textures`.
@@ -234,8 +424,18 @@ pub struct RasterizedTextureInfo {
     pub id: Id,
     pub width: u32,
     pub height: u32,
+    /// Position of the texture relative to the text area, see
+    /// [`crate::RasterizedTexture::offset_x`].
+    pub offset_x: i32,
+    pub offset_y: i32,
+    /// Fraction of a device pixel to move the texture by, see
+    /// [`crate::RasterizedTexture::subpixel_offset_x`].
+    pub subpixel_offset_x: f32,
+    pub subpixel_offset_y: f32,
     /// True if the texture dimensions changed compared to the previous rasterization.
     pub resized: bool,
+    /// Part of the texture that changed. Covers the whole texture if it was resized.
+    pub dirty_rect: DirtyRect,
 }
 
 impl TextContext {
@@ -249,6 +449,7 @@ impl TextContext {
         for source in fonts {
             db.load_font_source(source);
         }
+        self.font_generation += 1;
     }
 
     /// Loads fonts from byte slices into the font database.
@@ -264,6 +465,7 @@ impl TextContext {
             let source = fontdb::Source::Binary(Arc::new(font_bytes.to_vec()));
             db.load_font_source(source);
         }
+        self.font_generation += 1;
     }
 }
 
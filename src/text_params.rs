@@ -1,13 +1,19 @@
+use crate::byte_cursor::{byte_offset_cursor_to_byte_offset, char_byte_offset_to_cursor};
+use crate::display_text::DisplayText;
 use crate::math::Size;
 use crate::state::SIZE_EPSILON;
 use crate::style::TextStyle;
-use cosmic_text::Metrics;
+use crate::vertical::transpose;
+use cosmic_text::{Affinity, Cursor, Metrics};
+use std::ops::Range;
 
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct TextParams {
     size: Size,
     style: TextStyle,
     text: String,
+    // Collapsed copy of the text if the white space mode changes it
+    display_text: Option<DisplayText>,
     metadata: usize,
 
     // Device scale factor; 1.0 == logical pixels
@@ -25,6 +31,7 @@ impl TextParams {
             size,
             style,
             text: "".to_string(),
+            display_text: None,
             metadata,
             scale_factor: 1.0,
 
@@ -42,6 +49,16 @@ impl TextParams {
         self.size
     }
 
+    /// Size of the text area in the layout frame, which is rotated in vertical writing mode.
+    #[inline(always)]
+    pub fn layout_size(&self) -> Size {
+        if self.style.writing_mode.is_vertical() {
+            transpose(self.size)
+        } else {
+            self.size
+        }
+    }
+
     #[inline(always)]
     pub fn style(&self) -> &TextStyle {
         &self.style
@@ -64,10 +81,94 @@ impl TextParams {
         &self.text
     }
 
+    /// The text to shape, with white space collapsed according to the style.
+    #[inline(always)]
+    pub fn display_text(&self) -> &str {
+        match &self.display_text {
+            Some(display_text) => display_text.text(),
+            None => &self.text,
+        }
+    }
+
+    /// Returns the buffer cursor for a byte offset in the text.
+    pub fn cursor_for_byte_offset(&self, byte_offset: usize) -> Option<Cursor> {
+        match &self.display_text {
+            Some(display_text) => char_byte_offset_to_cursor(
+                display_text.text(),
+                display_text.to_display(byte_offset),
+            ),
+            None => char_byte_offset_to_cursor(&self.text, byte_offset),
+        }
+    }
+
+    /// Returns the byte offset in the text for a buffer cursor.
+    pub fn byte_offset_for_cursor(&self, cursor: Cursor) -> Option<usize> {
+        match &self.display_text {
+            Some(display_text) => byte_offset_cursor_to_byte_offset(display_text.text(), cursor)
+                .map(|offset| display_text.to_original(offset)),
+            None => byte_offset_cursor_to_byte_offset(&self.text, cursor),
+        }
+    }
+
+    /// Moves a cursor placed before the direction mark at the start of a line to after it, as
+    /// the mark has no position of its own in the original text.
+    pub fn skip_direction_mark(&self, cursor: Cursor) -> Cursor {
+        let Some(mark) = self.style.direction.mark() else {
+            return cursor;
+        };
+        let line_starts_with_mark = self
+            .display_text
+            .as_ref()
+            .is_some_and(|display_text| display_text.line_starts_with_mark(cursor.line));
+        if line_starts_with_mark && cursor.index <= mark.len_utf8() {
+            // The caret is drawn after the mark, on the side the paragraph starts at
+            Cursor::new_with_affinity(cursor.line, mark.len_utf8(), Affinity::Before)
+        } else {
+            cursor
+        }
+    }
+
+    fn update_display_text(&mut self) {
+        let white_space = self.style.white_space;
+        let text = if white_space.collapses_line_breaks() {
+            self.original_text()
+        } else {
+            &self.text
+        };
+        self.display_text = DisplayText::new(
+            text,
+            white_space,
+            self.style.direction,
+            self.style.hyphenation_language(),
+        );
+    }
+
+    /// Updates the display text after `removed` bytes of the text were replaced with the
+    /// `inserted` range of the text.
+    fn replace_in_display_text(&mut self, inserted: Range<usize>, removed: usize) {
+        let Some(display_text) = &mut self.display_text else {
+            return;
+        };
+        let text = if self.style.white_space.collapses_line_breaks()
+            && self.line_terminator_has_been_added
+        {
+            &self.text[..self.text.len().saturating_sub(1)]
+        } else {
+            &self.text
+        };
+        if inserted.end <= text.len() {
+            display_text.replace(text, inserted, removed);
+        } else {
+            // The edit touches the line terminator that isn't part of the collapsed text
+            self.update_display_text();
+        }
+    }
+
     #[inline(always)]
     pub fn insert_char(&mut self, index: usize, c: char) {
         if index <= self.text.len() {
             self.text.insert(index, c);
+            self.replace_in_display_text(index..index + c.len_utf8(), 0);
             self.changed = true;
         }
     }
@@ -76,25 +177,16 @@ impl TextParams {
     pub fn insert_str(&mut self, index: usize, s: &str) {
         if index <= self.text.len() {
             self.text.insert_str(index, s);
+            self.replace_in_display_text(index..index + s.len(), 0);
             self.changed = true;
         }
     }
 
-    #[inline(always)]
-    pub fn remove_char(&mut self, index: usize) -> Option<char> {
-        if index < self.text.len() {
-            let char = self.text.remove(index);
-            self.changed = true;
-            Some(char)
-        } else {
-            None
-        }
-    }
-
     #[inline(always)]
     pub fn remove_range(&mut self, start: usize, end: usize) {
         if start < end && end <= self.text.len() {
             self.text.drain(start..end);
+            self.replace_in_display_text(start..start, end - start);
             self.changed = true;
         }
     }
@@ -141,6 +233,7 @@ impl TextParams {
             } else {
                 self.line_terminator_has_been_added = false;
             }
+            self.update_display_text();
             self.changed = true;
         }
     }
@@ -161,7 +254,13 @@ impl TextParams {
             self.font_query_changed = true;
         }
         if &self.style != style {
+            let display_text_changed = self.style.white_space != style.white_space
+                || self.style.direction != style.direction
+                || self.style.hyphenation_language() != style.hyphenation_language();
             self.style = style.clone();
+            if display_text_changed {
+                self.update_display_text();
+            }
             self.changed = true;
         }
     }
@@ -3,6 +3,8 @@
 //! This module contains helper types and utilities used throughout the crate,
 //! including string handling optimizations.
 
+use cosmic_text::skrifa::FontRef;
+use cosmic_text::{fontdb, FontSystem};
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::hash::Hash;
@@ -110,7 +112,17 @@ impl<'de> Deserialize<'de> for ArcCowStr {
 
 #[inline(always)]
 pub fn srgb_to_linear_u8(c: u8) -> f32 {
-    let x = c as f32 / 255.0;
+    srgb_to_linear(c as f32 / 255.0)
+}
+
+#[inline(always)]
+pub fn linear_to_srgb_u8(x: f32) -> u8 {
+    (linear_to_srgb(x) * 255.0 + 0.5).floor() as u8
+}
+
+/// Converts an sRGB channel between 0 and 1 to linear light.
+#[inline(always)]
+pub(crate) fn srgb_to_linear(x: f32) -> f32 {
     if x <= 0.04045 {
         x / 12.92
     } else {
@@ -118,13 +130,128 @@ pub fn srgb_to_linear_u8(c: u8) -> f32 {
     }
 }
 
+/// Converts a channel in linear light to sRGB, clamped between 0 and 1.
 #[inline(always)]
-pub fn linear_to_srgb_u8(x: f32) -> u8 {
+pub(crate) fn linear_to_srgb(x: f32) -> f32 {
     let x = x.clamp(0.0, 1.0);
     let y = if x <= 0.0031308 {
         x * 12.92
     } else {
         1.055 * x.powf(1.0 / 2.4) - 0.055
     };
-    (y.clamp(0.0, 1.0) * 255.0 + 0.5).floor() as u8
+    y.clamp(0.0, 1.0)
+}
+
+/// Blends a color over an RGBA8 pixel with unmultiplied alpha.
+#[inline(always)]
+pub(crate) fn blend_unmultiplied(target: &mut [u8], source: [u8; 4]) {
+    let source_alpha = f32::from(source[3]) / 255.0;
+    let target_alpha = f32::from(target[3]) / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+    if alpha <= 0.0 {
+        return;
+    }
+    for channel in 0..3 {
+        let value = (f32::from(source[channel]) * source_alpha
+            + f32::from(target[channel]) * target_alpha * (1.0 - source_alpha))
+            / alpha;
+        target[channel] = value.round() as u8;
+    }
+    target[3] = (alpha * 255.0).round() as u8;
+}
+
+/// Blends a color over an RGBA8 pixel that is premultiplied in linear space and encoded as
+/// sRGB, as in [`crate::AlphaMode::Premultiplied`].
+#[inline(always)]
+pub(crate) fn blend_premultiplied(target: &mut [u8], source: [u8; 4]) {
+    let source_alpha = f32::from(source[3]) / 255.0;
+    let target_alpha = f32::from(target[3]) / 255.0;
+    for channel in 0..3 {
+        let value = srgb_to_linear_u8(source[channel]) * source_alpha
+            + srgb_to_linear_u8(target[channel]) * (1.0 - source_alpha);
+        target[channel] = linear_to_srgb_u8(value);
+    }
+    target[3] = ((source_alpha + target_alpha * (1.0 - source_alpha)) * 255.0).round() as u8;
+}
+
+/// Blends a color over an RGBA8 pixel with a separate coverage for each color channel, in
+/// linear space, as in [`crate::Antialiasing::Subpixel`].
+#[inline(always)]
+pub(crate) fn blend_subpixel(target: &mut [u8], source: [u8; 4], coverage: [u8; 3]) {
+    let source_alpha = f32::from(source[3]) / 255.0;
+    for channel in 0..3 {
+        let alpha = f32::from(coverage[channel]) / 255.0 * source_alpha;
+        let value = srgb_to_linear_u8(source[channel]) * alpha
+            + srgb_to_linear_u8(target[channel]) * (1.0 - alpha);
+        target[channel] = linear_to_srgb_u8(value);
+    }
+    let alpha = coverage.into_iter().max().unwrap_or(0) as f32 * source_alpha;
+    target[3] = target[3].max(alpha.round() as u8);
+}
+
+/// Median of three channels, which multi-channel distance fields are thresholded by.
+#[inline(always)]
+pub(crate) fn median(a: u8, b: u8, c: u8) -> u8 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+/// Converts the bits of an IEEE 754 half-precision float to a float.
+#[cfg(feature = "png")]
+pub(crate) fn f16_bits_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1F);
+    let mantissa = f32::from(bits & 0x3FF);
+    match exponent {
+        // Zero or subnormal
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1F if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Converts a float to the bits of the nearest IEEE 754 half-precision float.
+pub(crate) fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7F_FFFF;
+    if exponent == 0xFF {
+        // Infinity or NaN
+        let nan_bit = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7C00 | nan_bit;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+    // Number of mantissa bits dropped, which is more for subnormal halves
+    let (shift, mantissa, half) = if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        ((14 - half_exponent) as u32, mantissa | 0x80_0000, 0)
+    } else {
+        (13, mantissa, (half_exponent as u32) << 10)
+    };
+    let mut half = half | (mantissa >> shift);
+    // Round to nearest, ties to even
+    let round_bit = 1 << (shift - 1);
+    if mantissa & round_bit != 0 && mantissa & (3 * round_bit - 1) != 0 {
+        half += 1;
+    }
+    sign | half as u16
+}
+
+/// Calls `f` with the face of the font with the given id and weight, or returns `None` if the
+/// font can't be loaded.
+pub(crate) fn with_font_ref<T>(
+    font_system: &mut FontSystem,
+    font_id: fontdb::ID,
+    weight: fontdb::Weight,
+    f: impl FnOnce(FontRef) -> Option<T>,
+) -> Option<T> {
+    let font = font_system.get_font(font_id, weight)?;
+    let face_index = font_system.db().face(font_id)?.index;
+    f(FontRef::from_index(font.data(), face_index).ok()?)
 }
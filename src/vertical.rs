@@ -0,0 +1,296 @@
+//! Vertical writing mode.
+//!
+//! Vertical text is laid out by cosmic_text as regular horizontal text in a frame rotated by 90
+//! degrees clockwise: layout lines become columns, the layout's x axis runs down the screen, and
+//! the layout's y axis runs from the right edge of the text area to the left. Everything that
+//! depends on the layout keeps working in the layout frame, and positions are converted with
+//! [`VerticalFrame`] where they cross the public API: caret and selection geometry, scroll
+//! positions, hit testing, and rasterization.
+
+use crate::action::Action;
+use crate::math::{Point, Rect, Size};
+use crate::utils::with_font_ref;
+use ahash::{HashMap, HashSet};
+use cosmic_text::skrifa::raw::tables::gsub::{SingleSubst, SubstitutionSubtables};
+use cosmic_text::skrifa::raw::TableProvider;
+use cosmic_text::skrifa::{FontRef, Tag};
+use cosmic_text::{fontdb, FontSystem, LayoutGlyph};
+
+/// Converts positions between the rotated layout frame and the text area.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VerticalFrame {
+    /// Width of the text area, in the same units as the converted positions.
+    pub width: f32,
+}
+
+impl VerticalFrame {
+    pub fn new(width: f32) -> Self {
+        Self { width }
+    }
+
+    /// Converts a point in the layout frame to the text area.
+    pub fn to_area(self, layout: Point) -> Point {
+        Point::new(self.width - layout.y, layout.x)
+    }
+
+    /// Converts a point in the text area to the layout frame.
+    pub fn to_layout(self, area: Point) -> Point {
+        Point::new(area.y, self.width - area.x)
+    }
+
+    /// Converts the top-left corner of a caret in the layout frame to the top-left corner of the
+    /// caret in the text area, where it lies across the column.
+    pub fn caret_to_area(self, caret: Point, line_height: f32) -> Point {
+        self.to_area(Point::new(caret.x, caret.y + line_height))
+    }
+
+    /// Converts a rectangle in the layout frame to the text area.
+    pub fn rect_to_area(self, rect: Rect) -> Rect {
+        Rect::new(
+            Point::new(self.width - rect.max.y, rect.min.x),
+            Point::new(self.width - rect.min.y, rect.max.x),
+        )
+    }
+}
+
+/// Swaps the axes of a size, converting it between the layout frame and the text area.
+pub(crate) fn transpose(size: Size) -> Size {
+    Size::new(size.y, size.x)
+}
+
+/// Maps cursor movement actions to their direction in the layout frame: up and down move along
+/// a column, left moves to the next column, and right moves to the previous one.
+pub(crate) fn layout_action(action: &Action) -> Option<Action> {
+    match action {
+        Action::MoveCursorUp => Some(Action::MoveCursorLeft),
+        Action::MoveCursorDown => Some(Action::MoveCursorRight),
+        Action::MoveCursorLeft => Some(Action::MoveCursorDown),
+        Action::MoveCursorRight => Some(Action::MoveCursorUp),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the character stays upright in vertical text instead of being rotated
+/// sideways.
+///
+/// This is an approximation of the Unicode `Vertical_Orientation` property that covers the CJK
+/// scripts, full-width forms, and emoji. Brackets and the other characters that are
+/// transformed in vertical text (`Tr`) are rotated, unless the font substitutes a vertical form
+/// for them, see [`VerticalAlternates`].
+pub(crate) fn is_upright(character: char) -> bool {
+    matches!(
+        u32::from(character),
+        0x1100..=0x11FF // Hangul Jamo
+            | 0x2E80..=0x2FFF // CJK radicals, Kangxi radicals, ideographic description
+            | 0x3000..=0x3007 // CJK symbols and punctuation, up to the brackets
+            | 0x3012..=0x3013 // Postal mark, geta mark
+            | 0x3020..=0x302F // Postal mark face, Hangzhou numerals, tone marks
+            | 0x3031..=0x309F // Kana repeat marks, Hiragana
+            | 0x30A1..=0x30FB // Katakana
+            | 0x30FD..=0x4DBF // Katakana, Bopomofo, Hangul compatibility, enclosed CJK, Ext A
+            | 0x4E00..=0x9FFF // CJK unified ideographs
+            | 0xA960..=0xA97F // Hangul Jamo extended A
+            | 0xAC00..=0xD7FF // Hangul syllables, Jamo extended B
+            | 0xF900..=0xFAFF // CJK compatibility ideographs
+            | 0xFE10..=0xFE1F // Vertical forms
+            | 0xFE30..=0xFE4F // CJK compatibility forms
+            | 0xFF01..=0xFF07 // Full-width forms, up to the parentheses
+            | 0xFF0A..=0xFF3A // Full-width punctuation, digits, and Latin capitals
+            | 0xFF3C // Full-width reverse solidus
+            | 0xFF3E // Full-width circumflex accent
+            | 0xFF40..=0xFF5A // Full-width grave accent and Latin small letters
+            | 0xFF5C // Full-width vertical line
+            | 0xFFE0..=0xFFE6 // Full-width signs
+            | 0x1F000..=0x1FAFF // Emoji and pictographs
+            | 0x20000..=0x3FFFF // CJK ideographs, extensions B and later
+    )
+}
+
+/// Feature that substitutes the vertical forms of punctuation and brackets, applied to all
+/// vertical text.
+///
+/// `vrt2` isn't applied: its glyphs are rotated versions of proportional glyphs that are
+/// spaced by the vertical metrics of the font, which the horizontal layout doesn't use.
+pub(crate) const VERTICAL_ALTERNATES_FEATURE: [u8; 4] = *b"vert";
+
+/// The glyphs that the `vert` feature of each font substitutes, which are drawn upright
+/// whatever their characters are.
+#[derive(Debug, Default)]
+pub(crate) struct VerticalAlternates {
+    fonts: HashMap<fontdb::ID, HashSet<u16>>,
+}
+
+impl VerticalAlternates {
+    /// Returns `true` if the glyph stays upright in vertical text: its character is upright,
+    /// or the glyph is the vertical form of a character that is rotated otherwise.
+    pub fn is_upright(
+        &mut self,
+        font_system: &mut FontSystem,
+        glyph: &LayoutGlyph,
+        character: char,
+    ) -> bool {
+        is_upright(character)
+            || self
+                .fonts
+                .entry(glyph.font_id)
+                .or_insert_with(|| substituted_glyphs(font_system, glyph))
+                .contains(&glyph.glyph_id)
+    }
+}
+
+/// Reads the `vert` substitutes in the font of the glyph, see [`vertical_substitutes`].
+fn substituted_glyphs(font_system: &mut FontSystem, glyph: &LayoutGlyph) -> HashSet<u16> {
+    with_font_ref(font_system, glyph.font_id, glyph.font_weight, |font| {
+        Some(vertical_substitutes(font))
+    })
+    .unwrap_or_default()
+}
+
+/// Reads the glyphs that the single and alternate substitutions of the `vert` feature in the
+/// font substitute.
+fn vertical_substitutes(font: FontRef) -> HashSet<u16> {
+    let mut glyphs = HashSet::default();
+    let Ok(gsub) = font.gsub() else {
+        return glyphs;
+    };
+    let (Ok(features), Ok(lookups)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return glyphs;
+    };
+    let lookup_indices = features
+        .feature_records()
+        .iter()
+        .filter(|record| record.feature_tag() == Tag::new(&VERTICAL_ALTERNATES_FEATURE))
+        .filter_map(|record| record.feature(features.offset_data()).ok())
+        .flat_map(|feature| {
+            feature
+                .lookup_list_indices()
+                .iter()
+                .map(|index| index.get())
+        });
+    for lookup_index in lookup_indices {
+        let Ok(subtables) = lookups
+            .lookups()
+            .get(usize::from(lookup_index))
+            .and_then(|lookup| lookup.subtables())
+        else {
+            continue;
+        };
+        match subtables {
+            SubstitutionSubtables::Single(subtables) => {
+                for subtable in subtables.iter().flatten() {
+                    match subtable {
+                        SingleSubst::Format1(subtable) => {
+                            let Ok(coverage) = subtable.coverage() else {
+                                continue;
+                            };
+                            // The delta is added modulo 65536
+                            let delta = subtable.delta_glyph_id() as u16;
+                            glyphs.extend(
+                                coverage
+                                    .iter()
+                                    .map(|glyph| glyph.to_u16().wrapping_add(delta)),
+                            );
+                        }
+                        SingleSubst::Format2(subtable) => glyphs.extend(
+                            subtable
+                                .substitute_glyph_ids()
+                                .iter()
+                                .map(|glyph| glyph.get().to_u16()),
+                        ),
+                    }
+                }
+            }
+            SubstitutionSubtables::Alternate(subtables) => {
+                for subtable in subtables.iter().flatten() {
+                    for set in subtable.alternate_sets().iter().flatten() {
+                        glyphs.extend(
+                            set.alternate_glyph_ids()
+                                .iter()
+                                .map(|glyph| glyph.get().to_u16()),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    glyphs
+}
+
+/// Maps the pixels of a glyph from the layout frame to the text area.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum GlyphPlacement {
+    /// The glyph keeps its orientation and is centered in its cell in the column.
+    Upright { dx: f32, dy: f32 },
+    /// The glyph is rotated together with the layout.
+    Sideways(VerticalFrame),
+}
+
+impl GlyphPlacement {
+    /// Placement of an upright or a sideways glyph, with the center of its cell in the layout
+    /// frame given in DEVICE pixels. The frame must be in DEVICE pixels as well.
+    pub fn new(frame: VerticalFrame, upright: bool, cell_center: Point) -> Self {
+        if upright {
+            let area_center = frame.to_area(cell_center);
+            Self::Upright {
+                dx: area_center.x - cell_center.x,
+                dy: area_center.y - cell_center.y,
+            }
+        } else {
+            Self::Sideways(frame)
+        }
+    }
+
+    /// Position in the text area of the glyph pixel at the given layout position.
+    pub fn pixel(&self, x: i32, y: i32) -> (i32, i32) {
+        match *self {
+            Self::Upright { dx, dy } => (
+                (x as f32 + dx).round() as i32,
+                (y as f32 + dy).round() as i32,
+            ),
+            // The pixel covers `x..x + 1` and `y..y + 1`, which rotate to
+            // `width - y - 1..width - y` and `x..x + 1`
+            Self::Sideways(frame) => ((frame.width - y as f32 - 1.0).round() as i32, x),
+        }
+    }
+
+    /// Position in the text area of the glyph origin at the given layout position.
+    pub fn origin(&self, x: i32, y: i32) -> (i32, i32) {
+        match *self {
+            Self::Upright { .. } => self.pixel(x, y),
+            Self::Sideways(frame) => ((frame.width - y as f32).round() as i32, x),
+        }
+    }
+
+    /// Position in the text area of a point of the outline of a glyph whose origin is at the
+    /// given layout position. The point is at a layout position as well.
+    pub fn outline_point(&self, origin: (i32, i32), point: Point) -> Point {
+        match *self {
+            // Outlines move with the bitmap, which is placed at a whole pixel
+            Self::Upright { .. } => {
+                let (x, y) = self.origin(origin.0, origin.1);
+                Point::new(
+                    point.x + (x - origin.0) as f32,
+                    point.y + (y - origin.1) as f32,
+                )
+            }
+            Self::Sideways(frame) => Point::new(frame.width - point.y, point.x),
+        }
+    }
+
+    /// Position in the text area of the top-left corner of a glyph bitmap whose top-left corner
+    /// is at the given layout position, and whether the bitmap is rotated.
+    pub fn bitmap_origin(&self, x: i32, y: i32, height: u32) -> (i32, i32, bool) {
+        match self {
+            Self::Upright { .. } => {
+                let (x, y) = self.pixel(x, y);
+                (x, y, false)
+            }
+            // The bottom row of the bitmap becomes its leftmost column
+            Self::Sideways(_) => {
+                let (x, y) = self.pixel(x, y + height as i32 - 1);
+                (x, y, true)
+            }
+        }
+    }
+}